@@ -30,6 +30,16 @@ Table ref_type {
   type_fmm tinytext
 }
 
+Table ref_value {
+  reference_set varchar(64) [not null]
+  id int [not null]
+  value text [not null]
+
+  indexes {
+    (reference_set, id) [pk]
+  }
+}
+
 Table ddc_pgm {
   id int [pk, increment]
   program text [not null]
@@ -146,6 +156,8 @@ Ref: sdn.partysubtypeid > ref_type.id
 Table aircraft_operator {
   id int [pk]
   operator text [not null]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -161,6 +173,8 @@ Table aircraft_operator_sdn {
 Table former_vessel_flag {
   id int [pk, increment]
   value text
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -188,6 +202,39 @@ Table sdn_program {
 
 // Ref: sanction.id > sdn.fixed_ref
 
+// Evenements d'une sanction (designation, modification, radiation)
+Table sanction_event {
+  id int [pk]
+  event_type int [not null, note: "ref_value EntryEventType"]
+  event_date date [not null]
+  legal_basis int [note: "ref_value LegalBasis"]
+  topmaj tinytext [not null]
+}
+
+Table sanction_event_sdn {
+  event_id int [ref: > sanction_event.id]
+  sdn_id int [ref: > sdn.fixed_ref]
+  indexes {
+    (event_id, sdn_id) [pk]
+  }
+}
+
+// Mesures d'une sanction
+Table sanction_measure {
+  id int [pk]
+  sanctions_type int [not null, note: "ref_value SanctionsType"]
+  comment text
+  topmaj tinytext [not null]
+}
+
+Table sanction_measure_sdn {
+  measure_id int [ref: > sanction_measure.id]
+  sdn_id int [ref: > sdn.fixed_ref]
+  indexes {
+    (measure_id, sdn_id) [pk]
+  }
+}
+
 // Adresse d'un SDN
 Table address {
   id int [pk]
@@ -198,6 +245,8 @@ Table address {
   region text
   state text
   is_primary bool [not null]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -209,10 +258,31 @@ Table address_sdn {
   }
 }
 
+// Valeurs non principales d'une LocationPart (autre orthographe, écriture d'origine)
+Table address_variant {
+  id int [pk, note: "calculé depuis l'adresse, la LocationPart et la valeur"]
+  address_id int [not null, ref: > address.id]
+  loc_part_type int [not null, note: "LocPartType (1450 à 1456)"]
+  script int [note: "ScriptID"]
+  value text [not null]
+  topmaj tinytext [not null]
+}
+
+Table address_variant_sdn {
+  address_variant_id int [ref: > address_variant.id]
+  identity_id int [ref: > sdn.identity]
+  indexes {
+    (address_variant_id, identity_id) [pk]
+  }
+}
+
 // Code ISIN d'un SDN
 Table isin {
   id int [pk, increment]
   isin text
+  normalized text [note: "ISIN normalisé, vide si le code ISIN est invalide"]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -228,6 +298,8 @@ Table isin_sdn {
 Table issuer_name {
   id int [pk]
   issuer_name text
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -243,6 +315,8 @@ Table issuer_name_sdn {
 Table eo13662dd {
   id int [pk]
   reference_id int
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -260,6 +334,8 @@ Table eo13662dd_sdn {
 Table eo13846inf {
   id int [pk]
   reference_id int
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -277,6 +353,8 @@ Table eo13846inf_sdn {
 Table eo14024dd {
   id int [pk]
   reference_id int
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -294,6 +372,8 @@ Table eo14024dd_sdn {
 Table nationality_registration {
   id int [pk]
   location text
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -309,6 +389,8 @@ Table nationality_registration_sdn {
 Table citizen {
   id int [pk, increment]
   location text
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -324,6 +406,8 @@ Table citizen_sdn {
 Table caatsa235 {
   id int [pk, increment]
   reference_id int
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -340,6 +424,8 @@ Table caatsa235_sdn {
 Table equity_ticker {
   id int [pk, increment]
   equity_ticker text [not null]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -352,9 +438,48 @@ Table equity_ticker_sdn {
 }
 
 // Email d'un SDN
+Table date_period {
+  id int [pk, note: "id de la FeatureVersion"]
+  feature_type int [not null, ref: > ref_feature.id]
+  earliest date [note: "premier jour possible de la DatePeriod"]
+  latest date [note: "dernier jour possible de la DatePeriod"]
+  approximate bool [not null]
+  value text [not null, note: "format Ofac (ex: 01 JAN 2001, 2001 TO 2003)"]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
+  topmaj tinytext [not null]
+}
+
+Table date_period_sdn {
+  date_period_id int [ref: > date_period.id]
+  sdn_id int [ref: > sdn.fixed_ref]
+  indexes {
+    (date_period_id, sdn_id) [pk]
+  }
+}
+
+Table digital_currency_address {
+  id int [pk, note: "id de la FeatureVersion"]
+  currency text [not null, note: "code de la devise (ex: XBT, ETH, TRX)"]
+  address text [not null, note: "adresse telle que publiee (sensible a la casse)"]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
+  topmaj tinytext [not null]
+}
+
+Table digital_currency_address_sdn {
+  digital_currency_address_id int [ref: > digital_currency_address.id]
+  sdn_id int [ref: > sdn.fixed_ref]
+  indexes {
+    (digital_currency_address_id, sdn_id) [pk]
+  }
+}
+
 Table email {
   id int [pk, increment]
   email text [not null]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -369,6 +494,8 @@ Table email_sdn {
 Table target {
   id int [pk, increment]
   target int [not null]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -416,6 +543,11 @@ Table name_sdn {
 Table dob {
   id int [pk, increment]
   dob text [not null]
+  earliest date [note: "premier jour possible de la DatePeriod"]
+  latest date [note: "dernier jour possible de la DatePeriod"]
+  approximate bool [not null]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -431,6 +563,8 @@ Table dob_identity {
 Table pob {
   id int [pk, increment]
   pob text [not null]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -446,6 +580,8 @@ Table pob_identity {
 Table website {
   id int [pk, increment]
   website text [not null]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -461,6 +597,9 @@ Table website_identity {
 Table bic {
   id int [pk, increment]
   bic text [not null]
+  normalized text [note: "BIC normalisé, vide si le BIC est invalide"]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -475,12 +614,28 @@ Table bic_sdn {
 Table dateofissue {
   id int [pk, default: 0]
   last_document date [not null]
+  rolled_back_from date [note: "date de publication du dernier document annulé par un rollback (--rollback-to), il n'est plus importé sans -f"]
+}
+
+// Etat d'un SDN à chaque date de publication où il a changé, de cette date jusqu'à la ligne suivante du SDN
+Table sdn_history {
+  fixed_ref int [not null, note: "sdn.fixed_ref (pas de clé étrangère, l'historique est conservé)"]
+  date_of_issue date [not null, note: "date de publication du document importé"]
+  revision int [not null, default: 0, note: "0 pour les premières lignes d'une date, incrémentée pour les lignes enregistrées à nouveau à cette date (rollback ou import forcé)"]
+  sanction_status tinytext [not null, note: "ACTIVE ou INACTIVE"]
+  record longtext [note: "SdnRecord au format json, NULL si le SDN n'est plus dans la liste"]
+  rolled_back_from date [note: "date de publication du document annulé par un retour arrière, la ligne est datée de ce document avec la révision suivante"]
+  indexes {
+    (fixed_ref, date_of_issue, revision) [pk]
+  }
 }
 
 // Nationalité d'un SDN
 Table nationality {
   id int [pk]
   nationality int
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -499,6 +654,7 @@ Table document {
   id int [pk]
   doctype int
   registration_number text
+  normalized_number text [note: "numéro IMO, MMSI ou LEI normalisé, vide si le numéro est invalide"]
   issued_by int
   issued_date date
   expiration_date date
@@ -540,6 +696,8 @@ Table relation_sdn {
 Table other_vessel_flag {
   id int [pk, increment]
   value text
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -554,6 +712,9 @@ Table other_vessel_flag_sdn {
 Table bik {
   id int [pk]
   bik text
+  normalized text [note: "BIK normalisé, vide si le BIK est invalide"]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
@@ -568,6 +729,8 @@ Table bik_sdn {
 Table phone_number {
   id int [pk]
   phone_number text [not null]
+  reliability int [not null, note: "ref_value Reliability"]
+  comment text [note: "commentaire de la FeatureVersion"]
   topmaj tinytext [not null]
 }
 
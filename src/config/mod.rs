@@ -1,5 +1,6 @@
 use std::{env, error::Error, path::PathBuf};
 
+use chrono::NaiveDate;
 use log::{debug, info, warn, LevelFilter};
 use log4rs::{
     append::{console::ConsoleAppender, file::FileAppender},
@@ -8,10 +9,15 @@ use log4rs::{
     filter::threshold::ThresholdFilter,
 };
 
+use crate::document::legacy::LegacyFormat;
+use crate::document::inputs::list::{self, ListSource};
 use crate::document::DocumentType;
 
 use clap::Parser;
 
+/// Folder of the log file, the import reports written for the other teams are next to it
+pub const LOG_FOLDER: &str = "logs";
+
 #[derive(Parser, Debug, Clone)]
 #[clap(author, version, about, long_about = None)]
 pub struct Args {
@@ -27,25 +33,78 @@ pub struct Args {
     /// Force import (i.e same date in db)
     #[clap(short = 'f', long, action = clap::ArgAction::SetTrue, default_value = "false")]
     force: bool,
+    /// Stream DistinctParties to the database instead of loading the whole document in memory
+    #[clap(short = 's', long, action = clap::ArgAction::SetTrue, default_value = "false")]
+    stream: bool,
+    /// Import this file (xml, gz or zip) instead of the one found in the data folder, e.g. a file from the archive folder
+    #[clap(short = 'i', long)]
+    pub input: Option<PathBuf>,
+    /// Import the legacy Ofac files (sdn.xml or SDN.CSV, ADD.CSV and ALT.CSV) instead of the advanced xml
+    #[clap(short = 'l', long, value_parser = legacy_parsing)]
+    pub legacy: Option<String>,
+    /// Validate the advanced xml before importing it, the import is refused on blocking errors
+    #[clap(long, action = clap::ArgAction::SetTrue, default_value = "false")]
+    validate: bool,
+    /// Quarantine the DistinctParties that can not be converted instead of aborting the import
+    #[clap(long, action = clap::ArgAction::SetTrue, default_value = "false")]
+    lenient: bool,
+    /// Fail the import when unmapped feature, name part or location part types are found for the first time
+    #[clap(long, action = clap::ArgAction::SetTrue, default_value = "false")]
+    fail_on_unknowns: bool,
+    /// Yaml file mapping the Ofac FeatureTypes to the sdn columns and relations, the built-in mapping is used when not set
+    #[clap(long)]
+    pub mapping: Option<PathBuf>,
+    /// Import without writing, the changes are written to `changes/` in the data folder and the transaction is rolled back
+    #[clap(long, action = clap::ArgAction::SetTrue, default_value = "false")]
+    dry_run: bool,
+    /// History mode, date (YYYY-MM-DD) the list is queried at
+    #[clap(long, value_parser = date_parsing)]
+    pub as_of: Option<NaiveDate>,
+    /// History mode, only query this sdn
+    #[clap(long)]
+    pub fixed_ref: Option<i32>,
+    /// Import the archived document issued on this date (YYYY-MM-DD) again to roll the database back to it
+    #[clap(long, value_parser = date_parsing)]
+    pub rollback_to: Option<NaiveDate>,
 }
 
 fn mode_parsing(s: &str) -> Result<String, &'static str> {
     match s {
         "import" => Ok(String::from(s)),
         "export" => Ok(String::from(s)),
-        _ => Err("mode must be `import` or `export`"),
+        "history" => Ok(String::from(s)),
+        _ => Err("mode must be `import`, `export` or `history`"),
     }
 }
 
-fn data_type_parsing(s: &str) -> Result<String, &'static str> {
+fn data_type_parsing(s: &str) -> Result<String, String> {
     match s {
-        "OFAC" => Ok(String::from(s)),
-        "OFACNS" => Ok(String::from(s)),
         "FOFDBOF" => Ok(String::from(s)),
         "FOFNASY" => Ok(String::from(s)),
+        "CRYPTO" => Ok(String::from(s)),
         "ALL" => Ok(String::from(s)),
-        _ => Err("\ndatatype for import must be one of `OFAC` | `OFACNS` | `ALL`\n
-		datatype for export must be one of | `FOFDBOF` | `FOFNASY` | `ALL`"),
+        _ if list::find(s).is_some() => Ok(String::from(s)),
+        _ => Err(format!(
+            "\ndatatype for import must be one of {} | `ALL`\n\t\tdatatype for export must be one of | `FOFDBOF` | `FOFNASY` | `CRYPTO` | `ALL`",
+            list_names()
+        )),
+    }
+}
+
+/// Names of the registered lists, e.g. `` `OFAC` | `OFACNS` ``
+fn list_names() -> String {
+    list::SOURCES.iter().map(|source| format!("`{}`", source.name())).collect::<Vec<_>>().join(" | ")
+}
+
+fn date_parsing(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|err| format!("date must be YYYY-MM-DD ({})", err))
+}
+
+fn legacy_parsing(s: &str) -> Result<String, &'static str> {
+    match s {
+        "xml" => Ok(String::from(s)),
+        "csv" => Ok(String::from(s)),
+        _ => Err("legacy format must be `xml` or `csv`"),
     }
 }
 
@@ -55,22 +114,61 @@ pub struct Config {
     pub debug: bool,
     pub mode: String,
     pub force: bool,
+    pub stream: bool,
+    pub input: Option<PathBuf>,
+    pub legacy: Option<LegacyFormat>,
+    pub validate: bool,
+    pub lenient: bool,
+    pub fail_on_unknowns: bool,
+    pub mapping: Option<PathBuf>,
+    pub dry_run: bool,
+    pub as_of: Option<NaiveDate>,
+    pub fixed_ref: Option<i32>,
+    pub rollback_to: Option<NaiveDate>,
     loaded: bool,
 }
 
 impl Config {
     pub fn init(args: &Args) -> Result<Config, Box<dyn Error>> {
-        let data_type = match args.datatype.as_str() {
-            "OFAC" => DocumentType::OFAC,
-            "OFACNS" => DocumentType::OFACNS,
-            _ => return Err(format!("Data type {} not recognized (must be one of `OFAC` | `OFACNS`)", args.datatype).into()),
+        let data_type = match list::find(&args.datatype) {
+            Some(source) => DocumentType::of(source),
+            None => return Err(format!("Data type {} not recognized (must be one of {})", args.datatype, list_names()).into()),
+        };
+        let legacy = match args.legacy.as_deref() {
+            Some("xml") => Some(LegacyFormat::Xml),
+            Some("csv") => Some(LegacyFormat::Csv),
+            Some(format) => return Err(format!("Legacy format {} not recognized (must be one of `xml` | `csv`)", format).into()),
+            None => None,
         };
+        if legacy.is_some() && !data_type.source().has_legacy_files() {
+            return Err("Legacy files are only published by Ofac".into());
+        }
+        if legacy.is_some() && args.stream {
+            return Err("Legacy files can not be streamed".into());
+        }
+        if args.rollback_to.is_some() && (legacy.is_some() || args.input.is_some()) {
+            return Err("A rollback imports an archived document, it can not be combined with an input file or the legacy files".into());
+        }
+        if args.mode == "history" && args.as_of.is_none() {
+            return Err("History mode needs the date the list is queried at (--as-of YYYY-MM-DD)".into());
+        }
         let config = Config {
             data_type,
             debug: args.debug,
             mode: args.mode.to_owned(),
             loaded: false,
             force: args.force,
+            stream: args.stream,
+            input: args.input.clone(),
+            legacy,
+            validate: args.validate,
+            lenient: args.lenient,
+            fail_on_unknowns: args.fail_on_unknowns,
+            mapping: args.mapping.clone(),
+            dry_run: args.dry_run,
+            as_of: args.as_of,
+            fixed_ref: args.fixed_ref,
+            rollback_to: args.rollback_to,
         };
         info!("Config has been loadded successfully (force mode: {})", if config.force { "enabled" } else { "disabled" });
         debug!("Config values {:?}", config);
@@ -86,7 +184,7 @@ impl Config {
             return;
         }
 
-        let mut log_path = PathBuf::from("logs");
+        let mut log_path = PathBuf::from(LOG_FOLDER);
         if !log_path.is_dir() {
             warn!("logs directory doesn't exist");
         }
@@ -109,11 +207,17 @@ impl Config {
         info!("Config has been loadded successfully (force mode: {})", if self.force { "enabled" } else { "disabled" });
         self.loaded = true;
     }
+    /// The list this config imports or exports
+    pub fn source(&self) -> &'static dyn ListSource {
+        self.data_type.source()
+    }
     pub fn get_connection_string(&self) -> String {
-        match self.data_type {
-            DocumentType::OFAC => env::var("OFAC_DATABASE_URL").expect("OFAC_DATABASE_URL environment variable must be set"),
-            DocumentType::OFACNS => env::var("OFAC_NS_DATABASE_URL").expect("OFAC_NS_DATABASE_URL environment variable must be set"),
-        }
+        let var = self.source().database_url_var();
+        env::var(var).unwrap_or_else(|_| panic!("{} environment variable must be set", var))
+    }
+    /// Optional lists are only processed with `ALL` when their database is configured
+    pub fn is_enabled(source: &dyn ListSource) -> bool {
+        source.is_required() || env::var(source.database_url_var()).is_ok()
     }
     pub fn get_ddc_connection_string(&self) -> String {
         env::var("DDC_DATABASE_URL").expect("DDC_DATABASE_URL environment variable must be set")
@@ -122,25 +226,15 @@ impl Config {
         env::var("DATABASE_URL").unwrap()
     }
     pub fn get_database_name(&self) -> String {
-        match self.data_type {
-            DocumentType::OFAC => {
-                let connection_str = env::var("OFAC_DATABASE_URL").expect("OFAC_DATABASE_URL environment variable must be set");
-                connection_str[connection_str.rfind('/').unwrap()..connection_str.len()].to_owned()
-            }
-            DocumentType::OFACNS => {
-                let connection_str = env::var("OFAC_NS_DATABASE_URL").expect("OFAC_NS_DATABASE_URL environment variable must be set");
-                connection_str[connection_str.rfind('/').unwrap()..connection_str.len()].to_owned()
-            }
-        }
+        let connection_str = self.get_connection_string();
+        connection_str[connection_str.rfind('/').unwrap()..connection_str.len()].to_owned()
     }
     pub fn get_ddc_database_name(&self) -> String {
         let connection_str = env::var("DDC_DATABASE_URL").expect("DDC_DATABASE_URL environment variable must be set");
         connection_str[connection_str.rfind('/').unwrap()..connection_str.len()].to_owned()
     }
     pub fn get_data_folder_path(&self) -> String {
-        match self.data_type {
-            DocumentType::OFAC => env::var("OFAC_DATA_FOLDER").expect("OFAC_DATA_FOLDER environment variable must be set"),
-            DocumentType::OFACNS => env::var("OFAC_NS_DATA_FOLDER").expect("OFAC_NS_DATA_FOLDER environment variable must be set"),
-        }
+        let var = self.source().data_folder_var();
+        env::var(var).unwrap_or_else(|_| panic!("{} environment variable must be set", var))
     }
 }
@@ -0,0 +1,313 @@
+//! Rows written by the importer, flushed as multi-row statements
+//!
+//! Inserts, updates, topmaj updates and deletes are accumulated by table and written with [`WriteBatch::flush`] once a
+//! batch of sdns, the documents or the relations have been processed. Deletes are written first, then the other
+//! statements in the order their table was first written so that the entities are inserted before their link tables.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use log::error;
+use sea_orm::sea_query::{Alias, CaseStatement, Condition, Expr, Query, SimpleExpr};
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DatabaseTransaction, DbBackend, DbErr, EntityName, EntityTrait, IdenStatic, Iterable, PrimaryKeyToColumn, Statement, Value};
+
+/// Maximum number of rows written by a statement
+const STATEMENT_ROWS: usize = 500;
+
+/// Column set back to `N` by the topmaj only updates
+const TOPMAJ: &str = "topmaj";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteKind {
+    Insert,
+    /// Every column but the primary key is written again, the rows of a statement are told apart with a `CASE` by column
+    Update,
+    /// Only the topmaj of the row is set back to `N`
+    UpdateTopmajOnly,
+    Delete,
+}
+
+/// Rows of a table written with the same kind of statement
+#[derive(Debug)]
+struct Segment {
+    kind: WriteKind,
+    table: String,
+    /// Columns of the rows, only the primary key for the topmaj updates and the deletes
+    columns: Vec<String>,
+    /// Primary key columns, they are not updated by the updates
+    keys: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+/// Rows waiting to be written in the import transaction
+///
+/// The batch is owned by the [`crate::processor::import::Importer`] and given along with its transaction to the
+/// entities processed concurrently, so that the rows of an import are never written by another one
+#[derive(Debug, Default)]
+pub struct WriteBatch {
+    pending: Mutex<PendingRows>,
+}
+
+#[derive(Debug, Default)]
+struct PendingRows {
+    segments: Vec<Segment>,
+}
+
+impl PendingRows {
+    fn push(&mut self, kind: WriteKind, table: &str, columns: Vec<String>, keys: Vec<String>, row: Vec<Value>) {
+        match self.segments.iter_mut().find(|segment| segment.kind == kind && segment.table == table && segment.columns == columns) {
+            Some(segment) => segment.rows.push(row),
+            None => self.segments.push(Segment {
+                kind,
+                table: table.to_owned(),
+                columns,
+                keys,
+                rows: vec![row],
+            }),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Statements writing the batch, deletes first
+    fn statements(&self, backend: DbBackend) -> Vec<(&str, Statement)> {
+        let deletes = self.segments.iter().filter(|segment| segment.kind == WriteKind::Delete);
+        let others = self.segments.iter().filter(|segment| segment.kind != WriteKind::Delete);
+        let mut statements = Vec::new();
+        for segment in deletes.chain(others) {
+            for rows in segment.rows.chunks(STATEMENT_ROWS) {
+                statements.push((segment.table.as_str(), segment.statement(rows, backend)));
+            }
+        }
+        statements
+    }
+}
+
+impl Segment {
+    fn statement(&self, rows: &[Vec<Value>], backend: DbBackend) -> Statement {
+        let table = Alias::new(&self.table);
+        match self.kind {
+            WriteKind::Insert => {
+                let mut insert = Query::insert();
+                insert.into_table(table).columns(self.columns.iter().map(|column| Alias::new(column)));
+                for row in rows {
+                    insert.values_panic(row.iter().cloned().map(SimpleExpr::from));
+                }
+                backend.build(&insert)
+            }
+            WriteKind::Update => {
+                let mut update = Query::update();
+                update.table(table);
+                for (index, column) in self.columns.iter().enumerate().filter(|(_, column)| !self.keys.contains(column)) {
+                    let mut case = CaseStatement::new();
+                    for row in rows {
+                        case = case.case(self.row_condition(row), row[index].clone());
+                    }
+                    update.value(Alias::new(column), case.finally(Expr::col(Alias::new(column))));
+                }
+                backend.build(update.cond_where(self.key_condition(rows)))
+            }
+            WriteKind::UpdateTopmajOnly => backend.build(Query::update().table(table).value(Alias::new(TOPMAJ), "N").cond_where(self.key_condition(rows))),
+            WriteKind::Delete => backend.build(Query::delete().from_table(table).cond_where(self.key_condition(rows))),
+        }
+    }
+
+    /// Value of each primary key column in `row`
+    fn key_values<'a>(&'a self, row: &'a [Value]) -> impl Iterator<Item = (&'a String, &'a Value)> {
+        self.keys.iter().map(move |key| (key, &row[self.columns.iter().position(|column| column == key).unwrap()]))
+    }
+
+    /// `key = value` for each primary key column of `row`
+    fn row_condition(&self, row: &[Value]) -> Condition {
+        let mut condition = Condition::all();
+        for (column, value) in self.key_values(row) {
+            condition = condition.add(Expr::col(Alias::new(column)).eq(value.clone()));
+        }
+        condition
+    }
+
+    /// `key IN (...)`, or one condition by row for the composite keys
+    fn key_condition(&self, rows: &[Vec<Value>]) -> Condition {
+        if self.keys.len() == 1 {
+            let values = rows.iter().flat_map(|row| self.key_values(row).map(|(_, value)| value.clone()));
+            return Condition::all().add(Expr::col(Alias::new(&self.keys[0])).is_in(values));
+        }
+        let mut condition = Condition::any();
+        for row in rows {
+            condition = condition.add(self.row_condition(row));
+        }
+        condition
+    }
+}
+
+fn table_of<A: ActiveModelTrait>() -> String {
+    A::Entity::default().table_name().to_owned()
+}
+
+fn keys_of<A: ActiveModelTrait>(model: &A) -> (Vec<String>, Vec<Value>) {
+    let columns = <<A::Entity as EntityTrait>::PrimaryKey as Iterable>::iter().map(|key| key.into_column().as_str().to_owned()).collect();
+    let values = model.get_primary_key_value().map(|values| values.into_iter().collect()).unwrap_or_default();
+    (columns, values)
+}
+
+/// Columns of `model` which are set, with their values
+fn columns_of<A: ActiveModelTrait>(model: &A) -> (Vec<String>, Vec<Value>) {
+    let mut columns = Vec::new();
+    let mut values = Vec::new();
+    for column in <<A::Entity as EntityTrait>::Column as Iterable>::iter() {
+        if let Some(value) = model.get(column).into_value() {
+            columns.push(column.as_str().to_owned());
+            values.push(value);
+        }
+    }
+    (columns, values)
+}
+
+impl WriteBatch {
+    fn push<A: ActiveModelTrait>(&self, kind: WriteKind, model: &A) {
+        let (keys, key_values) = keys_of(model);
+        let (columns, row) = match kind {
+            WriteKind::Insert | WriteKind::Update => columns_of(model),
+            WriteKind::UpdateTopmajOnly | WriteKind::Delete => (keys.clone(), key_values),
+        };
+        self.pending.lock().unwrap().push(kind, &table_of::<A>(), columns, keys, row);
+    }
+
+    /// Insert `model` with its set columns
+    pub fn insert<A: ActiveModelTrait>(&self, model: &A) {
+        self.push(WriteKind::Insert, model);
+    }
+
+    /// Update every set column of `model`
+    pub fn update<A: ActiveModelTrait>(&self, model: &A) {
+        self.push(WriteKind::Update, model);
+    }
+
+    /// Set the topmaj of `model` back to `N`
+    pub fn update_only_topmaj<A: ActiveModelTrait>(&self, model: &A) {
+        self.push(WriteKind::UpdateTopmajOnly, model);
+    }
+
+    /// Delete `model` by its primary key
+    pub fn delete<A: ActiveModelTrait>(&self, model: &A) {
+        self.push(WriteKind::Delete, model);
+    }
+
+    /// Write the pending rows in `tx`
+    ///
+    /// The transaction is locked for the whole batch, so that batches taken by concurrent tasks are written in order
+    pub async fn flush(&self, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>) -> Result<(), DbErr> {
+        let lock = tx.lock().await;
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        if pending.is_empty() {
+            return Ok(());
+        }
+        for (table, statement) in pending.statements(lock.get_database_backend()) {
+            if let Err(err) = lock.execute(statement).await {
+                error!("Batched write to {} failed : {}", table, err);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod batch {
+    use super::*;
+    use crate::db::entity::{bic, bic_sdn};
+    use crate::db::changes::ChangeRecorder;
+    use crate::db::{OfacEntity, OfacEntityFinalOp};
+    use sea_orm::{IntoActiveModel, Set};
+
+    fn bic_model(id: i32, value: &str, topmaj: &str) -> bic::Model {
+        bic::Model {
+            id,
+            bic: value.to_owned(),
+            normalized: Some(value.to_owned()),
+            reliability: 1,
+            comment: None,
+            topmaj: topmaj.to_owned(),
+        }
+    }
+
+    fn bic(id: i32, value: &str, topmaj: &str) -> bic::ActiveModel {
+        bic_model(id, value, topmaj).into_active_model()
+    }
+
+    /// Process the bics of the sdn 36 published in the document against the ones in database, returns the statements written
+    async fn process_bics(document: &[bic::Model], in_db: &[bic::Model], excepted_op: OfacEntityFinalOp) -> Vec<String> {
+        let batch = WriteBatch::default();
+        let mut op = OfacEntityFinalOp::Nothing;
+        bic::ActiveModel::process_entity(&mut document.to_vec(), &mut in_db.to_vec(), &batch, &ChangeRecorder::default(), 36, &mut op).await.unwrap();
+        assert!(op == excepted_op);
+        let pending = std::mem::take(&mut *batch.pending.lock().unwrap());
+        pending.statements(DbBackend::MySql).into_iter().map(|(_, statement)| statement.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn topmaj_sequence() {
+        let excepted = vec![
+            "INSERT INTO `bic` (`id`, `bic`, `normalized`, `reliability`, `comment`, `topmaj`) VALUES (1, 'HAVIGB2L', 'HAVIGB2L', 1, NULL, 'O')",
+            "INSERT INTO `bic_sdn` (`bic_id`, `sdn_id`) VALUES (1, 36)",
+        ];
+        assert_eq!(excepted, process_bics(&[bic_model(1, "HAVIGB2L", "N")], &[], OfacEntityFinalOp::Insert).await);
+
+        let excepted = vec!["UPDATE `bic` SET `topmaj` = 'N' WHERE `id` IN (1)"];
+        assert_eq!(excepted, process_bics(&[bic_model(1, "HAVIGB2L", "N")], &[bic_model(1, "HAVIGB2L", "O")], OfacEntityFinalOp::UpdateTopmajOnly).await);
+
+        let excepted: Vec<String> = Vec::new();
+        assert_eq!(excepted, process_bics(&[bic_model(1, "HAVIGB2L", "N")], &[bic_model(1, "HAVIGB2L", "N")], OfacEntityFinalOp::Nothing).await);
+
+        let excepted = vec![
+            "UPDATE `bic` SET \
+                `bic` = (CASE WHEN (`id` = 1) THEN 'HAVIGB2X' ELSE `bic` END), \
+                `normalized` = (CASE WHEN (`id` = 1) THEN 'HAVIGB2X' ELSE `normalized` END), \
+                `reliability` = (CASE WHEN (`id` = 1) THEN 1 ELSE `reliability` END), \
+                `comment` = (CASE WHEN (`id` = 1) THEN NULL ELSE `comment` END), \
+                `topmaj` = (CASE WHEN (`id` = 1) THEN 'O' ELSE `topmaj` END) \
+                WHERE `id` IN (1)",
+        ];
+        assert_eq!(excepted, process_bics(&[bic_model(1, "HAVIGB2X", "N")], &[bic_model(1, "HAVIGB2L", "N")], OfacEntityFinalOp::Update).await);
+
+        let excepted = vec!["DELETE FROM `bic_sdn` WHERE `bic_id` = 1 AND `sdn_id` = 36", "DELETE FROM `bic` WHERE `id` IN (1)"];
+        assert_eq!(excepted, process_bics(&[], &[bic_model(1, "HAVIGB2X", "O")], OfacEntityFinalOp::Nothing).await);
+    }
+
+    #[test]
+    fn statements_by_table() {
+        let mut batch = PendingRows::default();
+        for (kind, model) in [
+            (WriteKind::Insert, bic(1, "HAVIGB2L", "O")),
+            (WriteKind::Update, bic(2, "SABRRUMM", "O")),
+            (WriteKind::Insert, bic(3, "BKCHCNBJ", "O")),
+            (WriteKind::Update, bic(6, "VTBRRUMM", "O")),
+        ] {
+            let (keys, _) = keys_of(&model);
+            let (columns, row) = columns_of(&model);
+            batch.push(kind, &table_of::<bic::ActiveModel>(), columns, keys, row);
+        }
+        let link = bic_sdn::ActiveModel { bic_id: Set(4), sdn_id: Set(36) };
+        let (keys, row) = keys_of(&link);
+        batch.push(WriteKind::Delete, &table_of::<bic_sdn::ActiveModel>(), keys.clone(), keys, row);
+        let (keys, row) = keys_of(&bic(5, "", "O"));
+        batch.push(WriteKind::UpdateTopmajOnly, &table_of::<bic::ActiveModel>(), keys.clone(), keys, row);
+
+        let excepted = vec![
+            "DELETE FROM `bic_sdn` WHERE `bic_id` = 4 AND `sdn_id` = 36",
+            "INSERT INTO `bic` (`id`, `bic`, `normalized`, `reliability`, `comment`, `topmaj`) VALUES (1, 'HAVIGB2L', 'HAVIGB2L', 1, NULL, 'O'), (3, 'BKCHCNBJ', 'BKCHCNBJ', 1, NULL, 'O')",
+            "UPDATE `bic` SET \
+                `bic` = (CASE WHEN (`id` = 2) THEN 'SABRRUMM' WHEN (`id` = 6) THEN 'VTBRRUMM' ELSE `bic` END), \
+                `normalized` = (CASE WHEN (`id` = 2) THEN 'SABRRUMM' WHEN (`id` = 6) THEN 'VTBRRUMM' ELSE `normalized` END), \
+                `reliability` = (CASE WHEN (`id` = 2) THEN 1 WHEN (`id` = 6) THEN 1 ELSE `reliability` END), \
+                `comment` = (CASE WHEN (`id` = 2) THEN NULL WHEN (`id` = 6) THEN NULL ELSE `comment` END), \
+                `topmaj` = (CASE WHEN (`id` = 2) THEN 'O' WHEN (`id` = 6) THEN 'O' ELSE `topmaj` END) \
+                WHERE `id` IN (2, 6)",
+            "UPDATE `bic` SET `topmaj` = 'N' WHERE `id` IN (5)",
+        ];
+        let statements: Vec<String> = batch.statements(DbBackend::MySql).into_iter().map(|(_, statement)| statement.to_string()).collect();
+        assert_eq!(excepted, statements);
+    }
+}
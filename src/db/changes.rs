@@ -0,0 +1,309 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Local;
+use log::info;
+use sea_orm::sea_query::ValueTuple;
+use sea_orm::{ActiveModelTrait, EntityName, EntityTrait, IdenStatic, Iterable, ModelTrait, PrimaryKeyToColumn, Value};
+use serde::Serialize;
+
+tokio::task_local! {
+    /// FixedRef of the sdn processed by the task, see [`sdn_scope`]
+    static SDN: i32;
+}
+
+/// Table of the documents, they are linked to the identity of the sdn
+const DOCUMENT_TABLE: &str = "document";
+
+/// Columns left out of the field changes, they are maintained by the importer
+const IGNORED_COLUMNS: &[&str] = &["topmaj", "last_update", "updated_by"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChangeKind {
+    Insert,
+    Update,
+    /// Only the topmaj of the row is set back to `N`
+    UpdateTopmajOnly,
+    Delete,
+    /// Sdn set INACTIVE because it is not in the document anymore
+    Deactivation,
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            ChangeKind::Insert => "INSERT",
+            ChangeKind::Update => "UPDATE",
+            ChangeKind::UpdateTopmajOnly => "UPDATE TOPMAJ",
+            ChangeKind::Delete => "DELETE",
+            ChangeKind::Deactivation => "DEACTIVATION",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+/// Value of a column before and after the change, `None` for NULL and for the side of an insert or a delete without row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub column: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// A row written by the importer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub table: String,
+    /// Primary key of the row, the values are joined with `/` for composite keys
+    pub key: String,
+    /// Row of the link table the row is saved with (e.g. the sdn FixedRef)
+    pub link: Option<i32>,
+    /// FixedRef of the sdn the row belongs to, `None` for the reference tables
+    pub fixed_ref: Option<i32>,
+    /// Changed columns of an update, every column of an insert or a delete
+    pub fields: Vec<FieldChange>,
+}
+
+impl Change {
+    pub fn of_model<M: ModelTrait>(kind: ChangeKind, model: &M, link: Option<i32>) -> Change {
+        let key: Vec<String> = <<M::Entity as EntityTrait>::PrimaryKey as Iterable>::iter().map(|key| model.get(key.into_column()).to_string()).collect();
+        Change {
+            kind,
+            table: M::Entity::default().table_name().to_owned(),
+            key: key.join("/"),
+            link,
+            fixed_ref: None,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn of_active_model<A: ActiveModelTrait>(kind: ChangeKind, model: &A, link: Option<i32>) -> Change {
+        let key = match model.get_primary_key_value() {
+            Some(ValueTuple::One(value)) => value.to_string(),
+            Some(ValueTuple::Two(lhs, rhs)) => format!("{}/{}", lhs, rhs),
+            Some(values) => format!("{:?}", values),
+            None => String::new(),
+        };
+        Change {
+            kind,
+            table: A::Entity::default().table_name().to_owned(),
+            key,
+            link,
+            fixed_ref: None,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Insert of `model` with the value of every column
+    pub fn of_insert<M: ModelTrait>(model: &M, link: Option<i32>) -> Change {
+        let mut change = Change::of_model(ChangeKind::Insert, model, link);
+        change.fields = field_changes(None, Some(model));
+        change
+    }
+
+    /// Delete of `model` with the value of every column
+    pub fn of_delete<M: ModelTrait>(model: &M, link: Option<i32>) -> Change {
+        let mut change = Change::of_model(ChangeKind::Delete, model, link);
+        change.fields = field_changes(Some(model), None);
+        change
+    }
+
+    /// Update of `before` to `after` with the changed columns
+    pub fn of_update<M: ModelTrait>(before: &M, after: &M, link: Option<i32>) -> Change {
+        let mut change = Change::of_model(ChangeKind::Update, after, link);
+        change.fields = field_changes(Some(before), Some(after));
+        change
+    }
+}
+
+fn field_changes<M: ModelTrait>(before: Option<&M>, after: Option<&M>) -> Vec<FieldChange> {
+    let mut fields = Vec::new();
+    for column in <<M::Entity as EntityTrait>::Column as Iterable>::iter() {
+        if IGNORED_COLUMNS.contains(&column.as_str()) {
+            continue;
+        }
+        let old = before.and_then(|model| format_value(model.get(column)));
+        let new = after.and_then(|model| format_value(model.get(column)));
+        if old != new {
+            fields.push(FieldChange {
+                column: column.as_str().to_owned(),
+                before: old,
+                after: new,
+            });
+        }
+    }
+    fields
+}
+
+fn format_value(value: Value) -> Option<String> {
+    match value {
+        Value::String(Some(value)) => Some(*value),
+        Value::ChronoDate(Some(date)) => Some(date.to_string()),
+        value if value.to_string() == "NULL" => None,
+        value => Some(value.to_string()),
+    }
+}
+
+/// Every insert, update, delete and deactivation of an import
+#[derive(Debug, Default)]
+pub struct ChangeSet {
+    pub changes: Vec<Change>,
+}
+
+impl ChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Number of changes by table and kind
+    pub fn summary(&self) -> BTreeMap<(&str, ChangeKind), usize> {
+        let mut summary = BTreeMap::new();
+        for change in self.changes.iter() {
+            *summary.entry((change.table.as_str(), change.kind)).or_insert(0) += 1;
+        }
+        summary
+    }
+
+    /// Set the FixedRef of the document changes from the identities of the sdns (identity to FixedRef)
+    pub fn resolve_documents(&mut self, identities: &HashMap<i32, i32>) {
+        for change in self.changes.iter_mut().filter(|change| change.table == DOCUMENT_TABLE && change.fixed_ref.is_none()) {
+            change.fixed_ref = change.link.and_then(|identity| identities.get(&identity).copied());
+        }
+    }
+
+    pub fn log(&self) {
+        for ((table, kind), count) in self.summary() {
+            info!("{} {} : {}", table, kind, count);
+        }
+    }
+
+    /// Write the changes to `changes/` in `folder`
+    pub fn write(&self, folder: &Path) -> Result<PathBuf, std::io::Error> {
+        let folder = folder.join("changes");
+        std::fs::create_dir_all(&folder)?;
+        let path = folder.join(format!("changes{}.csv", Local::now().format("%Y%m%d_%H%M%S")));
+        let mut file = std::fs::File::create(&path)?;
+        self.write_in(&mut file)?;
+        Ok(path)
+    }
+
+    /// One line by change, or by changed column when the change has columns
+    fn write_in<W: Write>(&self, buffer: &mut W) -> Result<(), std::io::Error> {
+        writeln!(buffer, "kind;table;key;link;column;before;after")?;
+        for change in self.changes.iter() {
+            let link = change.link.map(|link| link.to_string()).unwrap_or_default();
+            if change.fields.is_empty() {
+                writeln!(buffer, "{};{};{};{};;;", change.kind, change.table, change.key, link)?;
+            }
+            for field in change.fields.iter() {
+                let before = field.before.as_deref().unwrap_or_default().replace(';', " ");
+                let after = field.after.as_deref().unwrap_or_default().replace(';', " ");
+                writeln!(buffer, "{};{};{};{};{};{};{}", change.kind, change.table, change.key, link, field.column, before, after)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Changes of an import
+///
+/// The recorder is owned by the [`crate::processor::import::Importer`] and given along with its write batch to the
+/// entities processed concurrently
+#[derive(Debug, Default)]
+pub struct ChangeRecorder {
+    recorded: Mutex<ChangeSet>,
+}
+
+impl ChangeRecorder {
+    /// The FixedRef of the change is the one of the [`sdn_scope`] the change is recorded in, if not set
+    pub fn record(&self, mut change: Change) {
+        if change.fixed_ref.is_none() {
+            change.fixed_ref = SDN.try_with(|fixed_ref| *fixed_ref).ok();
+        }
+        self.recorded.lock().unwrap().changes.push(change);
+    }
+
+    /// Take the changes recorded so far
+    pub fn take(&self) -> ChangeSet {
+        std::mem::take(&mut *self.recorded.lock().unwrap())
+    }
+}
+
+/// Run `future` as the processing of the sdn `fixed_ref`, the changes it records belong to the sdn
+pub async fn sdn_scope<F: Future>(fixed_ref: i32, future: F) -> F::Output {
+    SDN.scope(fixed_ref, future).await
+}
+
+#[cfg(test)]
+mod changes {
+    use super::*;
+    use crate::db::entity::bic;
+
+    #[test]
+    fn update_lists_changed_columns() {
+        let before = bic::Model {
+            id: 33828,
+            bic: "HAVIGB2L".to_owned(),
+            normalized: None,
+            reliability: 1,
+            comment: None,
+            topmaj: "N".to_owned(),
+        };
+        let after = bic::Model {
+            normalized: Some("HAVIGB2L".to_owned()),
+            topmaj: "O".to_owned(),
+            ..before.clone()
+        };
+        let excepted = Change {
+            kind: ChangeKind::Update,
+            table: "bic".to_owned(),
+            key: "33828".to_owned(),
+            link: Some(36),
+            fixed_ref: None,
+            fields: vec![FieldChange {
+                column: "normalized".to_owned(),
+                before: None,
+                after: Some("HAVIGB2L".to_owned()),
+            }],
+        };
+        let update = Change::of_update(&before, &after, Some(36));
+        assert_eq!(excepted, update);
+
+        let changes = ChangeSet {
+            changes: vec![Change::of_model(ChangeKind::UpdateTopmajOnly, &before, Some(36)), update],
+        };
+        assert_eq!(Some(&1), changes.summary().get(&("bic", ChangeKind::Update)));
+        let mut buffer = Vec::new();
+        changes.write_in(&mut buffer).unwrap();
+        assert_eq!(
+            "kind;table;key;link;column;before;after\nUPDATE TOPMAJ;bic;33828;36;;;\nUPDATE;bic;33828;36;normalized;;HAVIGB2L\n",
+            String::from_utf8(buffer).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn changes_belong_to_their_sdn_scope() {
+        let bic = bic::Model {
+            id: 33828,
+            bic: "HAVIGB2L".to_owned(),
+            normalized: None,
+            reliability: 1,
+            comment: None,
+            topmaj: "N".to_owned(),
+        };
+        let recorder = ChangeRecorder::default();
+        recorder.record(Change::of_insert(&bic, Some(36)));
+        sdn_scope(36, async { recorder.record(Change::of_delete(&bic, Some(36))) }).await;
+
+        let excepted = vec![(ChangeKind::Insert, None), (ChangeKind::Delete, Some(36))];
+        let changes = recorder.take();
+        assert_eq!(excepted, changes.changes.iter().map(|change| (change.kind, change.fixed_ref)).collect::<Vec<_>>());
+        assert!(recorder.take().is_empty());
+    }
+}
@@ -3,12 +3,13 @@ use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
 use crate::{
     db::impl_topmaj,
     document::{
+        inputs::known::LocPartKind,
         models::{feature::Feature, location::Locations},
         OfacDocumentReferences,
     },
 };
 
-use crate::db::OfacEntity;
+use crate::db::{ConversionErr, OfacEntity};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
 #[sea_orm(table_name = "address")]
@@ -27,6 +28,9 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub state: Option<String>,
     pub is_primary: bool,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Text")]
     pub topmaj: String,
 }
@@ -54,16 +58,21 @@ impl Related<super::address_sdn::Entity> for Entity {
 impl ActiveModelBehavior for ActiveModel {}
 
 impl Model {
-    pub fn from_ofac_document(entity: (&Feature, &Locations), references: &OfacDocumentReferences, is_primary: bool) -> Model {
+    pub fn from_ofac_document(entity: (&Feature, &Locations), references: &OfacDocumentReferences, is_primary: bool) -> Result<Model, ConversionErr> {
+        let location = match entity.0.version.location.and_then(|location| entity.1.get(location.id)) {
+            Some(location) => location,
+            None => return Err(ConversionErr::MissingLocation(entity.0.version.id)),
+        };
         let mut address = Model {
-            id: entity.0.version.location.unwrap().id,
+            id: location.id,
             is_primary,
+            reliability: entity.0.version.reliability_id,
+            comment: entity.0.version.comment(),
             topmaj: "N".to_owned(),
             ..Default::default()
         };
-        let location = entity.1.locations.iter().find(|&loc| loc.id == address.id).unwrap();
         if let Some(country) = location.location_country.as_ref() {
-            match references.area_codes.iter().find(|&area| area.id == country.id) {
+            match references.area_code(country.id) {
                 Some(area) => address.country = Some(area.id),
                 None => address.country = None,
             }
@@ -78,32 +87,32 @@ impl Model {
                 }
                 if let Some(mut value) = value {
                     value = value.to_uppercase();
-                    match part.id {
-                        1450 => address.region = Some(value),
-                        1451 => address.address = Some(value),
-                        1452 => {
-                            if address.address.is_some() {
-                                address.address.as_mut().unwrap().push(',');
-                                address.address.as_mut().unwrap().push(' ');
-                                address.address.as_mut().unwrap().push_str(&value);
+                    match references.resolve::<LocPartKind>(part.id) {
+                        Some(LocPartKind::Region) => address.region = Some(value),
+                        Some(LocPartKind::Address1) => address.address = Some(value),
+                        Some(LocPartKind::Address2) => {
+                            if let Some(address) = address.address.as_mut() {
+                                address.push(',');
+                                address.push(' ');
+                                address.push_str(&value);
                             }
                         }
-                        1453 => {
-                            if address.address.is_some() {
-                                address.address.as_mut().unwrap().push(',');
-                                address.address.as_mut().unwrap().push(' ');
-                                address.address.as_mut().unwrap().push_str(&value);
+                        Some(LocPartKind::Address3) => {
+                            if let Some(address) = address.address.as_mut() {
+                                address.push(',');
+                                address.push(' ');
+                                address.push_str(&value);
                             }
                         }
-                        1454 => address.city = Some(value),
-                        1455 => address.state = Some(value),
-                        1456 => address.postal_code = Some(value),
+                        Some(LocPartKind::City) => address.city = Some(value),
+                        Some(LocPartKind::StateProvince) => address.state = Some(value),
+                        Some(LocPartKind::PostalCode) => address.postal_code = Some(value),
                         _ => {}
                     }
                 }
             }
         }
-        address
+        Ok(address)
     }
 }
 
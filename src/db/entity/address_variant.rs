@@ -0,0 +1,134 @@
+use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
+
+use crate::{
+    db::{impl_topmaj, OfacEntity},
+    document::{
+        inputs::builder::synthetic_id,
+        models::{feature::Feature, location::Locations},
+        OfacDocumentReferences,
+    },
+};
+
+/// A LocationPartValue which is not the primary one (alternate spelling, original script...)
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
+#[sea_orm(table_name = "address_variant")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i32,
+    pub address_id: i32,
+    pub loc_part_type: i32,
+    pub script: Option<i32>,
+    #[sea_orm(column_type = "Text")]
+    pub value: String,
+    #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
+    pub topmaj: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(belongs_to = "super::address::Entity", from = "Column::AddressId", to = "super::address::Column::Id", on_update = "Restrict", on_delete = "Restrict")]
+    Address,
+    #[sea_orm(has_many = "super::address_variant_sdn::Entity")]
+    AddressVariantSdn,
+}
+
+impl Related<super::address::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Address.def()
+    }
+}
+
+impl Related<super::address_variant_sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AddressVariantSdn.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// LocationPartValues have no id, the id of a variant is computed from its address, its part and its value
+    pub fn from_ofac_document(entity: (&Feature, &Locations), references: &OfacDocumentReferences) -> Vec<Model> {
+        let mut variants = Vec::new();
+        let address_id = match entity.0.version.location {
+            Some(location) => location.id,
+            None => return variants,
+        };
+        let location = match entity.1.get(address_id) {
+            Some(location) => location,
+            None => return variants,
+        };
+        for part in location.location_parts.iter().flatten() {
+            for part_value in part.values.iter().filter(|part_value| !part_value.primary) {
+                let value = part_value.value.trim();
+                if value.is_empty() {
+                    continue;
+                }
+                variants.push(Model {
+                    id: synthetic_id(&format!("{}/{}/{}", address_id, part.id, value)),
+                    address_id,
+                    loc_part_type: part.id,
+                    script: part_value.script_id.or_else(|| references.detect_script_id(value)),
+                    value: value.to_uppercase(),
+                    topmaj: "N".to_owned(),
+                });
+            }
+        }
+        variants
+    }
+}
+
+impl_topmaj! {
+    Entity, Model, super::address_variant_sdn::ActiveModel, ActiveModel
+}
+
+#[cfg(test)]
+mod address_variant {
+    use super::*;
+    use crate::document::models::referencevaluesets::Script;
+    use quick_xml::de::from_str;
+
+    #[test]
+    fn parse_non_primary_values() {
+        let feature: Feature = from_str(
+            r#"
+			<Feature ID="2000" FeatureTypeID="25">
+			  <FeatureVersion ID="2001" ReliabilityID="1">
+				<Comment />
+				<VersionLocation LocationID="2002" />
+			  </FeatureVersion>
+			</Feature>"#,
+        )
+        .unwrap();
+        let locations: Locations = from_str(
+            r#"
+			<Locations>
+			  <Location ID="2002">
+				<LocationPart LocPartTypeID="1454">
+				  <LocationPartValue Primary="true">
+					<Value>Moscow</Value>
+				  </LocationPartValue>
+				  <LocationPartValue Primary="false">
+					<Value>Москва</Value>
+				  </LocationPartValue>
+				  <LocationPartValue Primary="false" ScriptID="215">
+					<Value>Moskva</Value>
+				  </LocationPartValue>
+				</LocationPart>
+			  </Location>
+			</Locations>"#,
+        )
+        .unwrap();
+        let mut references = OfacDocumentReferences::default();
+        references.script_values.scripts.push(Script {
+            id: 220,
+            code: "Cyrl".to_owned(),
+            value: "Cyrillic".to_owned(),
+        });
+        let variants = Model::from_ofac_document((&feature, &locations), &references);
+        assert_eq!(2, variants.len());
+        assert_eq!((2002, 1454, Some(220), "МОСКВА"), (variants[0].address_id, variants[0].loc_part_type, variants[0].script, &variants[0].value[..]));
+        assert_eq!((Some(215), "MOSKVA"), (variants[1].script, &variants[1].value[..]));
+        assert_ne!(variants[0].id, variants[1].id);
+    }
+}
@@ -0,0 +1,49 @@
+use sea_orm::entity::prelude::*;
+
+use crate::db::OfacRelEntity;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "address_variant_sdn")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub address_variant_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub identity_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(belongs_to = "super::address_variant::Entity", from = "Column::AddressVariantId", to = "super::address_variant::Column::Id", on_update = "Restrict", on_delete = "Restrict")]
+    AddressVariant,
+    #[sea_orm(belongs_to = "super::sdn::Entity", from = "Column::IdentityId", to = "super::sdn::Column::Identity", on_update = "Restrict", on_delete = "Restrict")]
+    Sdn,
+}
+
+impl Related<super::address_variant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AddressVariant.def()
+    }
+}
+
+impl Related<super::sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Sdn.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl OfacRelEntity for ActiveModel {}
+
+#[derive(Debug)]
+pub struct SdnToAddressVariant;
+
+impl Linked for SdnToAddressVariant {
+    type FromEntity = super::sdn::Entity;
+
+    type ToEntity = super::address_variant::Entity;
+
+    fn link(&self) -> Vec<RelationDef> {
+        vec![Relation::Sdn.def().rev(), Entity::belongs_to(super::address_variant::Entity).from(Column::AddressVariantId).to(super::address_variant::Column::Id).into()]
+    }
+}
@@ -11,6 +11,9 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(column_type = "Text")]
     pub operator: String,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
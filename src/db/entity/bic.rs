@@ -4,6 +4,7 @@ use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
 use crate::document::models::feature::FeatureVersion;
 
 use crate::db::{impl_parse_feature_version_value, impl_topmaj, OfacEntity};
+use crate::document::inputs::identifier::IdentifierKind;
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
 #[sea_orm(table_name = "bic")]
@@ -12,6 +13,12 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(column_type = "Text")]
     pub bic: String,
+    /// BIC without separators, `None` when the BIC is invalid
+    #[sea_orm(column_type = "Text", nullable)]
+    pub normalized: Option<String>,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
@@ -34,6 +41,14 @@ impl_topmaj! {
     Entity, Model, super::bic_sdn::ActiveModel, ActiveModel
 }
 
+impl Model {
+    /// Set the normalized BIC when the published one is valid
+    pub fn with_normalized(mut self) -> Model {
+        self.normalized = IdentifierKind::Bic.validate(&self.bic).ok();
+        self
+    }
+}
+
 impl_parse_feature_version_value! {
     Model, Model, bic, "Bic"
 }
@@ -57,6 +72,32 @@ mod bic {
         let excepted = Model {
             id: 33828,
             bic: "HAVIGB2L".to_owned(),
+            normalized: None,
+            reliability: 1,
+            comment: None,
+            topmaj: "N".to_owned(),
+        };
+        assert_eq!(excepted, bic);
+        assert_eq!(Some("HAVIGB2L".to_owned()), bic.with_normalized().normalized);
+    }
+
+    #[test]
+    fn parse_bic_with_reliability_and_comment() {
+        let feature: FeatureVersion = from_str(
+            r#"
+			  <FeatureVersion ID="33829" ReliabilityID="3">
+				<Comment>Used by a front company</Comment>
+				<VersionDetail DetailTypeID="1432">HAVIGB2L</VersionDetail>
+			  </FeatureVersion>"#,
+        )
+        .unwrap();
+        let bic = Model::from_ofac_document(&feature);
+        let excepted = Model {
+            id: 33829,
+            bic: "HAVIGB2L".to_owned(),
+            normalized: None,
+            reliability: 3,
+            comment: Some("USED BY A FRONT COMPANY".to_owned()),
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, bic);
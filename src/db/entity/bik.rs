@@ -4,6 +4,7 @@ use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
 use crate::document::models::feature::FeatureVersion;
 
 use crate::db::{impl_parse_feature_version_value, impl_topmaj, OfacEntity};
+use crate::document::inputs::identifier::IdentifierKind;
 
 #[derive(Clone, Debug, PartialEq, Eq, Default, DeriveEntityModel)]
 #[sea_orm(table_name = "bik")]
@@ -12,6 +13,12 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(column_type = "Text")]
     pub bik: String,
+    /// BIK without separators, `None` when the BIK is invalid
+    #[sea_orm(column_type = "Text", nullable)]
+    pub normalized: Option<String>,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
@@ -34,6 +41,14 @@ impl_topmaj! {
     Entity, Model, super::bik_sdn::ActiveModel, ActiveModel
 }
 
+impl Model {
+    /// Set the normalized BIK when the published one is valid
+    pub fn with_normalized(mut self) -> Model {
+        self.normalized = IdentifierKind::Bik.validate(&self.bik).ok();
+        self
+    }
+}
+
 impl_parse_feature_version_value! {
     Model, Model, bik, "Bik"
 }
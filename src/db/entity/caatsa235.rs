@@ -11,6 +11,9 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub reference_id: Option<i32>,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
@@ -2,7 +2,7 @@ use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
 
 use crate::document::models::{feature::Feature, location::Locations};
 
-use crate::db::{impl_topmaj, OfacEntity};
+use crate::db::{impl_topmaj, ConversionErr, OfacEntity};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
 #[sea_orm(table_name = "citizen")]
@@ -11,6 +11,9 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(column_type = "Text", nullable)]
     pub location: Option<String>,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
@@ -30,10 +33,17 @@ impl Related<super::citizen_sdn::Entity> for Entity {
 impl ActiveModelBehavior for ActiveModel {}
 
 impl Model {
-    pub fn from_ofac_document(entity: (&Feature, &Locations)) -> Model {
-        let mut citizen = Model { id: entity.0.id, ..Default::default() };
-        let location_id = entity.0.version.location.unwrap().id;
-        let location = entity.1.locations.iter().find(|&loc| loc.id == location_id).unwrap();
+    pub fn from_ofac_document(entity: (&Feature, &Locations)) -> Result<Model, ConversionErr> {
+        let mut citizen = Model {
+            id: entity.0.id,
+            reliability: entity.0.version.reliability_id,
+            comment: entity.0.version.comment(),
+            ..Default::default()
+        };
+        let location = match entity.0.version.location.and_then(|location| entity.1.get(location.id)) {
+            Some(location) => location,
+            None => return Err(ConversionErr::MissingLocation(entity.0.version.id)),
+        };
         if let Some(parts) = location.location_parts.as_ref() {
             for part in parts {
                 for part_value in part.values.iter() {
@@ -43,7 +53,7 @@ impl Model {
                 }
             }
         }
-        citizen
+        Ok(citizen)
     }
 }
 
@@ -0,0 +1,132 @@
+use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
+
+use crate::db::{impl_topmaj, OfacEntity};
+use crate::document::models::feature::Feature;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
+#[sea_orm(table_name = "date_period")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i32,
+    pub feature_type: i32,
+    pub earliest: Option<Date>,
+    pub latest: Option<Date>,
+    pub approximate: bool,
+    #[sea_orm(column_type = "Text")]
+    pub value: String,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
+    #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
+    pub topmaj: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::ref_feature::Entity",
+        from = "Column::FeatureType",
+        to = "super::ref_feature::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    RefFeature,
+    #[sea_orm(has_many = "super::date_period_sdn::Entity")]
+    DatePeriodSdn,
+}
+
+impl Related<super::date_period_sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DatePeriodSdn.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// `None` when the FeatureVersion has no DatePeriod
+    pub fn from_ofac_document(entity: &Feature) -> Option<Model> {
+        let date_period = entity.version.date_period.as_ref()?;
+        let mut model = Model {
+            id: entity.version.id,
+            feature_type: entity.feature_type,
+            approximate: date_period.is_approximate(),
+            reliability: entity.version.reliability_id,
+            comment: entity.version.comment(),
+            topmaj: "N".to_owned(),
+            ..Default::default()
+        };
+        if let Some((earliest, latest)) = date_period.bounds() {
+            model.earliest = Some(earliest);
+            model.latest = Some(latest);
+        }
+        if let Some(shape) = date_period.shape() {
+            model.value = shape.to_ofac_string_approximate(model.approximate);
+        }
+        Some(model)
+    }
+}
+
+impl_topmaj! {
+    Entity, Model, super::date_period_sdn::ActiveModel, ActiveModel
+}
+
+#[cfg(test)]
+mod date_period {
+    use super::*;
+    use chrono::NaiveDate;
+    use quick_xml::de::from_str;
+
+    #[test]
+    fn parse_ranged_date_period() {
+        let feature: Feature = from_str(
+            r#"
+			<Feature ID="1122" FeatureTypeID="646">
+			  <FeatureVersion ID="1123" ReliabilityID="1">
+				<Comment />
+				<DatePeriod CalendarTypeID="1" YearFixed="false" MonthFixed="false" DayFixed="false">
+				  <Start Approximate="false" YearFixed="false" MonthFixed="false" DayFixed="false">
+					<From>
+					  <Year>2001</Year>
+					  <Month>1</Month>
+					  <Day>1</Day>
+					</From>
+					<To>
+					  <Year>2001</Year>
+					  <Month>1</Month>
+					  <Day>1</Day>
+					</To>
+				  </Start>
+				  <End Approximate="false" YearFixed="false" MonthFixed="false" DayFixed="false">
+					<From>
+					  <Year>2003</Year>
+					  <Month>12</Month>
+					  <Day>31</Day>
+					</From>
+					<To>
+					  <Year>2003</Year>
+					  <Month>12</Month>
+					  <Day>31</Day>
+					</To>
+				  </End>
+				</DatePeriod>
+				<VersionDetail DetailTypeID="1430" />
+			  </FeatureVersion>
+			</Feature>"#,
+        )
+        .unwrap();
+        let date_period = Model::from_ofac_document(&feature);
+        let excepted = Model {
+            id: 1123,
+            feature_type: 646,
+            earliest: Some(NaiveDate::from_ymd(2001, 1, 1)),
+            latest: Some(NaiveDate::from_ymd(2003, 12, 31)),
+            approximate: false,
+            value: "2001 TO 2003".to_owned(),
+            reliability: 1,
+            comment: None,
+            topmaj: "N".to_owned(),
+        };
+        assert_eq!(Some(excepted), date_period);
+    }
+}
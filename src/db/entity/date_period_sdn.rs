@@ -0,0 +1,49 @@
+use sea_orm::entity::prelude::*;
+
+use crate::db::OfacRelEntity;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "date_period_sdn")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub date_period_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub sdn_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(belongs_to = "super::date_period::Entity", from = "Column::DatePeriodId", to = "super::date_period::Column::Id", on_update = "Restrict", on_delete = "Restrict")]
+    DatePeriod,
+    #[sea_orm(belongs_to = "super::sdn::Entity", from = "Column::SdnId", to = "super::sdn::Column::FixedRef", on_update = "Restrict", on_delete = "Restrict")]
+    Sdn,
+}
+
+impl Related<super::date_period::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DatePeriod.def()
+    }
+}
+
+impl Related<super::sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Sdn.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl OfacRelEntity for ActiveModel {}
+
+#[derive(Debug)]
+pub struct SdnToDatePeriod;
+
+impl Linked for SdnToDatePeriod {
+    type FromEntity = super::sdn::Entity;
+
+    type ToEntity = super::date_period::Entity;
+
+    fn link(&self) -> Vec<RelationDef> {
+        vec![Relation::Sdn.def().rev(), Entity::belongs_to(super::date_period::Entity).from(Column::DatePeriodId).to(super::date_period::Column::Id).into()]
+    }
+}
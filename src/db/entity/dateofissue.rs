@@ -6,6 +6,8 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: i32,
     pub last_document: Date,
+    /// Date of issue of the last document rolled back, it is only imported again when forced
+    pub rolled_back_from: Option<Date>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -0,0 +1,113 @@
+use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
+
+use crate::db::{impl_topmaj, OfacEntity};
+use crate::document::inputs::known::{FeatureKind, KnownValue};
+use crate::document::models::feature::Feature;
+use crate::document::OfacDocumentReferences;
+
+/// FeatureTypes named `Digital Currency Address - <currency>`
+const FEATURE_PREFIX: &str = "DIGITAL CURRENCY ADDRESS";
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
+#[sea_orm(table_name = "digital_currency_address")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Currency code (e.g. XBT, ETH)
+    #[sea_orm(column_type = "Text")]
+    pub currency: String,
+    /// Address as published, addresses are case sensitive
+    #[sea_orm(column_type = "Text")]
+    pub address: String,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
+    #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
+    pub topmaj: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::digital_currency_address_sdn::Entity")]
+    DigitalCurrencyAddressSdn,
+}
+
+impl Related<super::digital_currency_address_sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DigitalCurrencyAddressSdn.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Currency of a FeatureType named `Digital Currency Address - <currency>`, `None` for the other FeatureTypes
+pub fn currency(feature_type: i32, references: &OfacDocumentReferences) -> Option<String> {
+    let name = references.value_name(FeatureKind::SET, feature_type).or_else(|| FeatureKind::from_id(feature_type).and_then(FeatureKind::name))?;
+    let name = name.trim().to_uppercase();
+    let currency = name.strip_prefix(FEATURE_PREFIX)?.trim_start_matches(|c: char| c.is_whitespace() || c == '-').trim();
+    if currency.is_empty() {
+        return Some("UNKNOWN".to_owned());
+    }
+    Some(currency.to_owned())
+}
+
+impl Model {
+    /// Address of a digital currency feature, `None` for the other features and the features without a value
+    pub fn from_ofac_document(entity: &Feature, references: &OfacDocumentReferences) -> Option<Model> {
+        let currency = currency(entity.feature_type, references)?;
+        let address = entity.version.detail.as_ref()?.value.as_ref()?.trim();
+        if address.is_empty() {
+            return None;
+        }
+        Some(Model {
+            id: entity.version.id,
+            currency,
+            address: address.to_owned(),
+            reliability: entity.version.reliability_id,
+            comment: entity.version.comment(),
+            topmaj: "N".to_owned(),
+        })
+    }
+}
+
+impl_topmaj! {
+    Entity, Model, super::digital_currency_address_sdn::ActiveModel, ActiveModel
+}
+
+#[cfg(test)]
+mod digital_currency_address {
+    use super::*;
+    use crate::document::models::referencevaluesets::FeatureType;
+    use quick_xml::de::from_str;
+
+    #[test]
+    fn parse_digital_currency_address() {
+        let feature: Feature = from_str(
+            r#"
+			<Feature ID="35183" FeatureTypeID="1267">
+			  <FeatureVersion ID="35184" ReliabilityID="1">
+				<Comment />
+				<VersionDetail DetailTypeID="1432">TQn9Y2khEsLJW1ChVWFMSMeRDow5KcbLSE</VersionDetail>
+			  </FeatureVersion>
+			</Feature>"#,
+        )
+        .unwrap();
+        let mut references = OfacDocumentReferences::default();
+        assert_eq!(None, Model::from_ofac_document(&feature, &references));
+        references.feature_types.types.push(FeatureType {
+            id: 1267,
+            value: "Digital Currency Address - TRX".to_owned(),
+        });
+        let excepted = Model {
+            id: 35184,
+            currency: "TRX".to_owned(),
+            address: "TQn9Y2khEsLJW1ChVWFMSMeRDow5KcbLSE".to_owned(),
+            reliability: 1,
+            comment: None,
+            topmaj: "N".to_owned(),
+        };
+        assert_eq!(Some(excepted), Model::from_ofac_document(&feature, &references));
+        assert_eq!(Some("XBT".to_owned()), currency(FeatureKind::DigitalCurrencyXbt.id(), &OfacDocumentReferences::default()));
+        assert_eq!(None, currency(FeatureKind::Website.id(), &OfacDocumentReferences::default()));
+    }
+}
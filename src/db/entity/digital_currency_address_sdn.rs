@@ -0,0 +1,49 @@
+use sea_orm::entity::prelude::*;
+
+use crate::db::OfacRelEntity;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "digital_currency_address_sdn")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub digital_currency_address_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub sdn_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(belongs_to = "super::digital_currency_address::Entity", from = "Column::DigitalCurrencyAddressId", to = "super::digital_currency_address::Column::Id", on_update = "Restrict", on_delete = "Restrict")]
+    DigitalCurrencyAddress,
+    #[sea_orm(belongs_to = "super::sdn::Entity", from = "Column::SdnId", to = "super::sdn::Column::FixedRef", on_update = "Restrict", on_delete = "Restrict")]
+    Sdn,
+}
+
+impl Related<super::digital_currency_address::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DigitalCurrencyAddress.def()
+    }
+}
+
+impl Related<super::sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Sdn.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl OfacRelEntity for ActiveModel {}
+
+#[derive(Debug)]
+pub struct SdnToDigitalCurrencyAddress;
+
+impl Linked for SdnToDigitalCurrencyAddress {
+    type FromEntity = super::sdn::Entity;
+
+    type ToEntity = super::digital_currency_address::Entity;
+
+    fn link(&self) -> Vec<RelationDef> {
+        vec![Relation::Sdn.def().rev(), Entity::belongs_to(super::digital_currency_address::Entity).from(Column::DigitalCurrencyAddressId).to(super::digital_currency_address::Column::Id).into()]
+    }
+}
@@ -1,6 +1,7 @@
+use log::warn;
 use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
 
-use crate::document::models::feature::FeatureVersion;
+use crate::document::models::feature::{DatePeriodShape, FeatureVersion};
 
 use crate::db::{impl_topmaj, OfacEntity};
 
@@ -11,6 +12,12 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(column_type = "Text")]
     pub dob: String,
+    pub earliest: Option<Date>,
+    pub latest: Option<Date>,
+    pub approximate: bool,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
@@ -30,94 +37,27 @@ impl Related<super::dob_identity::Entity> for Entity {
 impl ActiveModelBehavior for ActiveModel {}
 
 impl Model {
-    /// TODO : simplified conditions here
     pub fn from_ofac_document(entity: &FeatureVersion) -> Model {
         let mut dob = Model {
             id: entity.id,
+            reliability: entity.reliability_id,
+            comment: entity.comment(),
             topmaj: "N".to_owned(),
             ..Default::default()
         };
-        let date_of_period = entity.date_period.as_ref().unwrap();
-        let start = date_of_period.start.as_ref().unwrap();
-        let end = date_of_period.end.as_ref().unwrap();
-        // Unique dmy date
-        if start.from == start.to && end.from == end.to && start.from == end.from {
-            if !start.approximate && !end.approximate {
-                dob.dob = start.from.to_ofac_string_dmy();
-            } else {
-                dob.dob = format!("CIRCA {}", start.from.to_ofac_string_dmy());
-            }
-        }
-        // Only year date
-        else if start.from.is_first_day_of_month()
-            && start.to.is_first_day_of_month()
-            && end.from.is_last_day_of_month()
-            && end.to.is_last_day_of_month()
-            && start.from.is_first_month_of_year()
-            && start.to.is_first_month_of_year()
-            && end.from.is_last_month_of_year()
-            && end.to.is_last_month_of_year()
-            && start.from.year == start.to.year
-            && end.from.year == end.to.year
-            && start.from.year == end.from.year
-        {
-            if !start.approximate && !end.approximate {
-                dob.dob = start.from.year.to_uppercase();
-            } else {
-                dob.dob = format!("CIRCA {}", start.from.year);
-            }
+        if entity.date_period.is_none() {
+            warn!("Dob has no DatePeriod for FeatureVersionId={}", dob.id);
+            return dob;
         }
-        // Only month date
-        else if start.from.is_first_day_of_month()
-            && start.to.is_first_day_of_month()
-            && end.from.is_last_day_of_month()
-            && end.to.is_last_day_of_month()
-            && start.from.year == start.to.year
-            && end.from.year == end.to.year
-            && start.from.year == end.from.year
-            && start.from.month == start.to.month
-            && end.from.month == end.to.month
-            && start.from.month == end.from.month
-        {
-            if !start.approximate && !end.approximate {
-                dob.dob = start.from.to_ofac_string_my();
-            } else {
-                dob.dob = format!("CIRCA {}", start.from.to_ofac_string_my());
+        let date_period = entity.date_period.as_ref().unwrap();
+        dob.approximate = date_period.is_approximate();
+        match date_period.bounds() {
+            Some((earliest, latest)) => {
+                dob.earliest = Some(earliest);
+                dob.latest = Some(latest);
+                dob.dob = DatePeriodShape::classify(earliest, latest).to_ofac_string_approximate(dob.approximate);
             }
-        }
-        // Range of years
-        else if start.from.is_first_day_of_month()
-            && start.to.is_last_day_of_month()
-            && end.from.is_first_day_of_month()
-            && end.to.is_last_day_of_month()
-            && start.from.is_first_month_of_year()
-            && start.to.is_last_month_of_year()
-            && end.from.is_first_month_of_year()
-            && end.to.is_last_month_of_year()
-            && start.from.year == start.to.year
-            && end.from.year == end.to.year
-            && start.from.year != end.from.year
-        {
-            dob.dob = format!("{} TO {}", start.from.year, end.from.year);
-        }
-        // Range of months
-        else if start.from.is_first_day_of_month()
-            && start.to.is_first_day_of_month()
-            && end.from.is_last_day_of_month()
-            && end.to.is_last_day_of_month()
-            && start.from.is_first_month_of_year()
-            && start.to.is_first_month_of_year()
-            && end.from.is_first_month_of_year()
-            && end.to.is_first_month_of_year()
-            && start.from.year == start.to.year
-            && end.from.year == end.to.year
-            && start.from.year != end.from.year
-        {
-            dob.dob = format!("{} TO {}", start.from.to_ofac_string_my(), start.to.to_ofac_string_my());
-        }
-        // Range same year only
-        else if start.from.year == start.to.year && end.from.year == end.to.year {
-            dob.dob = format!("{} TO {}", start.from.to_ofac_string_dmy(), end.from.to_ofac_string_dmy());
+            None => warn!("Dob DatePeriod has no valid bounds for FeatureVersionId={}", dob.id),
         }
         dob
     }
@@ -130,6 +70,7 @@ impl_topmaj! {
 #[cfg(test)]
 mod dob {
     use super::*;
+    use chrono::NaiveDate;
     use quick_xml::de::from_str;
 
     #[test]
@@ -172,6 +113,11 @@ mod dob {
         let excepted = Model {
             id: 41040,
             dob: "1961".to_owned(),
+            earliest: Some(NaiveDate::from_ymd(1961, 1, 1)),
+            latest: Some(NaiveDate::from_ymd(1961, 12, 31)),
+            approximate: false,
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, dob);
@@ -217,6 +163,11 @@ mod dob {
         let excepted = Model {
             id: 6684,
             dob: "CIRCA 1948".to_owned(),
+            earliest: Some(NaiveDate::from_ymd(1948, 1, 1)),
+            latest: Some(NaiveDate::from_ymd(1948, 12, 31)),
+            approximate: true,
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, dob);
@@ -262,6 +213,11 @@ mod dob {
         let excepted = Model {
             id: 37877,
             dob: "FEB 1968".to_owned(),
+            earliest: Some(NaiveDate::from_ymd(1968, 2, 1)),
+            latest: Some(NaiveDate::from_ymd(1968, 2, 29)),
+            approximate: false,
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, dob);
@@ -307,6 +263,11 @@ mod dob {
         let excepted = Model {
             id: 37877,
             dob: "FEB 1967".to_owned(),
+            earliest: Some(NaiveDate::from_ymd(1967, 2, 1)),
+            latest: Some(NaiveDate::from_ymd(1967, 2, 28)),
+            approximate: false,
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, dob);
@@ -351,6 +312,11 @@ mod dob {
         let excepted = Model {
             id: 46531,
             dob: "13 APR 1975".to_owned(),
+            earliest: Some(NaiveDate::from_ymd(1975, 4, 13)),
+            latest: Some(NaiveDate::from_ymd(1975, 4, 13)),
+            approximate: false,
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, dob);
@@ -396,6 +362,11 @@ mod dob {
         let excepted = Model {
             id: 6516,
             dob: "CIRCA 07 JUL 1966".to_owned(),
+            earliest: Some(NaiveDate::from_ymd(1966, 7, 7)),
+            latest: Some(NaiveDate::from_ymd(1966, 7, 7)),
+            approximate: true,
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, dob);
@@ -441,6 +412,11 @@ mod dob {
         let excepted = Model {
             id: 1,
             dob: "26 SEP 1946 TO 07 DEC 1946".to_owned(),
+            earliest: Some(NaiveDate::from_ymd(1946, 9, 26)),
+            latest: Some(NaiveDate::from_ymd(1946, 12, 7)),
+            approximate: false,
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, dob);
@@ -486,6 +462,11 @@ mod dob {
         let excepted = Model {
             id: 17587,
             dob: "1984 TO 1986".to_owned(),
+            earliest: Some(NaiveDate::from_ymd(1984, 1, 1)),
+            latest: Some(NaiveDate::from_ymd(1986, 12, 31)),
+            approximate: false,
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, dob);
@@ -1,7 +1,12 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use sea_orm::{entity::prelude::*, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, Iterable, RelationTrait, Set};
 
+use crate::db::batch::WriteBatch;
+use crate::db::changes::{Change, ChangeRecorder};
+use crate::document::inputs::identifier::IdentifierKind;
+use crate::document::inputs::known::DocumentDateKind;
 use crate::document::{models::document::Document, OfacDocumentReferences};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
@@ -12,6 +17,9 @@ pub struct Model {
     pub doctype: Option<i32>,
     #[sea_orm(column_type = "Text", nullable)]
     pub registration_number: Option<String>,
+    /// IMO, MMSI or LEI without separators, `None` for the other documents and the invalid numbers
+    #[sea_orm(column_type = "Text", nullable)]
+    pub normalized_number: Option<String>,
     pub issued_by: Option<i32>,
     pub issued_date: Option<Date>,
     pub expiration_date: Option<Date>,
@@ -73,24 +81,25 @@ impl Model {
         };
         if let Some(issued_by) = entity.registration_number.as_ref() {
             document.registration_number = Some(issued_by.to_uppercase());
+            document.normalized_number = IdentifierKind::of_document(entity.type_id, references).and_then(|kind| kind.validate(issued_by).ok());
         }
         if let Some(issued_by) = entity.issued_by {
-            match references.area_codes.iter().find(|&area| area.id == issued_by) {
+            match references.area_code(issued_by) {
                 Some(area) => document.issued_by = Some(area.id),
                 None => document.issued_by = None,
             }
         }
         if let Some(dates) = entity.dates.as_ref() {
             for date in dates {
-                match date.type_id {
-                    1480 => {
+                match references.resolve::<DocumentDateKind>(date.type_id) {
+                    Some(DocumentDateKind::Issue) => {
                         let start = date.period.start.as_ref().unwrap();
                         let end = date.period.end.as_ref().unwrap();
                         if start.from == start.to && end.from == end.to {
                             document.issued_date = Some(start.from.to_sql_date());
                         }
                     }
-                    1481 => {
+                    Some(DocumentDateKind::Expiration) => {
                         let start = date.period.start.as_ref().unwrap();
                         let end = date.period.end.as_ref().unwrap();
                         if start.from == start.to && end.from == end.to {
@@ -107,54 +116,48 @@ impl Model {
 }
 
 impl ActiveModel {
-    pub async fn process_entities(entities: Vec<Model>, db: &DatabaseConnection, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>) -> Result<(), DbErr> {
-        let in_db = Arc::new(Entity::find().all(db).await?);
-        let tasks: Vec<_> = entities.into_iter().map(move |e| tokio::spawn(ActiveModel::process_entity(e, Arc::clone(&in_db), Arc::clone(tx)))).collect();
+    pub async fn process_entities(entities: Vec<Model>, db: &DatabaseConnection, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>, batch: &Arc<WriteBatch>, changes: &Arc<ChangeRecorder>) -> Result<(), DbErr> {
+        let in_db: Arc<HashMap<i32, Model>> = Arc::new(Entity::find().all(db).await?.into_iter().map(|model| (model.id, model)).collect());
+        let tasks: Vec<_> = entities.into_iter().map(move |e| tokio::spawn(ActiveModel::process_entity(e, Arc::clone(&in_db), Arc::clone(batch), Arc::clone(changes)))).collect();
         for task in tasks {
             task.await.unwrap().unwrap();
         }
-        Ok(())
+        batch.flush(tx).await
     }
-    pub async fn process_entity(mut model: Model, in_db: Arc<Vec<Model>>, tx: Arc<tokio::sync::Mutex<DatabaseTransaction>>) -> Result<(), DbErr> {
-        if insert_if_new_document(&mut model, &in_db, &tx).await? {
+    pub async fn process_entity(mut model: Model, in_db: Arc<HashMap<i32, Model>>, batch: Arc<WriteBatch>, changes: Arc<ChangeRecorder>) -> Result<(), DbErr> {
+        if insert_if_new_document(&mut model, &in_db, &batch, &changes) {
             return Ok(());
         }
         let id = model.id;
-        if let Some(index) = in_db.iter().position(|d| d.id == id) {
-            let mut in_db = in_db[index].clone();
+        if let Some(in_db) = in_db.get(&id) {
+            let mut in_db = in_db.clone();
             in_db.identity = model.identity;
             if in_db == model {
                 return Ok(());
             }
+            changes.record(Change::of_update(&in_db, &model, Some(model.identity)));
             // Update document
-            {
-                let lock = tx.lock().await;
-                set_all_active(model.clone())?.update(&*lock).await?;
-            }
+            batch.update(&set_all_active(model.clone())?);
         }
         Ok(())
     }
 }
 
 // Return true if inserted (i.e new document)
-async fn insert_if_new_document(model: &mut Model, in_db: &Arc<Vec<Model>>, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>) -> Result<bool, DbErr> {
+fn insert_if_new_document(model: &mut Model, in_db: &HashMap<i32, Model>, batch: &WriteBatch, changes: &ChangeRecorder) -> bool {
     let id = model.id;
     let identity = model.identity;
-    if in_db.iter().any(|m| m.id == id) {
-        return Ok(false);
+    if in_db.contains_key(&id) {
+        return false;
     }
     model.topmaj = "O".to_owned();
-    {
-        let lock = tx.lock().await;
-        ActiveModel::from(model.clone()).insert(&*lock).await?;
-        super::document_identity::ActiveModel {
-            identity_id: Set(identity),
-            document_id: Set(id),
-        }
-        .insert(&*lock)
-        .await?;
-    }
-    Ok(false)
+    batch.insert(&ActiveModel::from(model.clone()));
+    changes.record(Change::of_insert(&*model, Some(identity)));
+    batch.insert(&super::document_identity::ActiveModel {
+        identity_id: Set(identity),
+        document_id: Set(id),
+    });
+    false
 }
 
 fn set_all_active(document: Model) -> Result<ActiveModel, DbErr> {
@@ -4,6 +4,7 @@ use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
 use crate::document::models::feature::FeatureVersion;
 
 use crate::db::{impl_parse_feature_version_value, impl_topmaj, OfacEntity};
+use crate::document::inputs::identifier::IdentifierKind;
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
 #[sea_orm(table_name = "isin")]
@@ -12,6 +13,12 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(column_type = "Text")]
     pub isin: String,
+    /// ISIN without separators, `None` when the ISIN is invalid
+    #[sea_orm(column_type = "Text", nullable)]
+    pub normalized: Option<String>,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
@@ -34,6 +41,14 @@ impl_topmaj! {
     Entity, Model, super::isin_sdn::ActiveModel, ActiveModel
 }
 
+impl Model {
+    /// Set the normalized ISIN when the published one is valid
+    pub fn with_normalized(mut self) -> Model {
+        self.normalized = IdentifierKind::Isin.validate(&self.isin).ok();
+        self
+    }
+}
+
 impl_parse_feature_version_value! {
     Model, Model, isin, "Isin"
 }
@@ -2,6 +2,8 @@ pub mod prelude;
 
 pub mod address;
 pub mod address_sdn;
+pub mod address_variant;
+pub mod address_variant_sdn;
 pub mod aircraft_operator;
 pub mod aircraft_operator_sdn;
 pub mod bic;
@@ -12,6 +14,8 @@ pub mod caatsa235;
 pub mod caatsa235_sdn;
 pub mod citizen;
 pub mod citizen_sdn;
+pub mod date_period;
+pub mod date_period_sdn;
 pub mod dateofissue;
 pub mod ddc_alias;
 pub mod ddc_alias_sdn;
@@ -19,6 +23,8 @@ pub mod ddc_bic;
 pub mod ddc_bic_sdn;
 pub mod ddc_name;
 pub mod ddc_pgm;
+pub mod digital_currency_address;
+pub mod digital_currency_address_sdn;
 pub mod dob;
 pub mod dob_identity;
 pub mod document;
@@ -57,10 +63,16 @@ pub mod ref_document;
 pub mod ref_feature;
 pub mod ref_reference;
 pub mod ref_type;
+pub mod ref_value;
 pub mod relation;
 pub mod relation_sdn;
 pub mod sanction;
+pub mod sanction_event;
+pub mod sanction_event_sdn;
+pub mod sanction_measure;
+pub mod sanction_measure_sdn;
 pub mod sdn;
+pub mod sdn_history;
 pub mod sdn_program;
 pub mod target;
 pub mod target_sdn;
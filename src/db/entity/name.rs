@@ -1,9 +1,12 @@
+use std::collections::HashMap;
+
 use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
 
 use crate::document::models::distinctparty::{Identity, MasterNamePartGroup, NamePartGroups};
 
 use crate::db::{impl_topmaj, OfacEntity};
-use crate::document::models::referencevaluesets::ScriptValues;
+use crate::document::inputs::known::NamePart;
+use crate::document::OfacDocumentReferences;
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
 #[sea_orm(table_name = "name")]
@@ -56,9 +59,11 @@ impl Related<super::name_sdn::Entity> for Entity {
 impl ActiveModelBehavior for ActiveModel {}
 
 impl Model {
-    pub fn from_ofac_document(entity: &Identity, name_part_groups: &NamePartGroups, script_values: &ScriptValues) -> Vec<Model> {
+    pub fn from_ofac_document(entity: &Identity, name_part_groups: &NamePartGroups, references: &OfacDocumentReferences) -> Vec<Model> {
         let mut name = Vec::new();
         let mut full_name = String::new();
+        let latin_script = references.latin_script_id();
+        let groups: HashMap<i32, &MasterNamePartGroup> = name_part_groups.master_name_part_group.iter().map(|group| (group.name_part_group.id, group)).collect();
         for sdn_name in entity.alias.iter() {
             let is_primary = sdn_name.primary;
             let quality = if sdn_name.quality { "Low".to_string() } else { "Normal".to_string() };
@@ -73,10 +78,13 @@ impl Model {
                 full_name.clear();
                 for document_part in documented_name.parts.iter() {
                     let name_part_group_id = document_part.name_part.name_part_group_id;
-                    let name_part_type_id = name_part_groups.master_name_part_group.iter().find(|n| n.name_part_group.id == name_part_group_id).unwrap();
+                    let name_part_type_id = groups[&name_part_group_id];
                     let name = document_part.name_part.name.clone();
-                    model_name.split_name(script_values, name_part_type_id, name, document_part.name_part.script_id);
-                    model_name.script = document_part.name_part.script_id;
+                    model_name.split_name(references, name_part_type_id, name, document_part.name_part.script_id);
+                    // A name written in its original script keeps that script even if some of its parts are latin
+                    if model_name.script == 0 || model_name.script == latin_script {
+                        model_name.script = document_part.name_part.script_id;
+                    }
                     if is_primary && document_part.name_part.script_id == 215 && name_status == 1 {
                         model_name.is_primary_215 = true;
                         model_name.name_type = "NAME".to_owned();
@@ -88,34 +96,34 @@ impl Model {
         name
     }
 
-    fn split_name(&mut self, script_values: &ScriptValues, name_part_type_id: &MasterNamePartGroup, name: Option<String>, script_id: i32) {
+    fn split_name(&mut self, references: &OfacDocumentReferences, name_part_type_id: &MasterNamePartGroup, name: Option<String>, script_id: i32) {
         let mut is_arabic = false;
-        if let Some(arabic_script) = script_values.scripts.iter().find(|v| v.code == "Arab") {
+        if let Some(arabic_script) = references.script_values.scripts.iter().find(|v| v.code == "Arab") {
             is_arabic = arabic_script.id == script_id;
         }
-        match name_part_type_id.name_part_group.name_part_type_id {
-            1520 => {
+        match references.resolve::<NamePart>(name_part_type_id.name_part_group.name_part_type_id) {
+            Some(NamePart::LastName) => {
                 if !is_arabic {
                     self.last_name = name
                 } else {
                     self.first_name = name
                 }
             }
-            1521 => {
+            Some(NamePart::FirstName) => {
                 if !is_arabic {
                     self.first_name = name
                 } else {
                     self.last_name = name
                 }
             }
-            1522 => self.middle_name = name,
-            1523 => self.maiden_name = name,
-            1524 => self.aircraft_name = name,
-            1525 => self.entity_name = name,
-            1526 => self.vessel_name = name,
-            1528 => self.nickname = name,
-            91708 => self.patronymic = name,
-            91709 => self.matronymic = name,
+            Some(NamePart::MiddleName) => self.middle_name = name,
+            Some(NamePart::MaidenName) => self.maiden_name = name,
+            Some(NamePart::AircraftName) => self.aircraft_name = name,
+            Some(NamePart::EntityName) => self.entity_name = name,
+            Some(NamePart::VesselName) => self.vessel_name = name,
+            Some(NamePart::Nickname) => self.nickname = name,
+            Some(NamePart::Patronymic) => self.patronymic = name,
+            Some(NamePart::Matronymic) => self.matronymic = name,
             _ => {}
         }
     }
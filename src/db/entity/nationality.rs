@@ -2,7 +2,7 @@ use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
 
 use crate::document::{models::feature::Feature, models::location::Locations, OfacDocumentReferences};
 
-use crate::db::{impl_topmaj, OfacEntity};
+use crate::db::{impl_topmaj, ConversionErr, OfacEntity};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
 #[sea_orm(table_name = "nationality")]
@@ -10,6 +10,9 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: i32,
     pub nationality: Option<i32>,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
@@ -45,16 +48,23 @@ impl ActiveModelBehavior for ActiveModel {}
 pub struct NationalityEntity<'a>(pub &'a Feature, pub &'a Locations);
 
 impl Model {
-    pub fn from_ofac_document(entity: &NationalityEntity<'_>, references: &OfacDocumentReferences) -> Model {
-        let mut nationality = Model { id: entity.0.id, ..Default::default() };
-        let location_id = entity.0.version.location.unwrap().id;
-        let location = entity.1.locations.iter().find(|&loc| loc.id == location_id).unwrap();
+    pub fn from_ofac_document(entity: &NationalityEntity<'_>, references: &OfacDocumentReferences) -> Result<Model, ConversionErr> {
+        let mut nationality = Model {
+            id: entity.0.id,
+            reliability: entity.0.version.reliability_id,
+            comment: entity.0.version.comment(),
+            ..Default::default()
+        };
+        let location = match entity.0.version.location.and_then(|location| entity.1.get(location.id)) {
+            Some(location) => location,
+            None => return Err(ConversionErr::MissingLocation(entity.0.version.id)),
+        };
         if let Some(parts) = location.location_parts.as_ref() {
             for part in parts {
                 for part_value in part.values.iter() {
                     if part_value.primary {
                         let value = part_value.value.to_owned();
-                        match references.area_codes.iter().find(|&area| area.name == value) {
+                        match references.area_code_by_name(&value) {
                             Some(area) => nationality.nationality = Some(area.id),
                             None => nationality.nationality = None,
                         }
@@ -62,7 +72,7 @@ impl Model {
                 }
             }
         }
-        nationality
+        Ok(nationality)
     }
 }
 
@@ -2,7 +2,7 @@ use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
 
 use crate::document::{models::feature::Feature, models::location::Locations};
 
-use crate::db::{impl_topmaj, OfacEntity};
+use crate::db::{impl_topmaj, ConversionErr, OfacEntity};
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
 #[sea_orm(table_name = "nationality_registration")]
 pub struct Model {
@@ -10,6 +10,9 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(column_type = "Text", nullable)]
     pub location: Option<String>,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
@@ -31,14 +34,18 @@ impl ActiveModelBehavior for ActiveModel {}
 pub struct NationalityRegistrationEntity<'a>(pub &'a Feature, pub &'a Locations);
 
 impl Model {
-    pub fn from_ofac_document(entity: &NationalityRegistrationEntity<'_>) -> Model {
+    pub fn from_ofac_document(entity: &NationalityRegistrationEntity<'_>) -> Result<Model, ConversionErr> {
         let mut nationality_registration = Model {
             id: entity.0.id,
             location: None,
+            reliability: entity.0.version.reliability_id,
+            comment: entity.0.version.comment(),
             ..Default::default()
         };
-        let location_id = entity.0.version.location.unwrap().id;
-        let location = entity.1.locations.iter().find(|&loc| loc.id == location_id).unwrap();
+        let location = match entity.0.version.location.and_then(|location| entity.1.get(location.id)) {
+            Some(location) => location,
+            None => return Err(ConversionErr::MissingLocation(entity.0.version.id)),
+        };
         if let Some(parts) = location.location_parts.as_ref() {
             for part in parts {
                 for part_value in part.values.iter() {
@@ -48,7 +55,7 @@ impl Model {
                 }
             }
         }
-        nationality_registration
+        Ok(nationality_registration)
     }
 }
 
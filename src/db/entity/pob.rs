@@ -11,6 +11,9 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(column_type = "Text")]
     pub pob: String,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
@@ -56,6 +59,8 @@ mod pob {
         let excepted = Model {
             id: 2995,
             pob: "CULIACAN, SINALOA, MEXICO".to_owned(),
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, pob);
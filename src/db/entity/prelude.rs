@@ -1,5 +1,7 @@
 pub use super::address::Entity as Address;
 pub use super::address_sdn::Entity as AddressSdn;
+pub use super::address_variant::Entity as AddressVariant;
+pub use super::address_variant_sdn::Entity as AddressVariantSdn;
 pub use super::aircraft_operator::Entity as AircraftOperator;
 pub use super::aircraft_operator_sdn::Entity as AircraftOperatorSdn;
 pub use super::bic::Entity as Bic;
@@ -10,6 +12,8 @@ pub use super::caatsa235::Entity as Caatsa235;
 pub use super::caatsa235_sdn::Entity as Caatsa235Sdn;
 pub use super::citizen::Entity as Citizen;
 pub use super::citizen_sdn::Entity as CitizenSdn;
+pub use super::date_period::Entity as DatePeriod;
+pub use super::date_period_sdn::Entity as DatePeriodSdn;
 pub use super::dateofissue::Entity as Dateofissue;
 pub use super::ddc_alias::Entity as DdcAlias;
 pub use super::ddc_alias_sdn::Entity as DdcAliasSdn;
@@ -17,6 +21,8 @@ pub use super::ddc_bic::Entity as DdcBic;
 pub use super::ddc_bic_sdn::Entity as DdcBicSdn;
 pub use super::ddc_name::Entity as DdcName;
 pub use super::ddc_pgm::Entity as DdcPgm;
+pub use super::digital_currency_address::Entity as DigitalCurrencyAddress;
+pub use super::digital_currency_address_sdn::Entity as DigitalCurrencyAddressSdn;
 pub use super::dob::Entity as Dob;
 pub use super::dob_identity::Entity as DobIdentity;
 pub use super::document::Entity as Document;
@@ -53,9 +59,15 @@ pub use super::ref_document::Entity as RefDocument;
 pub use super::ref_feature::Entity as RefFeature;
 pub use super::ref_reference::Entity as RefReference;
 pub use super::ref_type::Entity as RefType;
+pub use super::ref_value::Entity as RefValue;
 pub use super::relation::Entity as Relation;
 pub use super::relation_sdn::Entity as RelationSdn;
+pub use super::sanction_event::Entity as SanctionEvent;
+pub use super::sanction_event_sdn::Entity as SanctionEventSdn;
+pub use super::sanction_measure::Entity as SanctionMeasure;
+pub use super::sanction_measure_sdn::Entity as SanctionMeasureSdn;
 pub use super::sdn::Entity as Sdn;
+pub use super::sdn_history::Entity as SdnHistory;
 pub use super::sdn_program::Entity as SdnProgram;
 pub use super::target::Entity as Target;
 pub use super::target_sdn::Entity as TargetSdn;
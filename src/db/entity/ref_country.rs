@@ -1,8 +1,10 @@
 use async_trait::async_trait;
 use sea_orm::{entity::prelude::*, DatabaseTransaction, Iterable};
+use std::collections::HashMap;
 use tokio::sync::MutexGuard;
 
 use crate::{
+    db::changes::{Change, ChangeRecorder},
     db::OfacRefEntity,
     document::{models::areacode::AreaCode, OfacDocumentReferences},
 };
@@ -56,10 +58,10 @@ impl ActiveModelBehavior for ActiveModel {}
 
 #[async_trait]
 impl OfacRefEntity<AreaCode, ActiveModel, Model> for ActiveModel {
-    async fn from_ofac_document(entity: &AreaCode, in_db: &[Model], _references: &OfacDocumentReferences, tx: &MutexGuard<DatabaseTransaction>) -> Result<Option<ActiveModel>, DbErr> {
+    async fn from_ofac_document(entity: &AreaCode, in_db: &HashMap<i32, Model>, _references: &OfacDocumentReferences, tx: &MutexGuard<DatabaseTransaction>, changes: &ChangeRecorder) -> Result<Option<ActiveModel>, DbErr> {
         let id = entity.id;
         let model = Model { id, value: entity.name.to_uppercase() };
-        match in_db.iter().find(|reference| reference.id == id) {
+        match in_db.get(&id) {
             Some(e) => {
                 if e == &model {
                     return Ok(None);
@@ -72,7 +74,8 @@ impl OfacRefEntity<AreaCode, ActiveModel, Model> for ActiveModel {
                 Ok(Some(am))
             }
             None => {
-                ActiveModel::from(model).insert(&**tx).await?;
+                let inserted = ActiveModel::from(model).insert(&**tx).await?;
+                changes.record(Change::of_insert(&inserted, None));
                 Ok(None)
             }
         }
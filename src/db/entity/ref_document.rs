@@ -1,8 +1,10 @@
 use async_trait::async_trait;
 use sea_orm::{entity::prelude::*, DatabaseTransaction, Iterable};
+use std::collections::HashMap;
 use tokio::sync::MutexGuard;
 
 use crate::{
+    db::changes::{Change, ChangeRecorder},
     db::OfacRefEntity,
     document::{models::referencevaluesets::IDRegDocType, OfacDocumentReferences},
 };
@@ -32,10 +34,10 @@ impl ActiveModelBehavior for ActiveModel {}
 
 #[async_trait]
 impl OfacRefEntity<IDRegDocType, ActiveModel, Model> for ActiveModel {
-    async fn from_ofac_document(entity: &IDRegDocType, in_db: &[Model], _references: &OfacDocumentReferences, tx: &MutexGuard<DatabaseTransaction>) -> Result<Option<ActiveModel>, DbErr> {
+    async fn from_ofac_document(entity: &IDRegDocType, in_db: &HashMap<i32, Model>, _references: &OfacDocumentReferences, tx: &MutexGuard<DatabaseTransaction>, changes: &ChangeRecorder) -> Result<Option<ActiveModel>, DbErr> {
         let id = entity.id;
         let model = Model { id, value: entity.value.to_uppercase() };
-        match in_db.iter().find(|reference| reference.id == id) {
+        match in_db.get(&id) {
             Some(e) => {
                 if e == &model {
                     return Ok(None);
@@ -48,7 +50,8 @@ impl OfacRefEntity<IDRegDocType, ActiveModel, Model> for ActiveModel {
                 Ok(Some(am))
             }
             None => {
-                ActiveModel::from(model).insert(&**tx).await?;
+                let inserted = ActiveModel::from(model).insert(&**tx).await?;
+                changes.record(Change::of_insert(&inserted, None));
                 Ok(None)
             }
         }
@@ -1,8 +1,10 @@
 use async_trait::async_trait;
 use sea_orm::{entity::prelude::*, DatabaseTransaction, Iterable};
+use std::collections::HashMap;
 use tokio::sync::MutexGuard;
 
 use crate::{
+    db::changes::{Change, ChangeRecorder},
     db::OfacRefEntity,
     document::{models::referencevaluesets::FeatureType, OfacDocumentReferences},
 };
@@ -29,10 +31,10 @@ impl ActiveModelBehavior for ActiveModel {}
 
 #[async_trait]
 impl OfacRefEntity<FeatureType, ActiveModel, Model> for ActiveModel {
-    async fn from_ofac_document(entity: &FeatureType, in_db: &[Model], _references: &OfacDocumentReferences, tx: &MutexGuard<DatabaseTransaction>) -> Result<Option<ActiveModel>, DbErr> {
+    async fn from_ofac_document(entity: &FeatureType, in_db: &HashMap<i32, Model>, _references: &OfacDocumentReferences, tx: &MutexGuard<DatabaseTransaction>, changes: &ChangeRecorder) -> Result<Option<ActiveModel>, DbErr> {
         let id = entity.id;
         let model = Model { id, value: entity.value.to_uppercase() };
-        match in_db.iter().find(|reference| reference.id == id) {
+        match in_db.get(&id) {
             Some(e) => {
                 if e == &model {
                     return Ok(None);
@@ -45,7 +47,8 @@ impl OfacRefEntity<FeatureType, ActiveModel, Model> for ActiveModel {
                 Ok(Some(am))
             }
             None => {
-                ActiveModel::from(model).insert(&**tx).await?;
+                let inserted = ActiveModel::from(model).insert(&**tx).await?;
+                changes.record(Change::of_insert(&inserted, None));
                 Ok(None)
             }
         }
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use sea_orm::{entity::prelude::*, DatabaseTransaction, Iterable};
+use std::collections::HashMap;
+use tokio::sync::MutexGuard;
+
+use crate::{
+    db::changes::{Change, ChangeRecorder},
+    db::OfacRefEntity,
+    document::{models::referencevaluesets::ReferenceValue, OfacDocumentReferences},
+};
+
+/// Values of the ReferenceValueSets without their own table, `reference_set` is the name of the set (e.g. `NamePartType`)
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "ref_value")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub reference_set: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub value: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[async_trait]
+impl<'a> OfacRefEntity<(&'a str, &'a ReferenceValue), ActiveModel, Model, (String, i32)> for ActiveModel {
+    async fn from_ofac_document(entity: &(&'a str, &'a ReferenceValue), in_db: &HashMap<(String, i32), Model>, _references: &OfacDocumentReferences, tx: &MutexGuard<DatabaseTransaction>, changes: &ChangeRecorder) -> Result<Option<ActiveModel>, DbErr> {
+        let (reference_set, value) = *entity;
+        let model = Model {
+            reference_set: reference_set.to_owned(),
+            id: value.id,
+            value: value.value.to_uppercase(),
+        };
+        match in_db.get(&(reference_set.to_owned(), value.id)) {
+            Some(e) => {
+                if e == &model {
+                    return Ok(None);
+                }
+                let mut am: ActiveModel = model.into();
+                for col in <<ActiveModel as sea_orm::ActiveModelTrait>::Entity as sea_orm::EntityTrait>::Column::iter() {
+                    let v = am.get(col);
+                    am.set(col, v.into_value().unwrap());
+                }
+                Ok(Some(am))
+            }
+            None => {
+                let inserted = ActiveModel::from(model).insert(&**tx).await?;
+                changes.record(Change::of_insert(&inserted, None));
+                Ok(None)
+            }
+        }
+    }
+}
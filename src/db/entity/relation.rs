@@ -1,5 +1,8 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::db::batch::WriteBatch;
+use crate::db::changes::{Change, ChangeRecorder};
 use crate::document::models::profilerelationship::ProfileRelationship;
 use sea_orm::{entity::prelude::*, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, Iterable, RelationTrait, Set};
 
@@ -57,53 +60,51 @@ impl Model {
 }
 
 impl ActiveModel {
-    pub async fn process_entities(entities: Vec<Model>, db: &DatabaseConnection, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>) -> Result<(), DbErr> {
-        let in_db = Entity::find().all(db).await?;
+    pub async fn process_entities(entities: Vec<Model>, db: &DatabaseConnection, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>, batch: &Arc<WriteBatch>, changes: &Arc<ChangeRecorder>) -> Result<(), DbErr> {
+        let in_db: Arc<HashMap<i32, Model>> = Arc::new(Entity::find().all(db).await?.into_iter().map(|model| (model.id, model)).collect());
         let tasks: Vec<_> = entities
             .into_iter()
-            .map(move |e| {
-                let tx = Arc::clone(tx);
-                tokio::spawn(ActiveModel::process_entity(e, in_db.clone(), tx))
-            })
+            .map(move |e| tokio::spawn(ActiveModel::process_entity(e, Arc::clone(&in_db), Arc::clone(batch), Arc::clone(changes))))
             .collect();
         for task in tasks {
             task.await.unwrap().unwrap();
         }
-        Ok(())
+        batch.flush(tx).await
     }
-    pub async fn process_entity(mut model: Model, in_db: Vec<Model>, tx: Arc<tokio::sync::Mutex<DatabaseTransaction>>) -> Result<(), DbErr> {
-        if insert_if_new_relation(&mut model, &in_db, &tx).await? {
+    pub async fn process_entity(mut model: Model, in_db: Arc<HashMap<i32, Model>>, batch: Arc<WriteBatch>, changes: Arc<ChangeRecorder>) -> Result<(), DbErr> {
+        if insert_if_new_relation(&mut model, &in_db, &batch, &changes) {
             return Ok(());
         }
         let id = model.id;
-        if let Some(index) = in_db.iter().position(|d| d.id == id) {
-            let in_db = in_db[index].clone();
-            if in_db == model {
+        if let Some(in_db) = in_db.get(&id) {
+            if *in_db == model {
                 return Ok(());
             }
+            changes.record(Change {
+                fixed_ref: Some(model.from_profile_id),
+                ..Change::of_update(in_db, &model, Some(model.from_profile_id))
+            });
             // Update relation
-            {
-                let lock = tx.lock().await;
-                set_all_active(model.clone())?.update(&*lock).await?;
-            }
+            batch.update(&set_all_active(model.clone())?);
         }
         Ok(())
     }
 }
 
 // Return true if inserted (i.e new relation)
-async fn insert_if_new_relation(model: &mut Model, in_db: &[Model], tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>) -> Result<bool, DbErr> {
+fn insert_if_new_relation(model: &mut Model, in_db: &HashMap<i32, Model>, batch: &WriteBatch, changes: &ChangeRecorder) -> bool {
     let id = model.id;
     let sdn = model.from_profile_id;
-    if in_db.iter().any(|m| m.id == id) {
-        return Ok(false);
-    }
-    {
-        let lock = tx.lock().await;
-        ActiveModel::from(model.clone()).insert(&*lock).await?;
-        super::relation_sdn::ActiveModel { sdn_id: Set(sdn), relation_id: Set(id) }.insert(&*lock).await?;
+    if in_db.contains_key(&id) {
+        return false;
     }
-    Ok(false)
+    batch.insert(&ActiveModel::from(model.clone()));
+    changes.record(Change {
+        fixed_ref: Some(sdn),
+        ..Change::of_insert(&*model, Some(sdn))
+    });
+    batch.insert(&super::relation_sdn::ActiveModel { sdn_id: Set(sdn), relation_id: Set(id) });
+    false
 }
 
 fn set_all_active(relation: Model) -> Result<ActiveModel, DbErr> {
@@ -1,4 +1,6 @@
-use sea_orm::{entity::prelude::*, DbErr};
+use sea_orm::entity::prelude::*;
+
+use crate::db::ConversionErr;
 
 use crate::document::{models::sanction::SanctionsEntry, OfacDocumentReferences};
 
@@ -9,20 +11,28 @@ pub struct Model {
     pub status: String,
     pub topmaj: String,
     pub programs: Vec<super::program::Model>,
+    pub events: Vec<super::sanction_event::Model>,
+    pub measures: Vec<super::sanction_measure::Model>,
     pub sdn_id: i32,
 }
 
 impl Model {
-    pub fn from_ofac_document(entity: &SanctionsEntry, _references: &OfacDocumentReferences) -> Result<Model, DbErr> {
+    pub fn from_ofac_document(entity: &SanctionsEntry, _references: &OfacDocumentReferences) -> Result<Model, ConversionErr> {
         let id = entity.id;
-        let entry = entity.events.get(0).unwrap();
+        let events = entity.events.iter().map(super::sanction_event::Model::from_ofac_document).collect::<Result<Vec<_>, _>>()?;
+        let date = match events.first() {
+            Some(event) => event.event_date,
+            None => return Err(ConversionErr::MissingSanctionsEvent(id)),
+        };
         let mut sanction = Model {
             id,
             status: "ACTIVE".to_owned(),
             topmaj: "N".to_owned(),
             sdn_id: entity.profile_id,
-            date: entry.date.to_sql_date(),
+            date,
             programs: Vec::new(),
+            events,
+            measures: entity.measures.iter().map(super::sanction_measure::Model::from_ofac_document).collect(),
         };
         for measure in entity.measures.iter() {
             if measure.program.is_none() {
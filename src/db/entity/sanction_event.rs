@@ -0,0 +1,78 @@
+use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
+
+use crate::db::{impl_topmaj, ConversionErr, OfacEntity};
+use crate::document::models::sanction::EntryEvent;
+
+/// An EntryEvent of the SanctionsEntry (designation, amendment, delisting), `event_type` and `legal_basis` are
+/// the EntryEventType and LegalBasis stored in `ref_value`
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
+#[sea_orm(table_name = "sanction_event")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i32,
+    pub event_type: i32,
+    pub event_date: Date,
+    pub legal_basis: Option<i32>,
+    #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
+    pub topmaj: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::sanction_event_sdn::Entity")]
+    SanctionEventSdn,
+}
+
+impl Related<super::sanction_event_sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SanctionEventSdn.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn from_ofac_document(entity: &EntryEvent) -> Result<Model, ConversionErr> {
+        Ok(Model {
+            id: entity.id,
+            event_type: entity.event_type_id,
+            event_date: entity.date.to_naive_date().ok_or(ConversionErr::InvalidEventDate(entity.id))?,
+            legal_basis: entity.legal_basis_id,
+            topmaj: "N".to_owned(),
+        })
+    }
+}
+
+impl_topmaj! {
+    Entity, Model, super::sanction_event_sdn::ActiveModel, ActiveModel
+}
+
+#[cfg(test)]
+mod sanction_event {
+    use super::*;
+    use quick_xml::de::from_str;
+
+    #[test]
+    fn parse_event() {
+        let event: EntryEvent = from_str(
+            r#"
+			<EntryEvent ID="36" EntryEventTypeID="1" LegalBasisID="12">
+				<Date CalendarTypeID="1">
+					<Year>1995</Year>
+					<Month>6</Month>
+					<Day>25</Day>
+				</Date>
+				<Comment />
+			</EntryEvent>"#,
+        )
+        .unwrap();
+        let excepted = Model {
+            id: 36,
+            event_type: 1,
+            event_date: Date::from_ymd(1995, 6, 25),
+            legal_basis: Some(12),
+            topmaj: "N".to_owned(),
+        };
+        assert_eq!(Ok(excepted), Model::from_ofac_document(&event));
+    }
+}
@@ -0,0 +1,49 @@
+use sea_orm::entity::prelude::*;
+
+use crate::db::OfacRelEntity;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "sanction_event_sdn")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub event_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub sdn_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(belongs_to = "super::sanction_event::Entity", from = "Column::EventId", to = "super::sanction_event::Column::Id", on_update = "Restrict", on_delete = "Restrict")]
+    SanctionEvent,
+    #[sea_orm(belongs_to = "super::sdn::Entity", from = "Column::SdnId", to = "super::sdn::Column::FixedRef", on_update = "Restrict", on_delete = "Restrict")]
+    Sdn,
+}
+
+impl Related<super::sanction_event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SanctionEvent.def()
+    }
+}
+
+impl Related<super::sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Sdn.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl OfacRelEntity for ActiveModel {}
+
+#[derive(Debug)]
+pub struct SdnToSanctionEvent;
+
+impl Linked for SdnToSanctionEvent {
+    type FromEntity = super::sdn::Entity;
+
+    type ToEntity = super::sanction_event::Entity;
+
+    fn link(&self) -> Vec<RelationDef> {
+        vec![Relation::Sdn.def().rev(), Entity::belongs_to(super::sanction_event::Entity).from(Column::EventId).to(super::sanction_event::Column::Id).into()]
+    }
+}
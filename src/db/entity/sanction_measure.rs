@@ -0,0 +1,46 @@
+use sea_orm::{entity::prelude::*, EntityTrait, RelationTrait, Set};
+
+use crate::db::{impl_topmaj, OfacEntity};
+use crate::document::models::sanction::SanctionsMeasure;
+
+/// A SanctionsMeasure of the SanctionsEntry, `sanctions_type` is the SanctionsType stored in `ref_value`
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
+#[sea_orm(table_name = "sanction_measure")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i32,
+    pub sanctions_type: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
+    #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
+    pub topmaj: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::sanction_measure_sdn::Entity")]
+    SanctionMeasureSdn,
+}
+
+impl Related<super::sanction_measure_sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SanctionMeasureSdn.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn from_ofac_document(entity: &SanctionsMeasure) -> Model {
+        Model {
+            id: entity.id,
+            sanctions_type: entity.sanctions_type_id,
+            comment: entity.program.as_ref().map(|comment| comment.to_uppercase()),
+            topmaj: "N".to_owned(),
+        }
+    }
+}
+
+impl_topmaj! {
+    Entity, Model, super::sanction_measure_sdn::ActiveModel, ActiveModel
+}
@@ -9,9 +9,12 @@ use sea_orm::ActiveValue::NotSet;
 use sea_orm::{entity::prelude::*, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, Iterable, QueryFilter, RelationTrait, Set};
 use sea_orm::{DatabaseTransaction, IntoActiveModel, ModelTrait};
 
-use crate::db::{OfacEntity, OfacEntityFinalOp};
+use crate::db::mapping::{FeatureTarget, MappedRelation};
+use crate::db::batch::WriteBatch;
+use crate::db::changes::{self, Change, ChangeKind, ChangeRecorder};
+use crate::db::{ConversionErr, OfacEntity, OfacEntityFinalOp};
 use crate::document::{
-    models::{distinctparty::DistinctParty, location::Locations, sanction::SanctionsEntries},
+    models::{distinctparty::DistinctParty, feature::Feature, location::Locations, sanction::SanctionsEntries},
     OfacDocumentReferences,
 };
 
@@ -117,6 +120,7 @@ pub struct SdnInnerRelation {
     pub is_active: bool,
 
     pub address: Vec<address::Model>,
+    pub address_variants: Vec<address_variant::Model>,
     pub names: Vec<name::Model>,
     pub operators: Vec<aircraft_operator::Model>,
     pub bics: Vec<bic::Model>,
@@ -125,6 +129,8 @@ pub struct SdnInnerRelation {
     pub pobs: Vec<pob::Model>,
     pub caatsa235s: Vec<caatsa235::Model>,
     pub citizens: Vec<citizen::Model>,
+    pub date_periods: Vec<date_period::Model>,
+    pub digital_currency_addresses: Vec<digital_currency_address::Model>,
     pub emails: Vec<email::Model>,
     pub eo13662dds: Vec<eo13662dd::Model>,
     pub eo13846infs: Vec<eo13846inf::Model>,
@@ -137,13 +143,53 @@ pub struct SdnInnerRelation {
     pub nationality_registrations: Vec<nationality_registration::Model>,
     pub other_vessel_flags: Vec<other_vessel_flag::Model>,
     pub programs: Vec<program::Model>,
+    pub sanction_events: Vec<sanction_event::Model>,
+    pub sanction_measures: Vec<sanction_measure::Model>,
     pub phone_numbers: Vec<phone_number::Model>,
     pub targets: Vec<target::Model>,
     pub websites: Vec<website::Model>,
 }
 
 impl SdnInnerRelation {
-    async fn process_relations(&mut self, db: &DatabaseConnection, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>) -> Result<OfacEntityFinalOp, DbErr> {
+    /// Add the child table row of a feature, features without the part the table is built from are skipped
+    fn push_feature(&mut self, relation: MappedRelation, feature: &Feature, locations: &Locations, references: &OfacDocumentReferences, is_primary_address: &mut bool) -> Result<(), ConversionErr> {
+        let version = &feature.version;
+        match relation {
+            MappedRelation::Dobs if version.date_period.is_some() => self.dobs.push(dob::Model::from_ofac_document(version)),
+            MappedRelation::Nationalities if version.location.is_some() => self.nationalities.push(nationality::Model::from_ofac_document(&NationalityEntity(feature, locations), references)?),
+            MappedRelation::Citizens if version.location.is_some() => self.citizens.push(citizen::Model::from_ofac_document((feature, locations))?),
+            MappedRelation::Address if version.location.is_some() => {
+                self.address.push(address::Model::from_ofac_document((feature, locations), references, *is_primary_address)?);
+                self.address_variants.extend(address_variant::Model::from_ofac_document((feature, locations), references));
+                *is_primary_address = false;
+            }
+            MappedRelation::NationalityRegistrations if version.location.is_some() => {
+                self.nationality_registrations.push(nationality_registration::Model::from_ofac_document(&NationalityRegistrationEntity(feature, locations))?)
+            }
+            _ if version.detail.is_none() => {}
+            MappedRelation::Pobs => self.pobs.push(pob::Model::from_ofac_document(version)),
+            MappedRelation::Bics => self.bics.push(bic::Model::from_ofac_document(version).with_normalized()),
+            MappedRelation::Websites => self.websites.push(website::Model::from_ofac_document(version)),
+            MappedRelation::Emails => self.emails.push(email::Model::from_ofac_document(version)),
+            MappedRelation::FormerVesselFlags => self.former_vessel_flags.push(former_vessel_flag::Model::from_ofac_document(version)),
+            MappedRelation::Operators => self.operators.push(aircraft_operator::Model::from_ofac_document(version)),
+            MappedRelation::Biks => self.biks.push(bik::Model::from_ofac_document(version).with_normalized()),
+            MappedRelation::Eo13662dds => self.eo13662dds.push(eo13662dd::Model::from_ofac_document(version)),
+            MappedRelation::OtherVesselFlags => self.other_vessel_flags.push(other_vessel_flag::Model::from_ofac_document(version)),
+            MappedRelation::PhoneNumbers => self.phone_numbers.push(phone_number::Model::from_ofac_document(version)),
+            MappedRelation::Caatsa235s => self.caatsa235s.push(caatsa235::Model::from_ofac_document(version)),
+            MappedRelation::Eo13846infs => self.eo13846infs.push(eo13846inf::Model::from_ofac_document(version)),
+            MappedRelation::EquityTickers => self.equity_tickers.push(equity_ticker::Model::from_ofac_document(version)),
+            MappedRelation::IssuerNames => self.issuer_names.push(issuer_name::Model::from_ofac_document(version)),
+            MappedRelation::Isins => self.isins.push(isin::Model::from_ofac_document(version).with_normalized()),
+            MappedRelation::Targets => self.targets.push(target::Model::from_ofac_document(version)),
+            MappedRelation::Eo14024dds => self.eo14024dds.push(eo14024dd::Model::from_ofac_document(version)),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn process_relations(&mut self, db: &DatabaseConnection, batch: &WriteBatch, changes: &ChangeRecorder) -> Result<OfacEntityFinalOp, DbErr> {
         let mut op = OfacEntityFinalOp::Nothing;
         if !self.is_active {
             info!("SDN with fixed_ref {} is INACTIVE and skipped", self.sdn.fixed_ref);
@@ -151,36 +197,55 @@ impl SdnInnerRelation {
         }
         let id = self.sdn.fixed_ref;
         let identity = self.sdn.identity;
-        address::ActiveModel::process_entity(&mut self.address, &mut self.sdn.find_linked(address_sdn::SdnToAddress).all(db).await?, db, tx, identity, &mut op).await?;
-        aircraft_operator::ActiveModel::process_entity(&mut self.operators, &mut self.sdn.find_linked(aircraft_operator_sdn::SdnToAircraftOperator).all(db).await?, db, tx, id, &mut op).await?;
-        name::ActiveModel::process_entity(&mut self.names, &mut self.sdn.find_linked(name_sdn::SdnToName).all(db).await?, db, tx, id, &mut op).await?;
-        bic::ActiveModel::process_entity(&mut self.bics, &mut self.sdn.find_linked(bic_sdn::SdnToBic).all(db).await?, db, tx, id, &mut op).await?;
-        bik::ActiveModel::process_entity(&mut self.biks, &mut self.sdn.find_linked(bik_sdn::SdnToBik).all(db).await?, db, tx, id, &mut op).await?;
-        dob::ActiveModel::process_entity(&mut self.dobs, &mut self.sdn.find_linked(dob_identity::SdnToDob).all(db).await?, db, tx, identity, &mut op).await?;
-        caatsa235::ActiveModel::process_entity(&mut self.caatsa235s, &mut self.sdn.find_linked(caatsa235_sdn::SdnToCaatsa235).all(db).await?, db, tx, id, &mut op).await?;
-        citizen::ActiveModel::process_entity(&mut self.citizens, &mut self.sdn.find_linked(citizen_sdn::SdnToCitizen).all(db).await?, db, tx, id, &mut op).await?;
-        email::ActiveModel::process_entity(&mut self.emails, &mut self.sdn.find_linked(email_sdn::SdnToEmail).all(db).await?, db, tx, id, &mut op).await?;
-        eo13662dd::ActiveModel::process_entity(&mut self.eo13662dds, &mut self.sdn.find_linked(eo13662dd_sdn::SdnToEo13662dd).all(db).await?, db, tx, id, &mut op).await?;
-        eo13846inf::ActiveModel::process_entity(&mut self.eo13846infs, &mut self.sdn.find_linked(eo13846inf_sdn::SdnToEo13846inf).all(db).await?, db, tx, id, &mut op).await?;
-        eo14024dd::ActiveModel::process_entity(&mut self.eo14024dds, &mut self.sdn.find_linked(eo14024dd_sdn::SdnToEo14024dd).all(db).await?, db, tx, id, &mut op).await?;
-        equity_ticker::ActiveModel::process_entity(&mut self.equity_tickers, &mut self.sdn.find_linked(equity_ticker_sdn::SdnToEquityTicker).all(db).await?, db, tx, id, &mut op).await?;
+        address::ActiveModel::process_entity(&mut self.address, &mut self.sdn.find_linked(address_sdn::SdnToAddress).all(db).await?, batch, changes, identity, &mut op).await?;
+        address_variant::ActiveModel::process_entity(
+            &mut self.address_variants,
+            &mut self.sdn.find_linked(address_variant_sdn::SdnToAddressVariant).all(db).await?,
+            batch,
+            changes,
+            identity,
+            &mut op,
+        )
+        .await?;
+        aircraft_operator::ActiveModel::process_entity(&mut self.operators, &mut self.sdn.find_linked(aircraft_operator_sdn::SdnToAircraftOperator).all(db).await?, batch, changes, id, &mut op).await?;
+        name::ActiveModel::process_entity(&mut self.names, &mut self.sdn.find_linked(name_sdn::SdnToName).all(db).await?, batch, changes, id, &mut op).await?;
+        bic::ActiveModel::process_entity(&mut self.bics, &mut self.sdn.find_linked(bic_sdn::SdnToBic).all(db).await?, batch, changes, id, &mut op).await?;
+        bik::ActiveModel::process_entity(&mut self.biks, &mut self.sdn.find_linked(bik_sdn::SdnToBik).all(db).await?, batch, changes, id, &mut op).await?;
+        dob::ActiveModel::process_entity(&mut self.dobs, &mut self.sdn.find_linked(dob_identity::SdnToDob).all(db).await?, batch, changes, identity, &mut op).await?;
+        caatsa235::ActiveModel::process_entity(&mut self.caatsa235s, &mut self.sdn.find_linked(caatsa235_sdn::SdnToCaatsa235).all(db).await?, batch, changes, id, &mut op).await?;
+        citizen::ActiveModel::process_entity(&mut self.citizens, &mut self.sdn.find_linked(citizen_sdn::SdnToCitizen).all(db).await?, batch, changes, id, &mut op).await?;
+        date_period::ActiveModel::process_entity(&mut self.date_periods, &mut self.sdn.find_linked(date_period_sdn::SdnToDatePeriod).all(db).await?, batch, changes, id, &mut op).await?;
+        digital_currency_address::ActiveModel::process_entity(
+            &mut self.digital_currency_addresses,
+            &mut self.sdn.find_linked(digital_currency_address_sdn::SdnToDigitalCurrencyAddress).all(db).await?,
+            batch,
+            changes,
+            id,
+            &mut op,
+        )
+        .await?;
+        email::ActiveModel::process_entity(&mut self.emails, &mut self.sdn.find_linked(email_sdn::SdnToEmail).all(db).await?, batch, changes, id, &mut op).await?;
+        eo13662dd::ActiveModel::process_entity(&mut self.eo13662dds, &mut self.sdn.find_linked(eo13662dd_sdn::SdnToEo13662dd).all(db).await?, batch, changes, id, &mut op).await?;
+        eo13846inf::ActiveModel::process_entity(&mut self.eo13846infs, &mut self.sdn.find_linked(eo13846inf_sdn::SdnToEo13846inf).all(db).await?, batch, changes, id, &mut op).await?;
+        eo14024dd::ActiveModel::process_entity(&mut self.eo14024dds, &mut self.sdn.find_linked(eo14024dd_sdn::SdnToEo14024dd).all(db).await?, batch, changes, id, &mut op).await?;
+        equity_ticker::ActiveModel::process_entity(&mut self.equity_tickers, &mut self.sdn.find_linked(equity_ticker_sdn::SdnToEquityTicker).all(db).await?, batch, changes, id, &mut op).await?;
         former_vessel_flag::ActiveModel::process_entity(
             &mut self.former_vessel_flags,
             &mut self.sdn.find_linked(former_vessel_flag_sdn::SdnToFormerVesselFlag).all(db).await?,
-            db,
-            tx,
+            batch,
+            changes,
             id,
             &mut op,
         )
         .await?;
-        isin::ActiveModel::process_entity(&mut self.isins, &mut self.sdn.find_linked(isin_sdn::SdnToIsin).all(db).await?, db, tx, id, &mut op).await?;
-        issuer_name::ActiveModel::process_entity(&mut self.issuer_names, &mut self.sdn.find_linked(issuer_name_sdn::SdnToIssuerName).all(db).await?, db, tx, id, &mut op).await?;
-        nationality::ActiveModel::process_entity(&mut self.nationalities, &mut self.sdn.find_linked(nationality_identity::SdnToNationality).all(db).await?, db, tx, identity, &mut op).await?;
+        isin::ActiveModel::process_entity(&mut self.isins, &mut self.sdn.find_linked(isin_sdn::SdnToIsin).all(db).await?, batch, changes, id, &mut op).await?;
+        issuer_name::ActiveModel::process_entity(&mut self.issuer_names, &mut self.sdn.find_linked(issuer_name_sdn::SdnToIssuerName).all(db).await?, batch, changes, id, &mut op).await?;
+        nationality::ActiveModel::process_entity(&mut self.nationalities, &mut self.sdn.find_linked(nationality_identity::SdnToNationality).all(db).await?, batch, changes, identity, &mut op).await?;
         nationality_registration::ActiveModel::process_entity(
             &mut self.nationality_registrations,
             &mut self.sdn.find_linked(nationality_registration_sdn::SdnToNationalityRegistration).all(db).await?,
-            db,
-            tx,
+            batch,
+            changes,
             id,
             &mut op,
         )
@@ -188,17 +253,27 @@ impl SdnInnerRelation {
         other_vessel_flag::ActiveModel::process_entity(
             &mut self.other_vessel_flags,
             &mut self.sdn.find_linked(other_vessel_flag_sdn::SdnToOtherVesselFlag).all(db).await?,
-            db,
-            tx,
+            batch,
+            changes,
+            id,
+            &mut op,
+        )
+        .await?;
+        phone_number::ActiveModel::process_entity(&mut self.phone_numbers, &mut self.sdn.find_linked(phone_number_sdn::SdnToPhoneNumber).all(db).await?, batch, changes, id, &mut op).await?;
+        program::ActiveModel::process_entity(&mut self.programs, &mut self.sdn.find_linked(sdn_program::SdnToProgram).all(db).await?, batch, changes, id, &mut op).await?;
+        sanction_event::ActiveModel::process_entity(&mut self.sanction_events, &mut self.sdn.find_linked(sanction_event_sdn::SdnToSanctionEvent).all(db).await?, batch, changes, id, &mut op).await?;
+        sanction_measure::ActiveModel::process_entity(
+            &mut self.sanction_measures,
+            &mut self.sdn.find_linked(sanction_measure_sdn::SdnToSanctionMeasure).all(db).await?,
+            batch,
+            changes,
             id,
             &mut op,
         )
         .await?;
-        phone_number::ActiveModel::process_entity(&mut self.phone_numbers, &mut self.sdn.find_linked(phone_number_sdn::SdnToPhoneNumber).all(db).await?, db, tx, id, &mut op).await?;
-        program::ActiveModel::process_entity(&mut self.programs, &mut self.sdn.find_linked(sdn_program::SdnToProgram).all(db).await?, db, tx, id, &mut op).await?;
-        pob::ActiveModel::process_entity(&mut self.pobs, &mut self.sdn.find_linked(pob_identity::SdnToPob).all(db).await?, db, tx, identity, &mut op).await?;
-        target::ActiveModel::process_entity(&mut self.targets, &mut self.sdn.find_linked(target_sdn::SdnToTarget).all(db).await?, db, tx, id, &mut op).await?;
-        website::ActiveModel::process_entity(&mut self.websites, &mut self.sdn.find_linked(website_identity::SdnToWebsite).all(db).await?, db, tx, identity, &mut op).await?;
+        pob::ActiveModel::process_entity(&mut self.pobs, &mut self.sdn.find_linked(pob_identity::SdnToPob).all(db).await?, batch, changes, identity, &mut op).await?;
+        target::ActiveModel::process_entity(&mut self.targets, &mut self.sdn.find_linked(target_sdn::SdnToTarget).all(db).await?, batch, changes, id, &mut op).await?;
+        website::ActiveModel::process_entity(&mut self.websites, &mut self.sdn.find_linked(website_identity::SdnToWebsite).all(db).await?, batch, changes, identity, &mut op).await?;
         Ok(op)
     }
 }
@@ -287,6 +362,8 @@ pub enum Relation {
     RefReference1,
     #[sea_orm(has_many = "super::address_sdn::Entity")]
     AddressSdn,
+    #[sea_orm(has_many = "super::address_variant_sdn::Entity")]
+    AddressVariantSdn,
     #[sea_orm(has_many = "super::aircraft_operator_sdn::Entity")]
     AircraftOperatorSdn,
     #[sea_orm(has_many = "super::name_sdn::Entity")]
@@ -299,8 +376,12 @@ pub enum Relation {
     Caatsa235Sdn,
     #[sea_orm(has_many = "super::citizen_sdn::Entity")]
     CitizenSdn,
+    #[sea_orm(has_many = "super::date_period_sdn::Entity")]
+    DatePeriodSdn,
     #[sea_orm(has_many = "super::ddc_alias_sdn::Entity")]
     DdcAliasSdn,
+    #[sea_orm(has_many = "super::digital_currency_address_sdn::Entity")]
+    DigitalCurrencyAddressSdn,
     #[sea_orm(has_many = "super::dob_identity::Entity")]
     DobIdentity,
     #[sea_orm(has_many = "super::document_identity::Entity")]
@@ -335,6 +416,10 @@ pub enum Relation {
     Relation,
     #[sea_orm(has_many = "super::relation_sdn::Entity")]
     RelationSdn,
+    #[sea_orm(has_many = "super::sanction_event_sdn::Entity")]
+    SanctionEventSdn,
+    #[sea_orm(has_many = "super::sanction_measure_sdn::Entity")]
+    SanctionMeasureSdn,
     #[sea_orm(has_many = "super::sdn_program::Entity")]
     ProgramSdn,
     #[sea_orm(has_many = "super::target_sdn::Entity")]
@@ -361,6 +446,12 @@ impl Related<super::address_sdn::Entity> for Entity {
     }
 }
 
+impl Related<super::address_variant_sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AddressVariantSdn.def()
+    }
+}
+
 impl Related<super::aircraft_operator_sdn::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::AircraftOperatorSdn.def()
@@ -409,6 +500,18 @@ impl Related<super::dob_identity::Entity> for Entity {
     }
 }
 
+impl Related<super::date_period_sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DatePeriodSdn.def()
+    }
+}
+
+impl Related<super::digital_currency_address_sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DigitalCurrencyAddressSdn.def()
+    }
+}
+
 impl Related<super::document_identity::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::DocumentIdentity.def()
@@ -505,6 +608,18 @@ impl Related<super::relation_sdn::Entity> for Entity {
     }
 }
 
+impl Related<super::sanction_event_sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SanctionEventSdn.def()
+    }
+}
+
+impl Related<super::sanction_measure_sdn::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SanctionMeasureSdn.def()
+    }
+}
+
 impl Related<super::sdn_program::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProgramSdn.def()
@@ -534,10 +649,13 @@ impl ActiveModelBehavior for ActiveModel {
 pub struct DocumentEntity<'a>(pub &'a DistinctParty, pub &'a Locations, pub &'a SanctionsEntries);
 
 impl Model {
-    pub fn from_ofac_document(entity: &DocumentEntity<'_>, references: &OfacDocumentReferences) -> Result<(Model, SdnInnerRelation), DbErr> {
+    pub fn from_ofac_document(entity: &DocumentEntity<'_>, references: &OfacDocumentReferences) -> Result<(Model, SdnInnerRelation), ConversionErr> {
         let fixed_ref = entity.0.fixed_ref;
         let identity = entity.0.profile.identity.id;
-        let sanction = sanction::Model::from_ofac_document(entity.2.entries.iter().find(|s| s.profile_id == fixed_ref).unwrap(), references)?;
+        let sanction = match entity.2.by_profile(fixed_ref) {
+            Some(entry) => sanction::Model::from_ofac_document(entry, references)?,
+            None => return Err(ConversionErr::MissingSanctionsEntry(fixed_ref)),
+        };
         let mut sdn_db = Model {
             identity,
             fixed_ref,
@@ -561,202 +679,25 @@ impl Model {
         }
         let mut inner_relations = SdnInnerRelation {
             programs: sanction.programs,
-            names: name::Model::from_ofac_document(&entity.0.profile.identity, &entity.0.profile.identity.name_part_groups, &references.script_values),
+            sanction_events: sanction.events,
+            sanction_measures: sanction.measures,
+            names: name::Model::from_ofac_document(&entity.0.profile.identity, &entity.0.profile.identity.name_part_groups, references),
             ..Default::default()
         };
         if let Some(features) = &entity.0.profile.feature {
+            let mapping = references.feature_mapping();
             let mut is_primary_address = true;
             for feature in features {
-                if let Some(date_period) = &feature.version.date_period.clone() {
-                    match feature.feature_type {
-                        8 => inner_relations.dobs.push(dob::Model::from_ofac_document(&feature.version)),
-                        45 => sdn_db.manufacture_date = date_period.parse_from_to(),
-                        867 => sdn_db.cmic_effective_date = date_period.parse_from_to(),
-                        868 => sdn_db.cmic_sales_date = date_period.parse_from_to(),
-                        869 => sdn_db.cmic_listing_date = date_period.parse_from_to(),
-                        _ => {}
-                    }
+                let map = mapping.find(feature.feature_type, references).filter(|map| map.sdn_type.as_ref().is_none_or(|sdn_type| *sdn_type == sdn_db.sdn_type));
+                let target = map.map(|map| map.target);
+                if feature.version.date_period.is_some() && target != Some(FeatureTarget::Relation(MappedRelation::Dobs)) {
+                    inner_relations.date_periods.extend(date_period::Model::from_ofac_document(feature));
                 }
-                if let Some(location) = feature.version.location {
-                    match feature.feature_type {
-                        10 => inner_relations.nationalities.push(nationality::Model::from_ofac_document(&NationalityEntity(feature, entity.1), references)),
-                        11 => inner_relations.citizens.push(citizen::Model::from_ofac_document((feature, entity.1))),
-                        25 => {
-                            inner_relations.address.push(address::Model::from_ofac_document((feature, entity.1), references, is_primary_address));
-                            is_primary_address = false;
-                        }
-                        365 => inner_relations
-                            .nationality_registrations
-                            .push(nationality_registration::Model::from_ofac_document(&NationalityRegistrationEntity(feature, entity.1))),
-                        404 => {
-                            let location = entity.1.locations.iter().find(|&loc| loc.id == location.id).unwrap();
-                            if let Some(parts) = location.location_parts.as_ref() {
-                                for part in parts {
-                                    for part_value in part.values.iter() {
-                                        if part_value.primary {
-                                            let value = part_value.value.to_owned();
-                                            match references.area_codes.iter().find(|&area| area.name == value) {
-                                                Some(area) => sdn_db.registration_country = Some(area.id),
-                                                None => sdn_db.registration_country = None,
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-                if let Some(detail) = feature.version.detail.clone() {
-                    match feature.feature_type {
-                        1 => sdn_db.vessel_call_sign = if detail.value.is_some() { Some(detail.value.unwrap().to_uppercase()) } else { None },
-                        2 => sdn_db.vessel_type = detail.detail_reference_id,
-                        3 => sdn_db.vessel_flag = if detail.value.is_some() { Some(detail.value.unwrap().to_uppercase()) } else { None },
-                        4 => sdn_db.vessel_owner = if detail.value.is_some() { Some(detail.value.unwrap().to_uppercase()) } else { None },
-                        5 => sdn_db.vessel_tonnage = if let Ok(tonnage) = detail.value.unwrap().parse::<i32>() { Some(tonnage) } else { None },
-                        6 => sdn_db.vessel_gross_registered_tonnage = if let Ok(gross_tonnage) = detail.value.unwrap().parse::<i32>() { Some(gross_tonnage) } else { None },
-                        9 => inner_relations.pobs.push(pob::Model::from_ofac_document(&feature.version)),
-                        13 => inner_relations.bics.push(bic::Model::from_ofac_document(&feature.version)),
-                        14 => inner_relations.websites.push(website::Model::from_ofac_document(&feature.version)),
-                        21 => inner_relations.emails.push(email::Model::from_ofac_document(&feature.version)),
-                        t if t == 24 && sdn_db.partysubtypeid == 1 => inner_relations.former_vessel_flags.push(former_vessel_flag::Model::from_ofac_document(&feature.version)),
-                        26 => sdn_db.title = if detail.value.is_some() { Some(detail.value.unwrap().to_uppercase()) } else { None },
-                        44 => sdn_db.construction_number = Some(detail.value.as_ref().unwrap().to_uppercase()),
-                        46 => sdn_db.transpondeur_code = Some(detail.value.as_ref().unwrap().to_uppercase()),
-                        47 => sdn_db.model = Some(detail.value.as_ref().unwrap().to_uppercase()),
-                        48 => inner_relations.operators.push(aircraft_operator::Model::from_ofac_document(&feature.version)),
-                        49 => sdn_db.previous_tail_number = Some(detail.value.as_ref().unwrap().to_uppercase()),
-                        50 => sdn_db.manufacturer_serial_number = Some(detail.value.as_ref().unwrap().to_uppercase()),
-                        64 => sdn_db.tail_number = Some(detail.value.as_ref().unwrap().to_uppercase()),
-                        104 => sdn_db.ifca_determination = detail.detail_reference_id,
-                        125 => sdn_db.additional_sanctions_information = Some(detail.detail_reference_id.unwrap()),
-                        164 => inner_relations.biks.push(bik::Model::from_ofac_document(&feature.version)),
-                        204 => inner_relations.eo13662dds.push(eo13662dd::Model::from_ofac_document(&feature.version)),
-                        224 => {
-                            sdn_db.gender = if detail.detail_type_id.unwrap() == 1431 && detail.detail_reference_id.unwrap() == 91526 {
-                                Some("MALE".to_owned())
-                            } else if detail.detail_type_id.unwrap() == 1431 && detail.detail_reference_id.unwrap() == 91527 {
-                                Some("FEMALE".to_owned())
-                            } else {
-                                None
-                            }
-                        }
-                        264 => sdn_db.locode = Some(detail.value.as_ref().unwrap().to_uppercase()),
-                        304 => sdn_db.micex_code = Some(detail.value.as_ref().unwrap().to_uppercase()),
-                        344 => match &mut sdn_db.dca_xbt {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_xbt = detail.value,
-                        },
-                        345 => match &mut sdn_db.dca_eth {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_eth = detail.value,
-                        },
-                        364 => sdn_db.duns_number = if let Ok(duns_number) = detail.value.unwrap().parse::<i32>() { Some(duns_number) } else { None },
-                        424 => inner_relations.other_vessel_flags.push(other_vessel_flag::Model::from_ofac_document(&feature.version)),
-                        425 => sdn_db.other_vessel_call_sign = detail.value.clone(),
-                        444 => match &mut sdn_db.dca_xmr {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_xmr = detail.value,
-                        },
-                        504 => sdn_db.secondary_sanctions_risks = detail.detail_reference_id,
-                        524 => inner_relations.phone_numbers.push(phone_number::Model::from_ofac_document(&feature.version)),
-                        525 => inner_relations.caatsa235s.push(caatsa235::Model::from_ofac_document(&feature.version)),
-                        526 => sdn_db.other_vessel_type = detail.detail_reference_id,
-                        566 => match &mut sdn_db.dca_ltc {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_ltc = detail.value,
-                        },
-                        586 => inner_relations.eo13846infs.push(eo13846inf::Model::from_ofac_document(&feature.version)),
-                        626 => sdn_db.prohibited_transactions = detail.detail_reference_id,
-                        646 => {
-                            if let Some(date_period) = feature.version.date_period.clone() {
-                                sdn_db.organization_established_date = Some(date_period.start.as_ref().unwrap().from.to_sql_date());
-                            }
-                        }
-                        647 => sdn_db.organization_type = detail.detail_reference_id,
-                        686 => match &mut sdn_db.dca_zec {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_zec = detail.value,
-                        },
-                        687 => match &mut sdn_db.dca_dash {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_dash = detail.value,
-                        },
-                        688 => match &mut sdn_db.dca_btg {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_btg = detail.value,
-                        },
-                        689 => match &mut sdn_db.dca_etc {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_etc = detail.value,
-                        },
-                        706 => match &mut sdn_db.dca_bsv {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_bsv = detail.value,
-                        },
-                        726 => match &mut sdn_db.dca_bch {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_bch = detail.value,
-                        },
-                        746 => match &mut sdn_db.dca_xvh {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_xvh = detail.value,
-                        },
-                        766 => inner_relations.equity_tickers.push(equity_ticker::Model::from_ofac_document(&feature.version)),
-                        767 => inner_relations.issuer_names.push(issuer_name::Model::from_ofac_document(&feature.version)),
-                        806 => inner_relations.isins.push(isin::Model::from_ofac_document(&feature.version)),
-                        826 => inner_relations.targets.push(target::Model::from_ofac_document(&feature.version)),
-                        827 => sdn_db.peesa_information = detail.detail_reference_id,
-                        887 => match &mut sdn_db.dca_usdt {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_usdt = detail.value,
-                        },
-                        907 => match &mut sdn_db.dca_xrp {
-                            Some(address) => {
-                                address.push('/');
-                                address.push_str(&detail.value.unwrap_or_else(|| "".to_owned()));
-                            }
-                            None => sdn_db.dca_xrp = detail.value,
-                        },
-                        947 | 948 => inner_relations.eo14024dds.push(eo14024dd::Model::from_ofac_document(&feature.version)),
-                        _ => {}
-                    }
+                inner_relations.digital_currency_addresses.extend(digital_currency_address::Model::from_ofac_document(feature, references));
+                match (map, target) {
+                    (Some(map), Some(FeatureTarget::Column(..))) => map.fill_column(&mut sdn_db, feature, entity.1, references),
+                    (_, Some(FeatureTarget::Relation(relation))) => inner_relations.push_feature(relation, feature, entity.1, references, &mut is_primary_address)?,
+                    _ => {}
                 }
             }
         }
@@ -768,24 +709,30 @@ impl ActiveModel {
     /// Process each entities in paralell
     /// * `db` - is used for SELECT
     /// * `tx` - is used for INSERT/UPDATE/DELETE
-    pub async fn process_entities(entities: &[(Model, SdnInnerRelation)], db: DatabaseConnection, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>) -> Result<(), DbErr> {
+    /// * `batch` - holds the writes of the relations, it is flushed in `tx` once every entity has been processed
+    /// * `changes` - records the changes of the entities
+    ///
+    /// Returns the fixed_refs saved, which must then be given to [`set_sanction_inactive`] once every entity has been processed
+    pub async fn process_entities(entities: &[(Model, SdnInnerRelation)], db: DatabaseConnection, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>, batch: &Arc<WriteBatch>, changes: &Arc<ChangeRecorder>) -> Result<Vec<i32>, DbErr> {
         let tasks: Vec<_> = entities
             .iter()
             .map(|e| {
                 let tx = Arc::clone(tx);
-                tokio::spawn(ActiveModel::process_entity(e.0.clone(), e.1.clone(), db.clone(), tx))
+                let batch = Arc::clone(batch);
+                let changes = Arc::clone(changes);
+                tokio::spawn(changes::sdn_scope(e.0.fixed_ref, ActiveModel::process_entity(e.0.clone(), e.1.clone(), db.clone(), tx, batch, changes)))
             })
             .collect();
         let mut saved_sdns = Vec::new();
         for task in tasks {
             saved_sdns.push(task.await.unwrap().unwrap());
         }
-        set_sanction_inactive(Arc::clone(tx), &saved_sdns).await?;
-        Ok(())
+        batch.flush(tx).await?;
+        Ok(saved_sdns)
     }
 
     /// Process an entity to save it in DB
-    async fn process_entity(mut sdn: Model, mut relations: SdnInnerRelation, db: DatabaseConnection, tx: Arc<tokio::sync::Mutex<DatabaseTransaction>>) -> Result<i32, DbErr> {
+    async fn process_entity(mut sdn: Model, mut relations: SdnInnerRelation, db: DatabaseConnection, tx: Arc<tokio::sync::Mutex<DatabaseTransaction>>, batch: Arc<WriteBatch>, changes: Arc<ChangeRecorder>) -> Result<i32, DbErr> {
         let fixed_ref = sdn.fixed_ref;
         if let Some(in_db) = Entity::find().filter(Column::FixedRef.eq(sdn.fixed_ref)).one(&db).await? {
             relations.sdn = in_db.clone();
@@ -804,8 +751,9 @@ impl ActiveModel {
                         model.topmaj = Set("N".to_owned());
                         model.update(&*lock).await?;
                     }
+                    changes.record(Change::of_model(ChangeKind::UpdateTopmajOnly, &sdn, None));
                 }
-                if relations.process_relations(&db, &tx).await? != OfacEntityFinalOp::Nothing {
+                if relations.process_relations(&db, &batch, &changes).await? != OfacEntityFinalOp::Nothing {
                     let lock = tx.lock().await;
                     let mut model = sdn.into_active_model();
                     model.topmaj = Set("O".to_owned());
@@ -813,6 +761,7 @@ impl ActiveModel {
                 }
                 return Ok(fixed_ref);
             }
+            changes.record(Change::of_update(&sdn_db, &sdn, None));
             let mut model = ActiveModel::from(sdn);
             model.topmaj = Set("O".to_owned());
             for col in Column::iter() {
@@ -823,7 +772,7 @@ impl ActiveModel {
                 let lock = tx.lock().await;
                 model.update(&*lock).await?;
             }
-            relations.process_relations(&db, &tx).await?;
+            relations.process_relations(&db, &batch, &changes).await?;
             return Ok(fixed_ref);
         }
         relations.sdn = sdn.clone();
@@ -835,17 +784,25 @@ impl ActiveModel {
             am.record_id = NotSet;
             am.insert(&*lock).await?;
         }
-        relations.process_relations(&db, &tx).await?;
+        changes.record(Change::of_insert(&sdn, None));
+        relations.process_relations(&db, &batch, &changes).await?;
         Ok(fixed_ref)
     }
 }
 
 /// Given fixed_refs are ACTIVE (i.e. presents in current xml document), other will be updated to INACTIVE
-pub async fn set_sanction_inactive(tx: Arc<tokio::sync::Mutex<DatabaseTransaction>>, fixed_refs: &[i32]) -> Result<(), DbErr> {
+pub async fn set_sanction_inactive(tx: Arc<tokio::sync::Mutex<DatabaseTransaction>>, changes: &ChangeRecorder, fixed_refs: &[i32]) -> Result<(), DbErr> {
     if fixed_refs.is_empty() {
         return Ok(());
     }
     let tx = tx.lock().await;
+    let deactivated = Entity::find().filter(Column::FixedRef.is_not_in(fixed_refs.to_vec())).filter(Column::SanctionStatus.eq("ACTIVE")).all(&*tx).await?;
+    for sdn in deactivated.iter() {
+        changes.record(Change {
+            fixed_ref: Some(sdn.fixed_ref),
+            ..Change::of_model(ChangeKind::Deactivation, sdn, None)
+        });
+    }
     Entity::update_many()
         .col_expr(Column::SanctionStatus, Expr::value("INACTIVE"))
         .col_expr(Column::Topmaj, Expr::value("N"))
@@ -0,0 +1,33 @@
+use sea_orm::entity::prelude::*;
+
+/// State of an sdn from a date of issue until the next row of the sdn
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Default)]
+#[sea_orm(table_name = "sdn_history")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub fixed_ref: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub date_of_issue: Date,
+    /// 0 for the first rows saved on a date of issue, the rows saved again on this date (a rollback or a forced import)
+    /// take the next revision, see [`crate::db::history::save_snapshots`]
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub revision: i32,
+    #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
+    pub sanction_status: String,
+    /// `SdnRecord` of the sdn as json, `None` when the sdn is not in the list anymore
+    #[sea_orm(column_type = "Custom(\"LONGTEXT\".to_owned())", nullable)]
+    pub record: Option<String>,
+    /// Date of issue of the document replaced when the row is saved by a rollback
+    pub rolled_back_from: Option<Date>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
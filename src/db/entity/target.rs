@@ -10,6 +10,9 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub target: Option<i32>,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Custom(\"TINYTEXT\".to_owned())")]
     pub topmaj: String,
 }
@@ -69,6 +72,8 @@ mod target {
         let excepted = Model {
             id: 47587,
             target: Some(92062),
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, target);
@@ -87,6 +92,8 @@ mod target {
         let excepted = Model {
             id: 47587,
             target: None,
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, target);
@@ -11,6 +11,9 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(column_type = "Text")]
     pub website: String,
+    pub reliability: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
     #[sea_orm(column_type = "Text")]
     pub topmaj: String,
 }
@@ -56,6 +59,8 @@ mod website {
         let excepted = Model {
             id: 6855,
             website: "WWW.ARRAI.TV".to_owned(),
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, website);
@@ -75,6 +80,8 @@ mod website {
         let excepted = Model {
             id: 6855,
             website: "".to_owned(),
+            reliability: 1,
+            comment: None,
             topmaj: "N".to_owned(),
         };
         assert_eq!(excepted, website);
@@ -0,0 +1,243 @@
+//! History of the sdns, kept by date of issue
+//!
+//! Each import saves the state of the added, changed, reactivated and inactivated sdns in `sdn_history` within its
+//! transaction, the sdns without history are saved too so that the first import after an upgrade keeps the whole list.
+//! The state of an sdn on a date is the last row of the sdn issued on or before this date.
+//!
+//! The rows saved again on a date of issue take the next revision of this date. A rollback is saved as a new revision
+//! of the document it replaces, the rows of the documents it goes back to are left untouched.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::NaiveDate;
+use log::info;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr, DeriveColumn, EntityTrait, EnumIter, IdenStatic, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Select, Set};
+
+use super::entity::{sdn, sdn_history};
+use super::find_records_by;
+use crate::processor::sdn::SdnRecord;
+
+const ACTIVE: &str = "ACTIVE";
+const INACTIVE: &str = "INACTIVE";
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
+enum QueryAs {
+    FixedRef,
+    Revision,
+}
+
+/// Next revision of the rows saved on `date`, 0 when nothing has been saved on this date yet
+async fn next_revision<C: ConnectionTrait>(db: &C, date: NaiveDate) -> Result<i32, DbErr> {
+    let last: Option<Option<i32>> = sdn_history::Entity::find()
+        .select_only()
+        .column_as(Expr::col(sdn_history::Column::Revision).max(), QueryAs::Revision)
+        .filter(sdn_history::Column::DateOfIssue.eq(date))
+        .into_values::<_, QueryAs>()
+        .one(db)
+        .await?;
+    Ok(last.flatten().map_or(0, |revision| revision + 1))
+}
+
+/// Save the state of the sdns of the document issued on `date_of_issue`, returns the number of sdns saved
+///
+/// `db` is the transaction of the import, `changed` are the sdns added, changed or reactivated by the import,
+/// `inactivated` the ones set INACTIVE, `rolled_back_from` the document replaced by a rollback. A rollback is saved on
+/// the date of the document it replaces, the restored state is then returned from this date on.
+pub async fn save_snapshots<C: ConnectionTrait>(db: &C, date_of_issue: NaiveDate, rolled_back_from: Option<NaiveDate>, changed: &[i32], inactivated: &[i32]) -> Result<usize, DbErr> {
+    let date = rolled_back_from.unwrap_or(date_of_issue);
+    let in_history: HashSet<i32> = sdn_history::Entity::find()
+        .select_only()
+        .column_as(sdn_history::Column::FixedRef, QueryAs::FixedRef)
+        .distinct()
+        .into_values::<_, QueryAs>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+    let active: Vec<i32> = sdn::Entity::find()
+        .select_only()
+        .column_as(sdn::Column::FixedRef, QueryAs::FixedRef)
+        .filter(sdn::Column::SanctionStatus.eq(ACTIVE.to_owned()))
+        .into_values::<_, QueryAs>()
+        .all(db)
+        .await?;
+    let to_save: Vec<i32> = active.into_iter().filter(|fixed_ref| changed.contains(fixed_ref) || !in_history.contains(fixed_ref)).collect();
+    if to_save.is_empty() && inactivated.is_empty() {
+        return Ok(0);
+    }
+    let revision = next_revision(db, date).await?;
+    let mut snapshots = Vec::with_capacity(to_save.len() + inactivated.len());
+    if !to_save.is_empty() {
+        for record in find_records_by(db, Condition::all().add(sdn::Column::FixedRef.is_in(to_save)), &[]).await? {
+            snapshots.push(snapshot(date, revision, rolled_back_from, Some(&record))?);
+        }
+    }
+    for fixed_ref in inactivated.iter() {
+        snapshots.push(sdn_history::ActiveModel {
+            fixed_ref: Set(*fixed_ref),
+            ..snapshot(date, revision, rolled_back_from, None)?
+        });
+    }
+    let saved = snapshots.len();
+    for chunk in snapshots.chunks(500) {
+        sdn_history::Entity::insert_many(chunk.to_vec()).exec(db).await?;
+    }
+    match rolled_back_from {
+        Some(rolled_back_from) => info!("History of {} sdns saved for {} revision {}, rolled back from {} to {}", saved, date, revision, rolled_back_from, date_of_issue),
+        None => info!("History of {} sdns saved for {} revision {}", saved, date, revision),
+    }
+    Ok(saved)
+}
+
+fn snapshot(date: NaiveDate, revision: i32, rolled_back_from: Option<NaiveDate>, record: Option<&SdnRecord>) -> Result<sdn_history::ActiveModel, DbErr> {
+    let json = match record {
+        Some(record) => Some(serde_json::to_string(record).map_err(|err| DbErr::Custom(err.to_string()))?),
+        None => None,
+    };
+    Ok(sdn_history::ActiveModel {
+        fixed_ref: Set(record.map(|record| record.fixed_ref).unwrap_or_default()),
+        date_of_issue: Set(date),
+        revision: Set(revision),
+        sanction_status: Set(if record.is_some() { ACTIVE } else { INACTIVE }.to_owned()),
+        record: Set(json),
+        rolled_back_from: Set(rolled_back_from),
+    })
+}
+
+fn to_record(row: &sdn_history::Model) -> Result<Option<SdnRecord>, DbErr> {
+    match &row.record {
+        Some(json) => serde_json::from_str(json).map(Some).map_err(|err| DbErr::Custom(format!("History of FixedRef {} on {} : {}", row.fixed_ref, row.date_of_issue, err))),
+        None => Ok(None),
+    }
+}
+
+/// Record of the sdn `fixed_ref` as it was in the list on `date`, `None` if it was not in the list
+pub async fn find_record_as_of(db: &DatabaseConnection, fixed_ref: i32, date: NaiveDate) -> Result<Option<SdnRecord>, DbErr> {
+    let row = sdn_history::Entity::find()
+        .filter(Condition::all().add(sdn_history::Column::FixedRef.eq(fixed_ref)).add(sdn_history::Column::DateOfIssue.lte(date)))
+        .order_by_desc(sdn_history::Column::DateOfIssue)
+        .order_by_desc(sdn_history::Column::Revision)
+        .one(db)
+        .await?;
+    match row {
+        Some(row) => to_record(&row),
+        None => Ok(None),
+    }
+}
+
+/// Rows of the last date of each sdn issued on or before `date`, the last revision is kept by [`last_revisions`]
+fn last_rows_as_of(date: NaiveDate) -> Select<sdn_history::Entity> {
+    let last_dates = sdn_history::Entity::find()
+        .select_only()
+        .column(sdn_history::Column::FixedRef)
+        .column_as(Expr::col(sdn_history::Column::DateOfIssue).max(), "date_of_issue")
+        .filter(sdn_history::Column::DateOfIssue.lte(date))
+        .group_by(sdn_history::Column::FixedRef)
+        .into_query();
+    sdn_history::Entity::find()
+        .filter(Expr::tuple([Expr::col(sdn_history::Column::FixedRef).into(), Expr::col(sdn_history::Column::DateOfIssue).into()]).in_subquery(last_dates))
+        .order_by_asc(sdn_history::Column::FixedRef)
+        .order_by_asc(sdn_history::Column::Revision)
+}
+
+/// Last revision of each sdn in `rows`, ordered by FixedRef
+fn last_revisions(rows: Vec<sdn_history::Model>) -> Vec<sdn_history::Model> {
+    let mut last: BTreeMap<i32, sdn_history::Model> = BTreeMap::new();
+    for row in rows {
+        match last.get(&row.fixed_ref) {
+            Some(kept) if kept.revision > row.revision => {}
+            _ => {
+                last.insert(row.fixed_ref, row);
+            }
+        }
+    }
+    last.into_values().collect()
+}
+
+/// Records of every sdn in the list on `date`
+pub async fn find_records_as_of(db: &DatabaseConnection, date: NaiveDate) -> Result<Vec<SdnRecord>, DbErr> {
+    let mut records = Vec::new();
+    for row in last_revisions(last_rows_as_of(date).all(db).await?) {
+        if let Some(record) = to_record(&row)? {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod history {
+    use super::*;
+    use sea_orm::DbBackend;
+    use crate::processor::sdn::SdnRecordAddress;
+
+    #[test]
+    fn snapshot_round_trip() {
+        let record = SdnRecord {
+            fixed_ref: 36,
+            name: "AEROCARIBBEAN AIRLINES".to_owned(),
+            programs: vec!["CUBA".to_owned()],
+            addresses: vec![SdnRecordAddress {
+                id: 25,
+                city: Some("Havana".to_owned()),
+                country: Some("Cuba".to_owned()),
+                is_primary: true,
+                ..Default::default()
+            }],
+            designation_date: Some(NaiveDate::from_ymd(1986, 12, 10)),
+            ..Default::default()
+        };
+        let date_of_issue = NaiveDate::from_ymd(2022, 11, 8);
+        let active = snapshot(date_of_issue, 0, None, Some(&record)).unwrap();
+        let row = sdn_history::Model {
+            fixed_ref: *active.fixed_ref.as_ref(),
+            date_of_issue: *active.date_of_issue.as_ref(),
+            revision: *active.revision.as_ref(),
+            sanction_status: active.sanction_status.as_ref().to_owned(),
+            record: active.record.as_ref().to_owned(),
+            rolled_back_from: *active.rolled_back_from.as_ref(),
+        };
+        assert_eq!(36, row.fixed_ref);
+        assert_eq!(ACTIVE, row.sanction_status);
+        assert_eq!(Some(record), to_record(&row).unwrap());
+
+        let inactive = snapshot(date_of_issue, 0, None, None).unwrap();
+        assert_eq!(INACTIVE, inactive.sanction_status.as_ref());
+        assert_eq!(None, to_record(&sdn_history::Model { record: None, ..row }).unwrap());
+    }
+
+    #[test]
+    fn rollback_is_a_new_revision() {
+        let rolled_back_from = NaiveDate::from_ymd(2022, 11, 10);
+        let row = |fixed_ref: i32, date_of_issue: NaiveDate, revision: i32, rolled_back_from: Option<NaiveDate>| sdn_history::Model {
+            fixed_ref,
+            date_of_issue,
+            revision,
+            sanction_status: ACTIVE.to_owned(),
+            record: None,
+            rolled_back_from,
+        };
+        let rollback = snapshot(rolled_back_from, 1, Some(rolled_back_from), None).unwrap();
+        assert_eq!(&rolled_back_from, rollback.date_of_issue.as_ref());
+        assert_eq!(&1, rollback.revision.as_ref());
+        assert_eq!(&Some(rolled_back_from), rollback.rolled_back_from.as_ref());
+
+        // The rows of the last date of each sdn, the rollback replaces the document it rolled back
+        let rows = vec![
+            row(36, rolled_back_from, 0, None),
+            row(36, rolled_back_from, 1, Some(rolled_back_from)),
+            row(173, rolled_back_from, 0, None),
+        ];
+        let excepted = vec![row(36, rolled_back_from, 1, Some(rolled_back_from)), row(173, rolled_back_from, 0, None)];
+        assert_eq!(excepted, last_revisions(rows));
+    }
+
+    #[test]
+    fn last_rows_are_selected_in_sql() {
+        let excepted = "SELECT `sdn_history`.`fixed_ref`, `sdn_history`.`date_of_issue`, `sdn_history`.`revision`, `sdn_history`.`sanction_status`, `sdn_history`.`record`, `sdn_history`.`rolled_back_from` FROM `sdn_history` \
+            WHERE (`fixed_ref`, `date_of_issue`) IN (SELECT `sdn_history`.`fixed_ref`, MAX(`date_of_issue`) AS `date_of_issue` FROM `sdn_history` \
+            WHERE `sdn_history`.`date_of_issue` <= '2022-11-08' GROUP BY `sdn_history`.`fixed_ref`) ORDER BY `sdn_history`.`fixed_ref` ASC, `sdn_history`.`revision` ASC";
+        assert_eq!(excepted, last_rows_as_of(NaiveDate::from_ymd(2022, 11, 8)).build(DbBackend::MySql).to_string());
+    }
+}
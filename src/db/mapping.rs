@@ -0,0 +1,319 @@
+//! Mapping of the Ofac FeatureTypes to the sdn columns and child tables
+//!
+//! The built-in mapping is `features.yaml`, another file can be given with `--mapping` to map new
+//! FeatureTypes without a new release of the agent.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use sea_orm::sea_query::Value;
+use sea_orm::{ColumnTrait, ColumnType, IdenStatic, ModelTrait};
+use serde::Deserialize;
+
+use super::entity::sdn;
+use crate::document::inputs::known::{GenderKind, KnownValue};
+use crate::document::inputs::models::feature::Feature;
+use crate::document::inputs::models::location::Locations;
+use crate::document::OfacDocumentReferences;
+
+/// Mapping used when no file is given
+const DEFAULT_MAPPING: &str = include_str!("features.yaml");
+
+/// sdn columns that are not filled from the features
+const RESERVED_COLUMNS: &[&str] = &["fixed_ref", "record_id", "identity", "partysubtypeid", "sdn_type", "sanction_date", "sanction_status", "comment", "topmaj"];
+
+lazy_static! {
+    static ref DEFAULT: FeatureMapping = FeatureMapping::from_yaml(DEFAULT_MAPPING).expect("Built-in feature mapping is invalid");
+}
+
+/// How a feature fills an sdn column
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Extract {
+    Value,
+    Uppercase,
+    Integer,
+    /// Values of every feature of the type joined with `/` (e.g. `dca_xbt`)
+    MultiValue,
+    DetailReference,
+    Gender,
+    /// Date of a DatePeriod published with the same start and end
+    DatePeriod,
+    EarliestDate,
+    /// AreaCode named by the primary values of the location
+    AreaCode,
+}
+
+impl Extract {
+    fn accepts(self, column_type: &ColumnType) -> bool {
+        match self {
+            Extract::Value | Extract::Uppercase | Extract::MultiValue | Extract::Gender => matches!(column_type, ColumnType::Text | ColumnType::String(_)),
+            Extract::Integer | Extract::DetailReference | Extract::AreaCode => matches!(column_type, ColumnType::Integer),
+            Extract::DatePeriod | Extract::EarliestDate => matches!(column_type, ColumnType::Date),
+        }
+    }
+}
+
+/// Child tables filled from the features, named as the fields of [`sdn::SdnInnerRelation`]
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum MappedRelation {
+    Address,
+    Bics,
+    Biks,
+    Caatsa235s,
+    Citizens,
+    Dobs,
+    Emails,
+    Eo13662dds,
+    Eo13846infs,
+    Eo14024dds,
+    EquityTickers,
+    FormerVesselFlags,
+    Isins,
+    IssuerNames,
+    Nationalities,
+    NationalityRegistrations,
+    Operators,
+    OtherVesselFlags,
+    PhoneNumbers,
+    Pobs,
+    Targets,
+    Websites,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+struct FeatureMappingEntry {
+    feature: i32,
+    name: Option<String>,
+    #[serde(default)]
+    aliases: Vec<i32>,
+    column: Option<String>,
+    relation: Option<MappedRelation>,
+    extract: Option<Extract>,
+    sdn_type: Option<String>,
+}
+
+/// Target of a FeatureType
+#[derive(Debug, Clone, Copy)]
+pub enum FeatureTarget {
+    Column(sdn::Column, Extract),
+    Relation(MappedRelation),
+}
+
+impl PartialEq for FeatureTarget {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (FeatureTarget::Column(column, extract), FeatureTarget::Column(other_column, other_extract)) => column.as_str() == other_column.as_str() && extract == other_extract,
+            (FeatureTarget::Relation(relation), FeatureTarget::Relation(other_relation)) => relation == other_relation,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for FeatureTarget {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct FeatureMap {
+    pub feature: i32,
+    pub target: FeatureTarget,
+    /// The feature is only mapped for this sdn type
+    pub sdn_type: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct FeatureMapping {
+    maps: Vec<FeatureMap>,
+    by_name: HashMap<String, usize>,
+    by_id: HashMap<i32, usize>,
+}
+
+impl FeatureMapping {
+    /// The built-in mapping, used by the documents without a mapping file
+    pub fn built_in() -> &'static FeatureMapping {
+        &DEFAULT
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<FeatureMapping, Box<dyn Error>> {
+        let content = std::fs::read_to_string(path.as_ref()).map_err(|err| format!("Feature mapping {} can not be read : {}", path.as_ref().to_string_lossy(), err))?;
+        FeatureMapping::from_yaml(&content)
+    }
+
+    /// Parse and check a mapping, a column must exist, accept the extracted value and can not be mapped twice
+    pub fn from_yaml(content: &str) -> Result<FeatureMapping, Box<dyn Error>> {
+        let entries: Vec<FeatureMappingEntry> = serde_yaml::from_str(content)?;
+        let mut mapping = FeatureMapping {
+            maps: Vec::with_capacity(entries.len()),
+            by_name: HashMap::new(),
+            by_id: HashMap::new(),
+        };
+        for entry in entries {
+            let target = match (entry.column.as_deref(), entry.relation, entry.extract) {
+                (Some(column), None, Some(extract)) => {
+                    if RESERVED_COLUMNS.contains(&column) {
+                        return Err(format!("FeatureType {} : column {} can not be mapped", entry.feature, column).into());
+                    }
+                    let column = sdn::Column::from_str(column).map_err(|_| format!("FeatureType {} : column {} does not exist", entry.feature, column))?;
+                    if !extract.accepts(column.def().get_column_type()) {
+                        return Err(format!("FeatureType {} : {:?} can not fill column {:?}", entry.feature, extract, column).into());
+                    }
+                    let shared = |map: &FeatureMap| matches!(map.target, FeatureTarget::Column(mapped, mapped_extract) if mapped.as_str() == column.as_str() && (extract != Extract::MultiValue || mapped_extract != Extract::MultiValue));
+                    if mapping.maps.iter().any(shared) {
+                        return Err(format!("FeatureType {} : column {:?} is already mapped", entry.feature, column).into());
+                    }
+                    FeatureTarget::Column(column, extract)
+                }
+                (None, Some(relation), None) => FeatureTarget::Relation(relation),
+                _ => return Err(format!("FeatureType {} must be mapped to a column with an extract, or to a relation", entry.feature).into()),
+            };
+            let position = mapping.maps.len();
+            for id in std::iter::once(entry.feature).chain(entry.aliases.iter().copied()) {
+                if mapping.by_id.insert(id, position).is_some() {
+                    return Err(format!("FeatureType {} is mapped twice", id).into());
+                }
+            }
+            if let Some(name) = entry.name.as_deref() {
+                mapping.by_name.insert(name.trim().to_uppercase(), position);
+            }
+            mapping.maps.push(FeatureMap {
+                feature: entry.feature,
+                target,
+                sdn_type: entry.sdn_type.map(|sdn_type| sdn_type.to_uppercase()),
+            });
+        }
+        Ok(mapping)
+    }
+
+    /// Mapping of a FeatureType, looked up by its name in the document and by its id when the name is not mapped
+    pub fn find(&self, feature_type: i32, references: &OfacDocumentReferences) -> Option<&FeatureMap> {
+        let by_name = references.value_name(FEATURE_TYPE_SET, feature_type).and_then(|name| self.by_name.get(&name.trim().to_uppercase()));
+        by_name.or_else(|| self.by_id.get(&feature_type)).map(|&position| &self.maps[position])
+    }
+
+    pub fn len(&self) -> usize {
+        self.maps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+}
+
+const FEATURE_TYPE_SET: &str = "FeatureType";
+
+impl FeatureMap {
+    /// Fill the column of `sdn` with `feature`, the column is kept when the feature has no DatePeriod or location to extract
+    pub fn fill_column(&self, sdn: &mut sdn::Model, feature: &Feature, locations: &Locations, references: &OfacDocumentReferences) {
+        let (column, extract) = match self.target {
+            FeatureTarget::Column(column, extract) => (column, extract),
+            FeatureTarget::Relation(_) => return,
+        };
+        let version = &feature.version;
+        let detail = version.detail.as_ref();
+        let from_detail = !matches!(extract, Extract::DatePeriod | Extract::EarliestDate | Extract::AreaCode);
+        if from_detail && detail.is_none() {
+            return;
+        }
+        let value = detail.and_then(|detail| detail.value.clone());
+        let extracted = match extract {
+            Extract::Value => Value::String(value.map(Box::new)),
+            Extract::Uppercase => Value::String(value.map(|value| Box::new(value.to_uppercase()))),
+            Extract::Integer => Value::Int(value.and_then(|value| value.trim().parse::<i32>().ok())),
+            Extract::MultiValue => match sdn.get(column) {
+                Value::String(Some(current)) => Value::String(Some(Box::new(format!("{}/{}", current, value.unwrap_or_default())))),
+                _ => Value::String(value.map(Box::new)),
+            },
+            Extract::DetailReference => Value::Int(detail.and_then(|detail| detail.detail_reference_id)),
+            Extract::Gender => Value::String(
+                detail
+                    .and_then(|detail| detail.detail_reference_id)
+                    .and_then(|id| references.resolve::<GenderKind>(id))
+                    .and_then(|gender| gender.name())
+                    .map(|gender| Box::new(gender.to_uppercase())),
+            ),
+            Extract::DatePeriod => match version.date_period.as_ref() {
+                Some(date_period) => Value::ChronoDate(date_period.parse_from_to().map(Box::new)),
+                None => return,
+            },
+            Extract::EarliestDate => match version.date_period.as_ref() {
+                Some(date_period) => Value::ChronoDate(date_period.bounds().map(|(earliest, _)| Box::new(earliest))),
+                None => return,
+            },
+            Extract::AreaCode => {
+                let location = match version.location.as_ref().and_then(|location| locations.get(location.id)) {
+                    Some(location) => location,
+                    None => return,
+                };
+                let mut area_code = None;
+                for part in location.location_parts.iter().flatten() {
+                    for part_value in part.values.iter().filter(|part_value| part_value.primary) {
+                        area_code = references.area_code_by_name(&part_value.value).map(|area| area.id);
+                    }
+                }
+                Value::Int(area_code)
+            }
+        };
+        sdn.set(column, extracted);
+    }
+}
+
+#[cfg(test)]
+mod mapping {
+    use super::*;
+    use crate::db::entity::sdn::DocumentEntity;
+    use crate::document::inputs::builder::{NamePart, OfacDocumentBuilder, PartyKind};
+    use crate::document::inputs::known::FeatureKind;
+    use crate::document::DocumentType;
+    use std::sync::Arc;
+
+    #[test]
+    fn built_in_mapping_maps_every_known_feature() {
+        let mapping = FeatureMapping::built_in();
+        let references = OfacDocumentReferences::default();
+        for (kind, _) in FeatureKind::VALUES {
+            assert!(mapping.find(kind.id(), &references).is_some(), "{:?} is not mapped", kind);
+        }
+        assert_eq!(FeatureKind::VALUES.len(), mapping.len());
+        let excepted = FeatureMap {
+            feature: 947,
+            target: FeatureTarget::Relation(MappedRelation::Eo14024dds),
+            sdn_type: None,
+        };
+        assert_eq!(Some(&excepted), mapping.find(948, &references));
+    }
+
+    #[test]
+    fn invalid_mappings_are_refused() {
+        assert!(FeatureMapping::from_yaml("- { feature: 1, column: unknown_column, extract: value }").is_err());
+        assert!(FeatureMapping::from_yaml("- { feature: 1, column: vessel_type, extract: uppercase }").is_err());
+        assert!(FeatureMapping::from_yaml("- { feature: 1, column: fixed_ref, extract: integer }").is_err());
+        assert!(FeatureMapping::from_yaml("- { feature: 1, relation: bics, extract: value }").is_err());
+        assert!(FeatureMapping::from_yaml("- { feature: 1, relation: bics }\n- { feature: 1, relation: biks }").is_err());
+        assert!(FeatureMapping::from_yaml("- { feature: 1, column: title, extract: value }\n- { feature: 2, column: title, extract: value }").is_err());
+        let mapping = FeatureMapping::from_yaml("- { feature: 1000, name: Digital Currency Address - SOL, column: dca_xbt, extract: multi_value }").unwrap();
+        assert_eq!(1, mapping.len());
+    }
+
+    #[test]
+    fn map_a_new_feature_from_file() {
+        let mut builder = OfacDocumentBuilder::new("", DocumentType::OFAC);
+        builder.date_of_issue(chrono::NaiveDate::from_ymd(2022, 11, 8));
+        let mut party = builder.party(36, PartyKind::Entity);
+        party.name(36, true, false, &[(NamePart::EntityName, "AEROCARIBBEAN AIRLINES")]);
+        party.feature_value(37, FeatureKind::DigitalCurrencyXbt.id(), "1AbC");
+        party.feature_value(38, 1000, "2dEf");
+        party.feature_value(39, FeatureKind::Title.id(), "director");
+        party.finish();
+        let mut document = builder.build();
+        let yaml = "- { feature: 344, column: dca_xbt, extract: multi_value }\n- { feature: 1000, name: Digital Currency Address - XBT2, column: dca_xbt, extract: multi_value }";
+        document.references.mapping = Some(Arc::new(FeatureMapping::from_yaml(yaml).unwrap()));
+        let party = &document.distinct_parties.parties[0];
+        let (sdn, _) = sdn::Model::from_ofac_document(&DocumentEntity(party, &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!(Some("1AbC/2dEf".to_owned()), sdn.dca_xbt);
+        assert_eq!(None, sdn.title);
+    }
+}
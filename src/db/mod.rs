@@ -1,22 +1,28 @@
-use crate::processor::sdn::{QuerySdnRecord, SdnRecord};
+use self::batch::WriteBatch;
+use self::changes::{Change, ChangeKind, ChangeRecorder};
+use crate::document::inputs::known::{EntryEventKind, KnownValue, LocPartKind, ReliabilityKind};
+use crate::processor::sdn::{QuerySdnDatePeriod, QuerySdnDigitalCurrencyAddress, QuerySdnDob, QuerySdnRecord, QuerySdnSanctionEvent, QuerySdnSanctionMeasure, SdnRecord, SdnRecordAddressVariant, SdnRecordDatePeriod, SdnRecordDigitalCurrencyAddress, SdnRecordDobPeriod, SdnRecordEvent};
 use crate::{config::Config, document::OfacDocumentReferences, processor::entity::name::SdnAlias};
 use async_trait::async_trait;
 use chrono::NaiveDate;
-use log::{error, info};
+use log::info;
 use sea_orm::entity::*;
 use sea_orm::sea_query::value::FromValueTuple;
 use sea_orm::sea_query::{Alias, Expr, Func, Iden, IntoCondition};
 use sea_orm::{
-    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, Condition, DatabaseTransaction, DeriveColumn, EntityTrait, EnumIter, IntoActiveModel, Iterable, JoinType, ModelTrait, QueryFilter, QueryOrder, RelationTrait, Value,
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseTransaction, DeriveColumn, EntityTrait, EnumIter, IntoActiveModel, Iterable, JoinType, ModelTrait, QueryFilter, QueryOrder, RelationTrait, Value,
 };
-use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr, QuerySelect};
-use std::fmt::Write;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr, QuerySelect, Select};
+use std::fmt::{Display, Write};
 use std::marker::Sync;
-use std::sync::Arc;
 use tokio::sync::MutexGuard;
 
+pub mod batch;
+pub mod changes;
 pub mod entity;
-use std::collections::BTreeMap;
+pub mod history;
+pub mod mapping;
+use std::collections::{BTreeMap, HashMap};
 
 /// Initialize a Mysql DB Pool with the provided Config
 pub async fn init_db(config: &Config) -> Result<DatabaseConnection, DbErr> {
@@ -58,6 +64,32 @@ pub enum OfacEntityFinalOp {
     Nothing,
 }
 
+/// A DistinctParty which can not be converted to its rows, see [`entity::sdn::Model::from_ofac_document`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConversionErr {
+    /// The profile has no SanctionsEntry
+    MissingSanctionsEntry(i32),
+    /// The SanctionsEntry has no SanctionsEvent
+    MissingSanctionsEvent(i32),
+    /// The SanctionsEvent date is not a calendar date
+    InvalidEventDate(i32),
+    /// The FeatureVersion points to a location which is not in the Locations
+    MissingLocation(i32),
+}
+
+impl Display for ConversionErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversionErr::MissingSanctionsEntry(profile) => write!(f, "No SanctionsEntry for the profile {}", profile),
+            ConversionErr::MissingSanctionsEvent(entry) => write!(f, "No SanctionsEvent in the SanctionsEntry {}", entry),
+            ConversionErr::InvalidEventDate(event) => write!(f, "Invalid date for the SanctionsEvent {}", event),
+            ConversionErr::MissingLocation(version) => write!(f, "Location not found for the FeatureVersion {}", version),
+        }
+    }
+}
+
+impl std::error::Error for ConversionErr {}
+
 /// `UPPER(*)` SQL operator
 struct SqlUpper;
 
@@ -69,8 +101,11 @@ impl Iden for SqlUpper {
 
 /// Represents an Ofac Ref (i.e. referential) entity that can be loaded from xml document
 #[async_trait]
-pub trait OfacRefEntity<T: std::marker::Sync, R, M> {
-    async fn from_ofac_document(entity: &T, in_db: &[M], references: &OfacDocumentReferences, tx: &MutexGuard<DatabaseTransaction>) -> Result<Option<R>, DbErr>;
+///
+/// The rows already in db are given by key (i.e their id)
+pub trait OfacRefEntity<T: std::marker::Sync, R, M, K = i32> {
+    /// New rows are inserted in `tx` and recorded in `changes`, the changed rows are returned to be updated
+    async fn from_ofac_document(entity: &T, in_db: &HashMap<K, M>, references: &OfacDocumentReferences, tx: &MutexGuard<DatabaseTransaction>, changes: &ChangeRecorder) -> Result<Option<R>, DbErr>;
 }
 
 /// Represents an Ofac Relation entity between Many to Many relation
@@ -111,6 +146,8 @@ macro_rules! impl_parse_feature_version_value {
             pub fn from_ofac_document(entity: &FeatureVersion) -> $m {
                 let mut model = $name {
                     id: entity.id,
+                    reliability: entity.reliability_id,
+                    comment: entity.comment(),
                     topmaj: "N".to_owned(),
                     ..Default::default()
                 };
@@ -137,6 +174,8 @@ macro_rules! impl_parse_feature_version_id {
             pub fn from_ofac_document(entity: &FeatureVersion) -> $m {
                 let mut model = $name {
                     id: entity.id,
+                    reliability: entity.reliability_id,
+                    comment: entity.comment(),
                     topmaj: "N".to_owned(),
                     ..Default::default()
                 };
@@ -165,9 +204,11 @@ where
     R: Send + Sync + OfacRelEntity + ActiveModelBehavior + ActiveModelTrait + IntoActiveModel<R>,
     AM: Send + Sync + PartialEq + ActiveModelBehavior + ActiveModelTrait<Entity = E>,
 {
-    async fn process_entity(models: &mut [M], related: &mut Vec<M>, db: &DatabaseConnection, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>, identity: i32, op: &mut OfacEntityFinalOp) -> Result<(), DbErr> {
+    /// Writes are added to `batch`, they are written in the import transaction when the batch is flushed, and recorded
+    /// in `changes`
+    async fn process_entity(models: &mut [M], related: &mut Vec<M>, batch: &WriteBatch, changes: &ChangeRecorder, identity: i32, op: &mut OfacEntityFinalOp) -> Result<(), DbErr> {
         for model in models.iter_mut() {
-            if Self::insert_if_new(model, tx, related, identity).await? {
+            if Self::insert_if_new(model, related, batch, changes, identity)? {
                 if *op == OfacEntityFinalOp::Nothing {
                     *op = OfacEntityFinalOp::Insert;
                 }
@@ -180,20 +221,22 @@ where
                 Self::set_topmaj(&mut in_db, "N".to_owned());
                 if &in_db == model {
                     if in_db_topmaj == *"O" {
-                        Self::update_only_topmaj(&mut in_db, db).await?;
+                        Self::update_only_topmaj(&mut in_db, batch);
+                        changes.record(Change::of_model(ChangeKind::UpdateTopmajOnly, &in_db, Some(identity)));
                         if *op == OfacEntityFinalOp::Nothing {
                             *op = OfacEntityFinalOp::UpdateTopmajOnly;
                         }
                     }
                     continue;
                 }
-                Self::update_entity(model, db).await?;
+                changes.record(Change::of_update(&in_db, model, Some(identity)));
+                Self::update_entity(model, batch)?;
                 if *op == OfacEntityFinalOp::Nothing {
                     *op = OfacEntityFinalOp::Update;
                 }
             }
         }
-        Self::process_related(related, db, identity).await?;
+        Self::process_related(related, batch, changes, identity)?;
         Ok(())
     }
 
@@ -208,57 +251,49 @@ where
 
     /// If entity is same as DB except for topmaj
     /// We have to update topmaj only
-    async fn update_only_topmaj(model: &mut M, db: &DatabaseConnection) -> Result<(), DbErr> {
-        let mut am: AM = model.clone().into_active_model();
-        Self::set_topmaj_active(&mut am, "N".to_owned());
-        am.update(db).await?;
-        Ok(())
+    fn update_only_topmaj(model: &mut M, batch: &WriteBatch) {
+        let am: AM = model.clone().into_active_model();
+        batch.update_only_topmaj(&am);
     }
 
     /// If entity is different from in DB
     /// We have to update all field
-    async fn update_entity(model: &mut M, db: &DatabaseConnection) -> Result<(), DbErr> {
+    fn update_entity(model: &mut M, batch: &WriteBatch) -> Result<(), DbErr> {
         Self::set_topmaj(model, "O".to_owned());
         let mut am = model.clone().into_active_model();
         Self::set_all_active(&mut am)?;
-        am.update(db).await?;
+        batch.update(&am);
         Ok(())
     }
 
     /// If related (i.e entity is in DB but not present in xml doc) remains after entity processed
     /// We have to delete these entities in DB
-    async fn process_related(related: &[M], db: &DatabaseConnection, rhs: i32) -> Result<(), DbErr> {
+    fn process_related(related: &[M], batch: &WriteBatch, changes: &ChangeRecorder, rhs: i32) -> Result<(), DbErr> {
         for model_rel in related {
             let am = model_rel.clone().into_active_model();
             let primary_key_value = match am.get_primary_key_value() {
                 Some(val) => FromValueTuple::from_value_tuple(val),
                 None => return Err(DbErr::Exec(sea_orm::RuntimeErr::Internal("Fail to get primary key from model".to_owned()))),
             };
-            R::generate(primary_key_value, rhs).delete(db).await?;
-            am.delete(db).await?;
+            batch.delete(&R::generate(primary_key_value, rhs));
+            batch.delete(&am);
+            changes.record(Change::of_delete(model_rel, Some(rhs)));
         }
         Ok(())
     }
 
     /// Insert the entity if it is not present in DB
     /// Return true if inserted (i.e new entity in db)
-    async fn insert_if_new(model: &mut M, tx: &Arc<tokio::sync::Mutex<DatabaseTransaction>>, related: &[M], rhs: i32) -> Result<bool, DbErr> {
-        let id = Self::get_primary_key(model).unwrap();
+    fn insert_if_new(model: &mut M, related: &[M], batch: &WriteBatch, changes: &ChangeRecorder, rhs: i32) -> Result<bool, DbErr> {
+        let id = Self::get_primary_key(model)?;
         if related.iter().any(|m| Self::get_primary_key(m).unwrap() == id) {
             return Ok(false);
         }
         Self::set_topmaj(model, "O".to_owned());
-        {
-            let am: AM = model.clone().into_active_model();
-            let lock = tx.lock().await;
-            match am.insert(&*lock).await {
-                Ok(_) => <R as sea_orm::ActiveModelTrait>::Entity::insert(R::generate(id, rhs)).exec(&*lock).await.unwrap(),
-                Err(e) => {
-                    error!("{:?} {}", model, e);
-                    return Err(e);
-                }
-            };
-        }
+        let am: AM = model.clone().into_active_model();
+        batch.insert(&am);
+        batch.insert(&R::generate(id, rhs));
+        changes.record(Change::of_insert(model, Some(rhs)));
         Ok(true)
     }
 
@@ -354,6 +389,24 @@ pub async fn find_fixed_ref_with_names(db: &DatabaseConnection) -> Result<BTreeM
 }
 
 pub async fn find_records(db: &DatabaseConnection, ddc_db: &DatabaseConnection) -> Result<(Vec<SdnRecord>, Vec<entity::ddc_name::Model>), DbErr> {
+    let ddc_programs: Vec<String> = entity::ddc_pgm::Entity::find()
+        .select_only()
+        .column_as(Func::cust(SqlUpper).args(vec![Expr::col(entity::ddc_pgm::Column::Program)]), Pgm::Program)
+        .filter(Condition::all().add(entity::ddc_pgm::Column::Sanctioned.eq(true)))
+        .into_values::<_, Pgm>()
+        .all(ddc_db)
+        .await
+        .unwrap();
+    let records = find_records_by(db, Condition::all(), &ddc_programs).await?;
+    let other_names = entity::ddc_name::Entity::find().all(ddc_db).await?;
+    Ok((records, other_names))
+}
+
+/// Records of the active sdns matching `condition`, `db` is the transaction of an import to read the sdns it has saved
+///
+/// The details of the records are queried apart, filtered by `condition` as well so that only the records are read
+pub async fn find_records_by<C: ConnectionTrait>(db: &C, condition: Condition, ddc_programs: &[String]) -> Result<Vec<SdnRecord>, DbErr> {
+    let active = condition.clone().add(entity::sdn::Column::SanctionStatus.eq("ACTIVE".to_owned()));
     let sdn_record: Vec<QuerySdnRecord> = entity::sdn::Entity::find()
         .select_only()
         .column(entity::sdn::Column::FixedRef)
@@ -384,7 +437,6 @@ pub async fn find_records(db: &DatabaseConnection, ddc_db: &DatabaseConnection)
         .column_as(entity::address::Column::State, "address_state")
         .column_as(entity::address::Column::Region, "address_region")
         .column_as(entity::address::Column::IsPrimary, "address_is_primary")
-        .column_as(entity::dob::Column::Dob, "dob_dob")
         .column_as(entity::pob::Column::Pob, "pob_pob")
         .column_as(entity::citizen::Column::Location, "citizen_location")
         .column_as(entity::website::Column::Website, "website_website")
@@ -397,6 +449,7 @@ pub async fn find_records(db: &DatabaseConnection, ddc_db: &DatabaseConnection)
         .column_as(entity::aircraft_operator::Column::Operator, "aircraft_operator")
         .column_as(entity::phone_number::Column::PhoneNumber, "phone_number")
         .column_as(entity::document::Column::RegistrationNumber, "document_registration_number")
+        .column_as(entity::document::Column::NormalizedNumber, "document_normalized_number")
         .column_as(entity::document::Column::Doctype, "document_type")
         .column_as(entity::document::Column::ExpirationDate, "document_expiration_date")
         .column_as(entity::document::Column::IssuedDate, "document_issued_date")
@@ -445,14 +498,6 @@ pub async fn find_records(db: &DatabaseConnection, ddc_db: &DatabaseConnection)
                 .into(),
         )
         .join(JoinType::LeftJoin, entity::address_sdn::Relation::Address.def())
-        .join_rev(
-            JoinType::LeftJoin,
-            entity::dob_identity::Entity::belongs_to(entity::sdn::Entity)
-                .from(entity::dob_identity::Column::IdentityId)
-                .to(entity::sdn::Column::Identity)
-                .into(),
-        )
-        .join(JoinType::LeftJoin, entity::dob_identity::Relation::Dob.def())
         .join_rev(
             JoinType::LeftJoin,
             entity::pob_identity::Entity::belongs_to(entity::sdn::Entity)
@@ -594,7 +639,7 @@ pub async fn find_records(db: &DatabaseConnection, ddc_db: &DatabaseConnection)
         .join_as(JoinType::LeftJoin, entity::sdn::Relation::RefReference5.def(), Alias::new("ref_prohibited_transactions"))
         .join_as(JoinType::LeftJoin, entity::sdn::Relation::RefReference6.def(), Alias::new("ref_organization_type"))
         .join_as(JoinType::LeftJoin, entity::sdn::Relation::RefReference4.def(), Alias::new("ref_vessel_type"))
-        .filter(Condition::all().add(entity::sdn::Column::SanctionStatus.eq("ACTIVE".to_owned())))
+        .filter(active.clone())
         .order_by_asc(entity::sdn::Column::FixedRef)
         .into_model::<QuerySdnRecord>()
         .all(db)
@@ -602,23 +647,19 @@ pub async fn find_records(db: &DatabaseConnection, ddc_db: &DatabaseConnection)
         .unwrap();
     let mut records = Vec::new();
     let mut current_fixed_ref = 0;
-    let mut current_record = SdnRecord::default();
-    let ddc_programs: Vec<String> = entity::ddc_pgm::Entity::find()
-        .select_only()
-        .column_as(Func::cust(SqlUpper).args(vec![Expr::col(entity::ddc_pgm::Column::Program)]), Pgm::Program)
-        .filter(Condition::all().add(entity::ddc_pgm::Column::Sanctioned.eq(true)))
-        .into_values::<_, Pgm>()
-        .all(ddc_db)
-        .await
-        .unwrap();
-    let other_names = entity::ddc_name::Entity::find().all(ddc_db).await?;
+    let mut current_record = SdnRecord {
+        ddc_programs: ddc_programs.to_vec(),
+        ..Default::default()
+    };
     let mut related_names = Vec::new();
     for sdn in sdn_record {
         if sdn.fixed_ref != current_fixed_ref && current_fixed_ref != 0 {
             related_names.clear();
             records.push(current_record);
-            current_record = SdnRecord::default();
-            current_record.ddc_programs = ddc_programs.clone();
+            current_record = SdnRecord {
+                ddc_programs: ddc_programs.to_vec(),
+                ..Default::default()
+            };
         }
         current_fixed_ref = sdn.fixed_ref;
         SdnRecord::from_query_sdn_record(&sdn, &mut current_record).unwrap();
@@ -657,10 +698,267 @@ pub async fn find_records(db: &DatabaseConnection, ddc_db: &DatabaseConnection)
             }
         }
     }
-    Ok((records, other_names))
+    if current_fixed_ref != 0 {
+        records.push(current_record);
+    }
+    let ref_values = find_ref_values(db).await?;
+    add_sanction_details(db, &active, &mut records, &ref_values).await?;
+    add_dob_details(db, &active, &mut records, &ref_values).await?;
+    add_date_periods(db, &active, &mut records).await?;
+    add_address_variants(db, &active, &mut records).await?;
+    let mut digital_currency_addresses = find_digital_currency_addresses(db, condition).await?;
+    for record in records.iter_mut() {
+        record.digital_currency_addresses = digital_currency_addresses.remove(&record.fixed_ref).unwrap_or_default();
+    }
+    Ok(records)
+}
+
+/// Values of every ReferenceValueSet, keyed by set and id
+async fn find_ref_values<C: ConnectionTrait>(db: &C) -> Result<HashMap<(String, i32), String>, DbErr> {
+    Ok(entity::ref_value::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|value| ((value.reference_set, value.id), value.value))
+        .collect())
+}
+
+/// Add the EntryEvents and SanctionsMeasures of each record, queried apart to keep the records query small
+async fn add_sanction_details<C: ConnectionTrait>(db: &C, condition: &Condition, records: &mut [SdnRecord], ref_values: &HashMap<(String, i32), String>) -> Result<(), DbErr> {
+    let ref_value = |set: &str, id: i32| ref_values.get(&(set.to_owned(), id)).cloned().unwrap_or_else(|| id.to_string());
+    let event_kind = |id: i32| match ref_values.get(&(EntryEventKind::SET.to_owned(), id)) {
+        Some(name) => EntryEventKind::from_name(name),
+        None => EntryEventKind::from_id(id),
+    };
+    let mut events: BTreeMap<i32, Vec<(SdnRecordEvent, Option<EntryEventKind>)>> = BTreeMap::new();
+    let query_events = entity::sdn::Entity::find()
+        .select_only()
+        .column(entity::sdn::Column::FixedRef)
+        .column(entity::sanction_event::Column::EventType)
+        .column(entity::sanction_event::Column::EventDate)
+        .column(entity::sanction_event::Column::LegalBasis)
+        .join_rev(
+            JoinType::InnerJoin,
+            entity::sanction_event_sdn::Entity::belongs_to(entity::sdn::Entity)
+                .from(entity::sanction_event_sdn::Column::SdnId)
+                .to(entity::sdn::Column::FixedRef)
+                .into(),
+        )
+        .join(JoinType::InnerJoin, entity::sanction_event_sdn::Relation::SanctionEvent.def())
+        .filter(condition.clone())
+        .into_model::<QuerySdnSanctionEvent>()
+        .all(db)
+        .await?;
+    for event in query_events {
+        let record_event = SdnRecordEvent {
+            event_type: ref_value(EntryEventKind::SET, event.event_type),
+            date: event.event_date,
+            legal_basis: event.legal_basis.map(|legal_basis| ref_value("LegalBasis", legal_basis)),
+        };
+        events.entry(event.fixed_ref).or_default().push((record_event, event_kind(event.event_type)));
+    }
+    let mut measures: BTreeMap<i32, Vec<String>> = BTreeMap::new();
+    let query_measures = entity::sdn::Entity::find()
+        .select_only()
+        .column(entity::sdn::Column::FixedRef)
+        .column(entity::sanction_measure::Column::SanctionsType)
+        .join_rev(
+            JoinType::InnerJoin,
+            entity::sanction_measure_sdn::Entity::belongs_to(entity::sdn::Entity)
+                .from(entity::sanction_measure_sdn::Column::SdnId)
+                .to(entity::sdn::Column::FixedRef)
+                .into(),
+        )
+        .join(JoinType::InnerJoin, entity::sanction_measure_sdn::Relation::SanctionMeasure.def())
+        .filter(condition.clone())
+        .into_model::<QuerySdnSanctionMeasure>()
+        .all(db)
+        .await?;
+    for measure in query_measures {
+        let sanctions_type = ref_value("SanctionsType", measure.sanctions_type);
+        let record_measures = measures.entry(measure.fixed_ref).or_default();
+        if !record_measures.contains(&sanctions_type) {
+            record_measures.push(sanctions_type);
+        }
+    }
+    for record in records.iter_mut() {
+        for (event, kind) in events.remove(&record.fixed_ref).unwrap_or_default() {
+            record.add_sanction_event(event, kind);
+        }
+        record.sanction_measures = measures.remove(&record.fixed_ref).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// Add the dobs with their bounds and reliability, the reliability is resolved by its name in `ref_value`
+///
+/// Each dob row is added on its own, two dobs formatted the same way keep their own reliability
+async fn add_dob_details<C: ConnectionTrait>(db: &C, condition: &Condition, records: &mut [SdnRecord], ref_values: &HashMap<(String, i32), String>) -> Result<(), DbErr> {
+    let reliability = |id: i32| match ref_values.get(&(ReliabilityKind::SET.to_owned(), id)) {
+        Some(name) => ReliabilityKind::from_name(name),
+        None => ReliabilityKind::from_id(id),
+    };
+    let mut dobs: BTreeMap<i32, Vec<QuerySdnDob>> = BTreeMap::new();
+    let query_dobs = entity::sdn::Entity::find()
+        .select_only()
+        .column(entity::sdn::Column::FixedRef)
+        .column(entity::dob::Column::Id)
+        .column(entity::dob::Column::Dob)
+        .column(entity::dob::Column::Reliability)
+        .column(entity::dob::Column::Earliest)
+        .column(entity::dob::Column::Latest)
+        .column(entity::dob::Column::Approximate)
+        .join_rev(
+            JoinType::InnerJoin,
+            entity::dob_identity::Entity::belongs_to(entity::sdn::Entity)
+                .from(entity::dob_identity::Column::IdentityId)
+                .to(entity::sdn::Column::Identity)
+                .into(),
+        )
+        .join(JoinType::InnerJoin, entity::dob_identity::Relation::Dob.def())
+        .filter(condition.clone())
+        .order_by_asc(entity::dob::Column::Id)
+        .into_model::<QuerySdnDob>()
+        .all(db)
+        .await?;
+    for dob in query_dobs {
+        let record_dobs = dobs.entry(dob.fixed_ref).or_default();
+        if !record_dobs.iter().any(|record_dob| record_dob.id == dob.id) {
+            record_dobs.push(dob);
+        }
+    }
+    for record in records.iter_mut() {
+        for dob in dobs.remove(&record.fixed_ref).unwrap_or_default() {
+            let kind = reliability(dob.reliability).unwrap_or(ReliabilityKind::Reliable);
+            record.add_dob(&dob.dob, kind);
+            if let (Some(earliest), Some(latest), true) = (dob.earliest, dob.latest, kind != ReliabilityKind::Fabricated) {
+                record.add_dob_period(SdnRecordDobPeriod {
+                    earliest,
+                    latest,
+                    approximate: dob.approximate,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Add the DatePeriods of the features, the feature is named after `ref_feature`
+async fn add_date_periods<C: ConnectionTrait>(db: &C, condition: &Condition, records: &mut [SdnRecord]) -> Result<(), DbErr> {
+    let mut date_periods: BTreeMap<i32, Vec<SdnRecordDatePeriod>> = BTreeMap::new();
+    let query_date_periods = entity::sdn::Entity::find()
+        .select_only()
+        .column(entity::sdn::Column::FixedRef)
+        .column(entity::date_period::Column::FeatureType)
+        .column_as(entity::ref_feature::Column::Value, "feature")
+        .column(entity::date_period::Column::Earliest)
+        .column(entity::date_period::Column::Latest)
+        .column(entity::date_period::Column::Approximate)
+        .column(entity::date_period::Column::Value)
+        .join_rev(
+            JoinType::InnerJoin,
+            entity::date_period_sdn::Entity::belongs_to(entity::sdn::Entity)
+                .from(entity::date_period_sdn::Column::SdnId)
+                .to(entity::sdn::Column::FixedRef)
+                .into(),
+        )
+        .join(JoinType::InnerJoin, entity::date_period_sdn::Relation::DatePeriod.def())
+        .join(JoinType::LeftJoin, entity::date_period::Relation::RefFeature.def())
+        .filter(condition.clone())
+        .order_by_asc(entity::date_period::Column::Earliest)
+        .into_model::<QuerySdnDatePeriod>()
+        .all(db)
+        .await?;
+    for date_period in query_date_periods {
+        date_periods.entry(date_period.fixed_ref).or_default().push(SdnRecordDatePeriod {
+            feature: date_period.feature.unwrap_or_else(|| date_period.feature_type.to_string()),
+            earliest: date_period.earliest,
+            latest: date_period.latest,
+            approximate: date_period.approximate,
+            value: date_period.value,
+        });
+    }
+    for record in records.iter_mut() {
+        record.date_periods = date_periods.remove(&record.fixed_ref).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// Digital currency addresses of the ACTIVE sdns matching `condition` by FixedRef
+pub async fn find_digital_currency_addresses<C: ConnectionTrait>(db: &C, condition: Condition) -> Result<BTreeMap<i32, Vec<SdnRecordDigitalCurrencyAddress>>, DbErr> {
+    let mut addresses: BTreeMap<i32, Vec<SdnRecordDigitalCurrencyAddress>> = BTreeMap::new();
+    let query_addresses = entity::sdn::Entity::find()
+        .select_only()
+        .column(entity::sdn::Column::FixedRef)
+        .column(entity::digital_currency_address::Column::Currency)
+        .column(entity::digital_currency_address::Column::Address)
+        .join_rev(
+            JoinType::InnerJoin,
+            entity::digital_currency_address_sdn::Entity::belongs_to(entity::sdn::Entity)
+                .from(entity::digital_currency_address_sdn::Column::SdnId)
+                .to(entity::sdn::Column::FixedRef)
+                .into(),
+        )
+        .join(JoinType::InnerJoin, entity::digital_currency_address_sdn::Relation::DigitalCurrencyAddress.def())
+        .filter(condition.add(entity::sdn::Column::SanctionStatus.eq("ACTIVE".to_owned())))
+        .order_by_asc(entity::digital_currency_address::Column::Currency)
+        .order_by_asc(entity::digital_currency_address::Column::Address)
+        .into_model::<QuerySdnDigitalCurrencyAddress>()
+        .all(db)
+        .await?;
+    for address in query_addresses {
+        addresses.entry(address.fixed_ref).or_default().push(SdnRecordDigitalCurrencyAddress {
+            currency: address.currency,
+            address: address.address,
+        });
+    }
+    Ok(addresses)
+}
+
+/// Add the non primary values of the addresses parts
+async fn add_address_variants<C: ConnectionTrait>(db: &C, condition: &Condition, records: &mut [SdnRecord]) -> Result<(), DbErr> {
+    let mut variants: HashMap<i32, Vec<SdnRecordAddressVariant>> = HashMap::new();
+    for variant in address_variants_of(condition).all(db).await? {
+        variants.entry(variant.address_id).or_default().push(SdnRecordAddressVariant {
+            part: LocPartKind::from_id(variant.loc_part_type).and_then(LocPartKind::name).map_or_else(|| variant.loc_part_type.to_string(), str::to_owned),
+            script: variant.script,
+            value: variant.value,
+        });
+    }
+    for address in records.iter_mut().flat_map(|record| record.addresses.iter_mut()) {
+        if let Some(address_variants) = variants.get(&address.id) {
+            address.variants = address_variants.clone();
+        }
+    }
+    Ok(())
+}
+
+/// Address variants of the sdns matching `condition`
+fn address_variants_of(condition: &Condition) -> Select<entity::address_variant::Entity> {
+    entity::address_variant::Entity::find()
+        .join(JoinType::InnerJoin, entity::address_variant::Relation::AddressVariantSdn.def())
+        .join(JoinType::InnerJoin, entity::address_variant_sdn::Relation::Sdn.def())
+        .filter(condition.clone())
+        .order_by_asc(entity::address_variant::Column::LocPartType)
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
 enum Pgm {
     Program,
 }
+
+#[cfg(test)]
+mod db {
+    use super::*;
+    use sea_orm::{DbBackend, QueryTrait};
+
+    #[test]
+    fn address_variants_are_read_for_the_records_only() {
+        let condition = Condition::all().add(entity::sdn::Column::FixedRef.is_in(vec![36, 173])).add(entity::sdn::Column::SanctionStatus.eq("ACTIVE".to_owned()));
+        let excepted = "SELECT `address_variant`.`id`, `address_variant`.`address_id`, `address_variant`.`loc_part_type`, `address_variant`.`script`, `address_variant`.`value`, `address_variant`.`topmaj` \
+            FROM `address_variant` INNER JOIN `address_variant_sdn` ON `address_variant`.`id` = `address_variant_sdn`.`address_variant_id` \
+            INNER JOIN `sdn` ON `address_variant_sdn`.`identity_id` = `sdn`.`identity` \
+            WHERE `sdn`.`fixed_ref` IN (36, 173) AND `sdn`.`sanction_status` = 'ACTIVE' ORDER BY `address_variant`.`loc_part_type` ASC";
+        assert_eq!(excepted, address_variants_of(&condition).build(DbBackend::MySql).to_string());
+    }
+}
@@ -0,0 +1,401 @@
+use std::error::Error;
+use std::io::BufRead;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use quick_xml::DeError;
+use serde::Deserialize;
+
+use crate::document::inputs::builder::{date_period_day, date_period_month, date_period_year, date_period_years, Address, NamePart, OfacDocumentBuilder, PartyBuilder, PartyKind, LATIN_SCRIPT};
+use crate::document::inputs::list::ListSource;
+use crate::document::inputs::reader::read_root_attribute;
+use crate::document::inputs::models::feature::DatePeriod;
+use crate::document::inputs::source::SourceReader;
+use crate::document::inputs::{DocumentType, OfacDocument};
+
+/// Root of the EU Financial Sanctions Files (FSF) xml
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Export {
+    /// e.g. `2022-11-08T18:00:01.360+01:00`
+    #[serde(rename = "generationDate")]
+    pub generation_date: String,
+    #[serde(rename = "sanctionEntity", default)]
+    pub entities: Vec<SanctionEntity>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct SanctionEntity {
+    #[serde(rename = "logicalId")]
+    pub logical_id: i32,
+    #[serde(rename = "euReferenceNumber")]
+    pub eu_reference_number: Option<String>,
+    #[serde(rename = "remark", default)]
+    pub remarks: Vec<String>,
+    #[serde(rename = "regulation", default)]
+    pub regulations: Vec<Regulation>,
+    #[serde(rename = "subjectType")]
+    pub subject_type: SubjectType,
+    #[serde(rename = "nameAlias", default)]
+    pub name_aliases: Vec<NameAlias>,
+    #[serde(rename = "citizenship", default)]
+    pub citizenships: Vec<Citizenship>,
+    #[serde(rename = "birthdate", default)]
+    pub birthdates: Vec<Birthdate>,
+    #[serde(rename = "identification", default)]
+    pub identifications: Vec<Identification>,
+    #[serde(rename = "address", default)]
+    pub addresses: Vec<EuAddress>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Regulation {
+    #[serde(rename = "publicationDate")]
+    pub publication_date: Option<String>,
+    pub programme: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct SubjectType {
+    /// `person` or `enterprise`
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct NameAlias {
+    #[serde(rename = "logicalId")]
+    pub logical_id: i32,
+    #[serde(rename = "firstName")]
+    pub first_name: Option<String>,
+    #[serde(rename = "middleName")]
+    pub middle_name: Option<String>,
+    #[serde(rename = "lastName")]
+    pub last_name: Option<String>,
+    #[serde(rename = "wholeName")]
+    pub whole_name: Option<String>,
+    pub function: Option<String>,
+    /// `M` or `F`
+    pub gender: Option<String>,
+    pub title: Option<String>,
+    /// Aliases which are not strong are low quality aliases
+    #[serde(default = "strong")]
+    pub strong: bool,
+}
+
+fn strong() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Citizenship {
+    #[serde(rename = "logicalId")]
+    pub logical_id: i32,
+    #[serde(rename = "countryDescription")]
+    pub country_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Birthdate {
+    #[serde(rename = "logicalId")]
+    pub logical_id: i32,
+    #[serde(default)]
+    pub circa: bool,
+    /// Full date, e.g. `1937-04-28`
+    pub birthdate: Option<String>,
+    #[serde(rename = "monthOfYear")]
+    pub month_of_year: Option<String>,
+    pub year: Option<String>,
+    #[serde(rename = "yearRangeFrom")]
+    pub year_range_from: Option<String>,
+    #[serde(rename = "yearRangeTo")]
+    pub year_range_to: Option<String>,
+    pub city: Option<String>,
+    pub place: Option<String>,
+    pub region: Option<String>,
+    #[serde(rename = "countryDescription")]
+    pub country_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Identification {
+    #[serde(rename = "logicalId")]
+    pub logical_id: i32,
+    pub number: Option<String>,
+    #[serde(rename = "latinNumber")]
+    pub latin_number: Option<String>,
+    #[serde(rename = "identificationTypeCode")]
+    pub type_code: Option<String>,
+    #[serde(rename = "identificationTypeDescription")]
+    pub type_description: Option<String>,
+    #[serde(rename = "countryDescription")]
+    pub country_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct EuAddress {
+    #[serde(rename = "logicalId")]
+    pub logical_id: i32,
+    pub street: Option<String>,
+    #[serde(rename = "poBox")]
+    pub po_box: Option<String>,
+    pub place: Option<String>,
+    pub city: Option<String>,
+    #[serde(rename = "zipCode")]
+    pub zip_code: Option<String>,
+    pub region: Option<String>,
+    #[serde(rename = "countryDescription")]
+    pub country_description: Option<String>,
+}
+
+/// Empty attributes are written `attr=""` in the FSF
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(|value| value.trim()).filter(|value| !value.is_empty() && *value != "UNKNOWN")
+}
+
+fn parse_iso_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Birthdates are either a full date, a month, a year or a range of years
+fn birthdate_period(birthdate: &Birthdate) -> Option<DatePeriod> {
+    if let Some(date) = non_empty(&birthdate.birthdate).and_then(parse_iso_date) {
+        return Some(date_period_day(date, birthdate.circa));
+    }
+    let year = non_empty(&birthdate.year).and_then(|year| year.parse::<i32>().ok());
+    let month = non_empty(&birthdate.month_of_year).and_then(|month| month.parse::<u32>().ok());
+    match (year, month) {
+        (Some(year), Some(month)) => Some(date_period_month(year, month, birthdate.circa)),
+        (Some(year), None) => Some(date_period_year(year, birthdate.circa)),
+        _ => {
+            let from = non_empty(&birthdate.year_range_from)?.parse().ok()?;
+            let to = non_empty(&birthdate.year_range_to)?.parse().ok()?;
+            Some(date_period_years(from, to))
+        }
+    }
+}
+
+/// Documents the exporters know about are given their Ofac type
+fn document_type(identification: &Identification) -> &str {
+    match non_empty(&identification.type_code) {
+        Some("passport") => "Passport",
+        Some("id") => "National ID No.",
+        _ => non_empty(&identification.type_description).or_else(|| non_empty(&identification.type_code)).unwrap_or("Other"),
+    }
+}
+
+fn add_name(party: &mut PartyBuilder, kind: PartyKind, alias: &NameAlias, primary: bool) {
+    let mut parts = Vec::new();
+    match (kind, non_empty(&alias.last_name)) {
+        (PartyKind::Individual, Some(last_name)) => {
+            for (part, value) in [(NamePart::FirstName, &alias.first_name), (NamePart::MiddleName, &alias.middle_name)] {
+                if let Some(value) = non_empty(value) {
+                    parts.push((part, value));
+                }
+            }
+            parts.push((NamePart::LastName, last_name));
+        }
+        (PartyKind::Individual, None) => parts.extend(non_empty(&alias.whole_name).map(|name| (NamePart::LastName, name))),
+        _ => parts.extend(non_empty(&alias.whole_name).map(|name| (NamePart::EntityName, name))),
+    }
+    party.name(alias.logical_id, primary, !alias.strong, &parts);
+}
+
+/// Read an EU FSF xml into an OfacDocument
+///
+/// logicalIds are used as FixedRefs and ids, the euReferenceNumber is kept in the comment
+pub fn read<R: BufRead, P: AsRef<Path>>(reader: R, folder: P) -> Result<OfacDocument, DeError> {
+    let export: Export = quick_xml::de::from_reader(reader)?;
+    let mut builder = OfacDocumentBuilder::new(folder, DocumentType::of(&EuSource));
+    match parse_iso_date(&export.generation_date) {
+        Some(date) => builder.date_of_issue(date),
+        None => return Err(DeError::Custom(format!("generationDate not recognized {}", export.generation_date))),
+    }
+    for entity in export.entities.iter() {
+        let kind = if entity.subject_type.code == "person" { PartyKind::Individual } else { PartyKind::Entity };
+        // The first latin alias is used as the NAME of the sdn
+        let primary = entity.name_aliases.iter().position(|alias| non_empty(&alias.whole_name).map(|name| builder.script_id(name)) == Some(LATIN_SCRIPT));
+        let mut party = builder.party(entity.logical_id, kind);
+        for (i, alias) in entity.name_aliases.iter().enumerate() {
+            add_name(&mut party, kind, alias, Some(i) == primary);
+            if let Some(title) = non_empty(&alias.title).or_else(|| non_empty(&alias.function)) {
+                if !party.has_feature(26) {
+                    party.feature_value(alias.logical_id, 26, title);
+                }
+            }
+            match non_empty(&alias.gender) {
+                Some("M") if !party.has_feature(224) => party.gender(alias.logical_id, true),
+                Some("F") if !party.has_feature(224) => party.gender(alias.logical_id, false),
+                _ => {}
+            }
+        }
+        if let Some(reference) = non_empty(&entity.eu_reference_number) {
+            party.comment(&format!("EU reference number {}.", reference));
+        }
+        for remark in entity.remarks.iter() {
+            party.comment(remark);
+        }
+        for (i, regulation) in entity.regulations.iter().enumerate() {
+            if let Some(programme) = non_empty(&regulation.programme) {
+                party.program(entity.logical_id * 100 + i as i32, programme);
+            }
+        }
+        if let Some(listed_on) = entity.regulations.iter().filter_map(|regulation| non_empty(&regulation.publication_date).and_then(parse_iso_date)).min() {
+            party.listed_on(listed_on);
+        }
+        for citizenship in entity.citizenships.iter() {
+            if let Some(country) = non_empty(&citizenship.country_description) {
+                party.feature_location(citizenship.logical_id, 10, &Address { country: Some(country.to_owned()), ..Default::default() });
+            }
+        }
+        for birthdate in entity.birthdates.iter() {
+            if let Some(period) = birthdate_period(birthdate) {
+                party.feature_date(birthdate.logical_id, 8, period);
+            }
+            let place: Vec<&str> = [&birthdate.city, &birthdate.place, &birthdate.region, &birthdate.country_description].into_iter().filter_map(non_empty).collect();
+            if !place.is_empty() {
+                party.feature_value(birthdate.logical_id, 9, &place.join(", "));
+            }
+        }
+        for identification in entity.identifications.iter() {
+            let number = non_empty(&identification.latin_number).or_else(|| non_empty(&identification.number)).unwrap_or_default();
+            party.document(identification.logical_id, document_type(identification), number, non_empty(&identification.country_description), None, None);
+        }
+        for address in entity.addresses.iter() {
+            party.feature_location(
+                address.logical_id,
+                25,
+                &Address {
+                    region: non_empty(&address.region).map(|region| region.to_owned()),
+                    address: [&address.street, &address.po_box, &address.place].into_iter().filter_map(non_empty).map(|line| line.to_owned()).collect(),
+                    city: non_empty(&address.city).map(|city| city.to_owned()),
+                    postal_code: non_empty(&address.zip_code).map(|zip_code| zip_code.to_owned()),
+                    country: non_empty(&address.country_description).map(|country| country.to_owned()),
+                    ..Default::default()
+                },
+            );
+        }
+        party.finish();
+    }
+    Ok(builder.build())
+}
+
+/// EU Financial Sanctions Files consolidated list (`eu_fsf.xml`)
+pub struct EuSource;
+
+impl ListSource for EuSource {
+    fn name(&self) -> &'static str {
+        "EU"
+    }
+
+    fn designation(&self) -> &'static str {
+        "EU"
+    }
+
+    fn oid_prefix(&self) -> &'static str {
+        "EUFS"
+    }
+
+    fn file_stem(&self) -> &'static str {
+        "eu_fsf"
+    }
+
+    fn database_url_var(&self) -> &'static str {
+        "EU_DATABASE_URL"
+    }
+
+    fn data_folder_var(&self) -> &'static str {
+        "EU_DATA_FOLDER"
+    }
+
+    fn read(&self, reader: SourceReader, document: &mut OfacDocument) -> Result<(), Box<dyn Error>> {
+        let content = read(reader, &document.root_folder)?;
+        document.set_content(content);
+        Ok(())
+    }
+
+    fn read_date_of_issue(&self, reader: SourceReader) -> Result<NaiveDate, Box<dyn Error>> {
+        let date = read_root_attribute(reader, "generationDate")?.ok_or("generationDate not found")?;
+        Ok(parse_iso_date(&date).ok_or(format!("generationDate not recognized {}", date))?)
+    }
+}
+
+#[cfg(test)]
+mod eu {
+    use super::*;
+    use crate::db::entity::sdn::{self, DocumentEntity};
+
+    const FSF_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<export xmlns="http://eu.europa.ec/fpi/fsd/export" generationDate="2022-11-08T18:00:01.360+01:00" globalFileId="140000">
+  <sanctionEntity designationDetails="" unitedNationId="" euReferenceNumber="EU.27.28" logicalId="13">
+    <remark>Head of the former regime.</remark>
+    <regulation regulationType="amendment" organisationType="council" publicationDate="2003-07-08" entryIntoForceDate="2003-07-07" numberTitle="1210/2003 (OJ L169)" programme="IRQ" logicalId="1">
+      <publicationUrl>http://eur-lex.europa.eu/LexUriServ/LexUriServ.do?uri=OJ:L:2003:169:0006:0023:EN:PDF</publicationUrl>
+    </regulation>
+    <subjectType code="person" classificationCode="P"/>
+    <nameAlias firstName="Saddam" middleName="" lastName="Hussein Al-Tikriti" wholeName="Saddam Hussein Al-Tikriti" function="" gender="M" title="" nameLanguage="" strong="true" regulationLanguage="en" logicalId="17"/>
+    <nameAlias firstName="" middleName="" lastName="" wholeName="Abu Ali" function="" gender="M" title="" nameLanguage="" strong="false" regulationLanguage="en" logicalId="18"/>
+    <nameAlias firstName="" middleName="" lastName="" wholeName="Саддам Хусейн" function="" gender="" title="" nameLanguage="RU" strong="true" regulationLanguage="en" logicalId="19"/>
+    <citizenship region="" countryIso2Code="IQ" countryDescription="IRAQ" regulationLanguage="en" logicalId="20"/>
+    <birthdate circa="false" calendarType="GREGORIAN" city="al-Awja, near Tikrit" zipCode="" birthdate="1937-04-28" dayOfMonth="28" monthOfYear="4" year="1937" region="" place="" countryIso2Code="IQ" countryDescription="IRAQ" regulationLanguage="en" logicalId="21"/>
+    <identification diplomatic="false" knownExpired="false" knownFalse="false" reportedLost="false" revokedByIssuer="false" issuedBy="" latinNumber="" nameOnDocument="" number="A123456" region="" countryIso2Code="IQ" countryDescription="IRAQ" identificationTypeCode="passport" identificationTypeDescription="National passport" regulationLanguage="en" logicalId="22"/>
+  </sanctionEntity>
+  <sanctionEntity designationDetails="" unitedNationId="" euReferenceNumber="EU.1.2" logicalId="30">
+    <regulation regulationType="amendment" organisationType="council" publicationDate="2014-07-31" programme="UKR" logicalId="2"/>
+    <subjectType code="enterprise" classificationCode="E"/>
+    <nameAlias firstName="" middleName="" lastName="" wholeName="Example Bank" function="" gender="" title="" nameLanguage="" strong="true" regulationLanguage="en" logicalId="31"/>
+    <address city="Moscow" street="1 Red Square" poBox="" zipCode="109012" regulationLanguage="en" region="" place="" asAtListingTime="false" countryIso2Code="RU" countryDescription="RUSSIAN FEDERATION" logicalId="32"/>
+  </sanctionEntity>
+</export>"#;
+
+    #[test]
+    fn read_fsf_xml() {
+        let document = read(FSF_XML.as_bytes(), "").unwrap();
+        assert_eq!(NaiveDate::from_ymd(2022, 11, 8), document.references.date_of_issue.to_sql_date());
+        assert_eq!(2, document.distinct_parties.parties.len());
+        assert_eq!(1571, document.documents.documents[0].type_id);
+
+        let (person, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[0], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!(13, person.fixed_ref);
+        assert_eq!("INDIVIDUAL", person.sdn_type);
+        assert_eq!(Some("MALE".to_owned()), person.gender);
+        assert_eq!(Some(NaiveDate::from_ymd(2003, 7, 8)), person.sanction_date);
+        assert_eq!(Some("EU REFERENCE NUMBER EU.27.28. HEAD OF THE FORMER REGIME.".to_owned()), person.comment);
+        assert_eq!(3, relations.names.len());
+        assert_eq!("NAME", relations.names[0].name_type);
+        assert_eq!(Some("Saddam".to_owned()), relations.names[0].first_name);
+        assert_eq!(Some("LOW".to_owned()), relations.names[1].quality);
+        assert_eq!("ALIAS", relations.names[2].name_type);
+        assert_eq!("28 APR 1937", relations.dobs[0].dob);
+        assert_eq!(1, relations.nationalities.len());
+        assert_eq!("IRQ", relations.programs[0].program);
+
+        let (enterprise, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[1], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!("ENTITY", enterprise.sdn_type);
+        assert_eq!(Some("Example Bank".to_owned()), relations.names[0].entity_name);
+        assert_eq!(Some("MOSCOW".to_owned()), relations.address[0].city);
+        assert_eq!(Some("109012".to_owned()), relations.address[0].postal_code);
+    }
+
+    #[test]
+    fn parse_birthdate_shapes() {
+        let mut birthdate = Birthdate {
+            logical_id: 1,
+            circa: true,
+            birthdate: Some(String::new()),
+            month_of_year: Some(String::new()),
+            year: Some("1960".to_owned()),
+            year_range_from: None,
+            year_range_to: None,
+            city: None,
+            place: None,
+            region: None,
+            country_description: None,
+        };
+        let year = birthdate_period(&birthdate).unwrap();
+        assert!(year.start.unwrap().approximate);
+        birthdate.year = None;
+        birthdate.year_range_from = Some("1960".to_owned());
+        birthdate.year_range_to = Some("1962".to_owned());
+        let range = birthdate_period(&birthdate).unwrap();
+        assert_eq!("1962", range.end.unwrap().from.year);
+    }
+}
@@ -0,0 +1,685 @@
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use chrono::{Datelike, NaiveDate};
+
+pub use super::known::NamePart;
+use super::known::{DocumentDateKind, DocumentKind, EntryEventKind, FeatureKind, GenderKind, KnownValue, LocPartKind};
+use super::models::alias::{Alias, DocumentedName, DocumentedNamePart, NamePartValue};
+use super::models::areacode::AreaCode;
+use super::models::dateofissue::DateOfIssue;
+use super::models::distinctparty::{DistinctParty, Identity, MasterNamePartGroup, NamePartGroup, NamePartGroups, Profile};
+use super::models::document::{Document, DocumentDate};
+use super::models::feature::{Date, DatePeriod, DatePeriodRange, Feature, FeatureVersion, VersionDetail, VersionLocation};
+use super::models::location::{Location, LocationCountry, LocationPart, LocationPartValue};
+use super::models::referencevaluesets::{DetailReference, FeatureType, IDRegDocType, PartySubType, Script};
+use super::models::sanction::{EntryEvent, SanctionsEntry, SanctionsMeasure};
+use super::{DocumentType, OfacDocument};
+
+/// Script used by every latin name
+pub const LATIN_SCRIPT: i32 = 215;
+
+/// SanctionsType of the measures holding a program
+const PROGRAM_SANCTIONS_TYPE: i32 = 1;
+
+/// Feature types whose value is a DetailReference instead of a free text
+pub const REFERENCE_FEATURE_TYPES: &[i32] = &[2, 104, 125, 204, 504, 525, 526, 586, 626, 647, 826, 827, 947];
+
+/// Feature types whose value is a DatePeriod
+pub const DATE_FEATURE_TYPES: &[i32] = &[8, 45, 646, 867, 868, 869];
+
+const PARTY_SUB_TYPES: &[(i32, &str)] = &[(1, "Vessel"), (2, "Aircraft"), (3, "Unknown"), (4, "Unknown")];
+
+/// First id given to references that are not known by Ofac, far from the ids used in the advanced xml
+const SYNTHETIC_REFERENCE_ID: u32 = 1_000_000_000;
+
+/// Ids given to the items without id, far from the ids published in the lists
+const DERIVED_ITEM_IDS: Range<i32> = 100_000_000..1_000_000_000;
+
+/// Ids given to the addresses without id, they are written in the OID of the alternative records which only has room for 5 digits
+const DERIVED_ADDRESS_IDS: Range<i32> = 1..100_000;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum PartyKind {
+    Vessel = 1,
+    Aircraft = 2,
+    Entity = 3,
+    Individual = 4,
+}
+
+/// Address of a location, every part is optional
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct Address {
+    pub region: Option<String>,
+    pub address: Vec<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+}
+
+impl Address {
+    pub fn is_empty(&self) -> bool {
+        self.region.is_none() && self.address.is_empty() && self.city.is_none() && self.state.is_none() && self.postal_code.is_none() && self.country.is_none()
+    }
+}
+
+/// Gives a unique id to every item (name, feature, location, document, program) of a built document
+///
+/// The id published in the list is kept when no other item has it yet, the other items get an id computed from
+/// the FixedRef of their party and their rank in it, so that it stays the same from one import to another.
+#[derive(Default)]
+struct ItemIds {
+    used: HashSet<i32>,
+}
+
+impl ItemIds {
+    fn allocate(&mut self, published: Option<i32>, key: &str, range: Range<i32>) -> i32 {
+        if let Some(id) = published {
+            if self.used.insert(id) {
+                return id;
+            }
+        }
+        let span = (range.end - range.start) as u32;
+        let mut id = range.start + (fnv1a(key) % span) as i32;
+        while !self.used.insert(id) {
+            id = if id + 1 == range.end { range.start } else { id + 1 };
+        }
+        id
+    }
+}
+
+/// Builds an OfacDocument from lists that are not published in the Ofac advanced format
+///
+/// Ids published in the lists are given by the caller so they stay the same from one import to another, see [`ItemIds`]
+/// for the items without id. References (countries, document types...) unknown to Ofac get an id computed from their name.
+pub struct OfacDocumentBuilder {
+    document: OfacDocument,
+    ids: ItemIds,
+}
+
+impl OfacDocumentBuilder {
+    pub fn new<P: AsRef<Path>>(folder: P, document_type: DocumentType) -> OfacDocumentBuilder {
+        let mut document = OfacDocument::new(folder, document_type);
+        for (id, value) in PARTY_SUB_TYPES {
+            document.references.party_sub_type_values.values.push(PartySubType { id: *id, value: value.to_string() });
+        }
+        document.references.script_values.scripts.push(Script {
+            id: LATIN_SCRIPT,
+            code: "Latn".to_owned(),
+            value: "Latin".to_owned(),
+        });
+        OfacDocumentBuilder { document, ids: ItemIds::default() }
+    }
+
+    pub fn date_of_issue(&mut self, date: NaiveDate) {
+        self.document.references.date_of_issue = DateOfIssue {
+            year: date.year().to_string(),
+            month: date.month().to_string(),
+            day: date.day().to_string(),
+        };
+    }
+
+    /// Start a new party, it is added to the document when [`PartyBuilder::finish`] is called
+    pub fn party(&mut self, fixed_ref: i32, kind: PartyKind) -> PartyBuilder<'_> {
+        PartyBuilder {
+            party: DistinctParty {
+                fixed_ref,
+                comment: None,
+                profile: Profile {
+                    party_sub_id: kind as i32,
+                    identity: Identity {
+                        id: fixed_ref,
+                        alias: Vec::new(),
+                        name_part_groups: NamePartGroups { master_name_part_group: Vec::new() },
+                    },
+                    feature: None,
+                },
+            },
+            entry: SanctionsEntry {
+                id: fixed_ref,
+                profile_id: fixed_ref,
+                events: Vec::new(),
+                measures: Vec::new(),
+            },
+            items: 0,
+            builder: self,
+        }
+    }
+
+    pub fn contains(&self, fixed_ref: i32) -> bool {
+        self.document.distinct_parties.parties.iter().any(|party| party.fixed_ref == fixed_ref)
+    }
+
+    pub fn build(mut self) -> OfacDocument {
+        self.document.build_indexes();
+        self.document.is_loaded = true;
+        self.document
+    }
+
+    /// Id of the given country, registered in the area codes when needed
+    pub fn country_id(&mut self, name: &str) -> i32 {
+        let name = name.trim();
+        if let Some(area) = self.document.references.area_codes.iter().find(|area| area.name.eq_ignore_ascii_case(name)) {
+            return area.id;
+        }
+        let id = synthetic_id(name);
+        self.document.references.area_codes.push(AreaCode { id, name: name.to_owned() });
+        id
+    }
+
+    /// Id of the given DetailReference, registered in the references when needed
+    pub fn detail_reference_id(&mut self, value: &str) -> i32 {
+        let value = value.trim();
+        let references = &mut self.document.references.detail_references.detail_references;
+        if let Some(reference) = references.iter().find(|reference| reference.value.eq_ignore_ascii_case(value)) {
+            return reference.id;
+        }
+        let id = synthetic_id(value);
+        references.push(DetailReference { id, value: value.to_owned() });
+        id
+    }
+
+    /// Id of the given IDRegDocType, Ofac ids are used for known types
+    pub fn document_type_id(&mut self, value: &str) -> i32 {
+        let value = value.trim();
+        let types = &mut self.document.references.reg_doc_types.reg_doc_types;
+        if let Some(doc_type) = types.iter().find(|doc_type| doc_type.value.eq_ignore_ascii_case(value)) {
+            return doc_type.id;
+        }
+        let id = match DocumentKind::from_name(value) {
+            Some(kind) => kind.id(),
+            None => synthetic_id(value),
+        };
+        types.push(IDRegDocType { id, value: value.to_owned() });
+        id
+    }
+
+    /// Id of the script a name is written in
+    pub fn script_id(&mut self, name: &str) -> i32 {
+        let (code, value) = detect_script(name);
+        if code == "Latn" {
+            return LATIN_SCRIPT;
+        }
+        let scripts = &mut self.document.references.script_values.scripts;
+        if let Some(script) = scripts.iter().find(|script| script.code == code) {
+            return script.id;
+        }
+        let id = synthetic_id(code);
+        scripts.push(Script {
+            id,
+            code: code.to_owned(),
+            value: value.to_owned(),
+        });
+        id
+    }
+
+    fn register_feature_type(&mut self, feature_type: i32) {
+        let types = &mut self.document.references.feature_types.types;
+        if types.iter().any(|t| t.id == feature_type) {
+            return;
+        }
+        if let Some(name) = FeatureKind::from_id(feature_type).and_then(|kind| kind.name()) {
+            types.push(FeatureType { id: feature_type, value: name.to_owned() });
+        }
+    }
+
+    fn location(&mut self, id: i32, address: &Address) -> i32 {
+        let mut parts = Vec::new();
+        let mut push = |kind: LocPartKind, value: &Option<String>| {
+            if let Some(value) = value.as_ref().map(|v| v.trim()).filter(|v| !v.is_empty()) {
+                parts.push(LocationPart {
+                    id: kind.id(),
+                    values: vec![LocationPartValue {
+                        primary: true,
+                        script_id: None,
+                        value: value.to_owned(),
+                    }],
+                });
+            }
+        };
+        push(LocPartKind::Region, &address.region);
+        for (kind, line) in [LocPartKind::Address1, LocPartKind::Address2, LocPartKind::Address3].into_iter().zip(address.address.iter()) {
+            push(kind, &Some(line.to_owned()));
+        }
+        push(LocPartKind::City, &address.city);
+        push(LocPartKind::StateProvince, &address.state);
+        push(LocPartKind::PostalCode, &address.postal_code);
+        let location_country = address.country.as_ref().map(|country| LocationCountry { id: self.country_id(country) });
+        self.document.locations.locations.push(Location {
+            id,
+            location_area_code: None,
+            location_country,
+            location_parts: if parts.is_empty() { None } else { Some(parts) },
+        });
+        id
+    }
+}
+
+/// Builds a DistinctParty and its SanctionsEntry
+///
+/// Items are given the id published in the list, or `None` when the list gives them no id, see [`ItemIds`]
+pub struct PartyBuilder<'a> {
+    builder: &'a mut OfacDocumentBuilder,
+    party: DistinctParty,
+    entry: SanctionsEntry,
+    /// Number of items added to the party
+    items: usize,
+}
+
+impl<'a> PartyBuilder<'a> {
+    pub fn fixed_ref(&self) -> i32 {
+        self.party.fixed_ref
+    }
+
+    /// Unique id of the next item of the party
+    fn item_id(&mut self, id: Option<i32>, range: Range<i32>) -> i32 {
+        self.items += 1;
+        let key = format!("{}/{}", self.party.fixed_ref, self.items);
+        self.builder.ids.allocate(id, &key, range)
+    }
+
+    pub fn comment(&mut self, comment: &str) {
+        let comment = comment.trim();
+        if comment.is_empty() {
+            return;
+        }
+        match &mut self.party.comment {
+            Some(existing) => {
+                existing.push(' ');
+                existing.push_str(comment);
+            }
+            None => self.party.comment = Some(comment.to_owned()),
+        }
+    }
+
+    /// Add a name, the primary latin name becomes the NAME of the sdn and the others its aliases
+    pub fn name<I: Into<Option<i32>>>(&mut self, id: I, primary: bool, low_quality: bool, parts: &[(NamePart, &str)]) {
+        let parts: Vec<(NamePart, &str)> = parts.iter().map(|(part, value)| (*part, value.trim())).filter(|(_, value)| !value.is_empty()).collect();
+        if parts.is_empty() {
+            return;
+        }
+        let id = self.item_id(id.into(), DERIVED_ITEM_IDS);
+        let script_id = self.builder.script_id(&parts.iter().map(|(_, value)| *value).collect::<Vec<_>>().join(" "));
+        let groups = &mut self.party.profile.identity.name_part_groups.master_name_part_group;
+        let mut documented_parts = Vec::new();
+        for (part, value) in parts {
+            if !groups.iter().any(|group| group.name_part_group.id == part as i32) {
+                groups.push(MasterNamePartGroup {
+                    name_part_group: NamePartGroup {
+                        id: part as i32,
+                        name_part_type_id: part as i32,
+                    },
+                });
+            }
+            documented_parts.push(DocumentedNamePart {
+                name_part: NamePartValue {
+                    name: Some(value.to_owned()),
+                    script_id,
+                    name_part_group_id: part as i32,
+                },
+            });
+        }
+        self.party.profile.identity.alias.push(Alias {
+            alias_type: if primary { 1403 } else { 1400 },
+            primary,
+            documented_name: vec![DocumentedName {
+                parts: documented_parts,
+                doc_name_status: 1,
+                id,
+            }],
+            quality: low_quality,
+        });
+    }
+
+    pub fn has_primary_name(&self) -> bool {
+        self.party.profile.identity.alias.iter().any(|alias| alias.primary)
+    }
+
+    /// Add a free text feature (e.g. Place of Birth, Website)
+    pub fn feature_value<I: Into<Option<i32>>>(&mut self, id: I, feature_type: i32, value: &str) {
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        self.feature(
+            id.into(),
+            feature_type,
+            Some(VersionDetail {
+                detail_type_id: None,
+                detail_reference_id: None,
+                value: Some(value.to_owned()),
+            }),
+            None,
+            None,
+        );
+    }
+
+    /// Add a feature whose value is a DetailReference (e.g. Vessel Type)
+    pub fn feature_reference<I: Into<Option<i32>>>(&mut self, id: I, feature_type: i32, reference: &str) {
+        if reference.trim().is_empty() {
+            return;
+        }
+        let reference_id = self.builder.detail_reference_id(reference);
+        self.feature(
+            id.into(),
+            feature_type,
+            Some(VersionDetail {
+                detail_type_id: None,
+                detail_reference_id: Some(reference_id),
+                value: None,
+            }),
+            None,
+            None,
+        );
+    }
+
+    pub fn gender<I: Into<Option<i32>>>(&mut self, id: I, male: bool) {
+        self.feature(
+            id.into(),
+            FeatureKind::Gender.id(),
+            Some(VersionDetail {
+                detail_type_id: Some(1431),
+                detail_reference_id: Some(if male { GenderKind::Male.id() } else { GenderKind::Female.id() }),
+                value: None,
+            }),
+            None,
+            None,
+        );
+    }
+
+    /// Add a dated feature (e.g. Birthdate)
+    pub fn feature_date<I: Into<Option<i32>>>(&mut self, id: I, feature_type: i32, period: DatePeriod) {
+        self.feature(id.into(), feature_type, None, None, Some(period));
+    }
+
+    /// Add a feature located at `address` (e.g. Location, Nationality Country)
+    ///
+    /// Nationalities and citizenships are read from the primary location part, so the country is also given as the region part
+    pub fn feature_location<I: Into<Option<i32>>>(&mut self, id: I, feature_type: i32, address: &Address) {
+        if address.is_empty() {
+            return;
+        }
+        let range = if feature_type == FeatureKind::Location.id() { DERIVED_ADDRESS_IDS } else { DERIVED_ITEM_IDS };
+        let id = self.item_id(id.into(), range);
+        let mut address = address.clone();
+        if [10, 11, 365].contains(&feature_type) && address.region.is_none() {
+            address.region = address.country.clone();
+        }
+        let location_id = self.builder.location(id, &address);
+        self.add_feature(id, feature_type, None, Some(VersionLocation { id: location_id }), None);
+    }
+
+    pub fn has_feature(&self, feature_type: i32) -> bool {
+        self.party.profile.feature.iter().flatten().any(|feature| feature.feature_type == feature_type)
+    }
+
+    fn feature(&mut self, id: Option<i32>, feature_type: i32, detail: Option<VersionDetail>, location: Option<VersionLocation>, date_period: Option<DatePeriod>) {
+        let id = self.item_id(id, DERIVED_ITEM_IDS);
+        self.add_feature(id, feature_type, detail, location, date_period);
+    }
+
+    fn add_feature(&mut self, id: i32, feature_type: i32, detail: Option<VersionDetail>, location: Option<VersionLocation>, date_period: Option<DatePeriod>) {
+        self.builder.register_feature_type(feature_type);
+        self.party.profile.feature.get_or_insert_with(Vec::new).push(Feature {
+            id,
+            feature_type,
+            version: FeatureVersion {
+                id,
+                reliability_id: 1,
+                comment: None,
+                detail,
+                location,
+                date_period,
+            },
+        });
+    }
+
+    /// Add an identification document
+    pub fn document<I: Into<Option<i32>>>(&mut self, id: I, doc_type: &str, number: &str, issued_by: Option<&str>, issued: Option<NaiveDate>, expiration: Option<NaiveDate>) {
+        let id = self.item_id(id.into(), DERIVED_ITEM_IDS);
+        let type_id = self.builder.document_type_id(doc_type);
+        let issued_by = issued_by.map(|v| v.trim()).filter(|v| !v.is_empty()).map(|country| self.builder.country_id(country));
+        let mut dates = Vec::new();
+        if let Some(issued) = issued {
+            dates.push(DocumentDate {
+                type_id: DocumentDateKind::Issue.id(),
+                period: date_period_day(issued, false),
+            });
+        }
+        if let Some(expiration) = expiration {
+            dates.push(DocumentDate {
+                type_id: DocumentDateKind::Expiration.id(),
+                period: date_period_day(expiration, false),
+            });
+        }
+        let number = number.trim();
+        self.builder.document.documents.documents.push(Document {
+            id,
+            type_id,
+            identity_id: self.party.profile.identity.id,
+            issued_by,
+            comment: None,
+            registration_number: if number.is_empty() { None } else { Some(number.to_owned()) },
+            reference: None,
+            dates: if dates.is_empty() { None } else { Some(dates) },
+        });
+    }
+
+    /// Add a sanctions program (e.g. SDGT)
+    pub fn program<I: Into<Option<i32>>>(&mut self, id: I, program: &str) {
+        let program = program.trim();
+        if program.is_empty() || self.entry.measures.iter().any(|measure| measure.program.as_deref() == Some(program)) {
+            return;
+        }
+        let id = self.item_id(id.into(), DERIVED_ITEM_IDS);
+        self.entry.measures.push(SanctionsMeasure {
+            id,
+            sanctions_type_id: PROGRAM_SANCTIONS_TYPE,
+            program: Some(program.to_owned()),
+        });
+    }
+
+    /// Date the party has been added to the list
+    pub fn listed_on(&mut self, date: NaiveDate) {
+        let date = Date {
+            year: date.year().to_string(),
+            month: date.month().to_string(),
+            day: date.day().to_string(),
+        };
+        match self.entry.events.first_mut() {
+            Some(event) => event.date = date,
+            None => self.entry.events.push(EntryEvent {
+                id: self.entry.id,
+                event_type_id: EntryEventKind::Created.id(),
+                legal_basis_id: None,
+                date,
+            }),
+        }
+    }
+
+    /// Add the party to the document, the date of issue is used when no listing date has been given
+    pub fn finish(mut self) {
+        if self.entry.events.is_empty() {
+            let date_of_issue = &self.builder.document.references.date_of_issue;
+            self.entry.events.push(EntryEvent {
+                id: self.entry.id,
+                event_type_id: EntryEventKind::Created.id(),
+                legal_basis_id: None,
+                date: Date {
+                    year: date_of_issue.year.to_owned(),
+                    month: date_of_issue.month.to_owned(),
+                    day: date_of_issue.day.to_owned(),
+                },
+            });
+        }
+        self.builder.document.distinct_parties.parties.push(self.party);
+        self.builder.document.sanction_entries.entries.push(self.entry);
+    }
+}
+
+fn date(year: i32, month: u32, day: u32) -> Date {
+    Date {
+        year: year.to_string(),
+        month: month.to_string(),
+        day: day.to_string(),
+    }
+}
+
+fn last_day_of_month(year: i32, month: u32) -> u32 {
+    let next = if month == 12 { NaiveDate::from_ymd(year + 1, 1, 1) } else { NaiveDate::from_ymd(year, month + 1, 1) };
+    next.pred().day()
+}
+
+fn range(from: Date, to: Date, approximate: bool) -> DatePeriodRange {
+    DatePeriodRange { from, to, approximate }
+}
+
+/// A single day
+pub fn date_period_day(day: NaiveDate, approximate: bool) -> DatePeriod {
+    let value = date(day.year(), day.month(), day.day());
+    DatePeriod {
+        start: Some(range(value.clone(), value.clone(), approximate)),
+        end: Some(range(value.clone(), value, approximate)),
+    }
+}
+
+/// A whole month
+pub fn date_period_month(year: i32, month: u32, approximate: bool) -> DatePeriod {
+    let first = date(year, month, 1);
+    let last = date(year, month, last_day_of_month(year, month));
+    DatePeriod {
+        start: Some(range(first.clone(), first, approximate)),
+        end: Some(range(last.clone(), last, approximate)),
+    }
+}
+
+/// A whole year
+pub fn date_period_year(year: i32, approximate: bool) -> DatePeriod {
+    let first = date(year, 1, 1);
+    let last = date(year, 12, 31);
+    DatePeriod {
+        start: Some(range(first.clone(), first, approximate)),
+        end: Some(range(last.clone(), last, approximate)),
+    }
+}
+
+/// From the start of year `from` to the end of year `to`
+pub fn date_period_years(from: i32, to: i32) -> DatePeriod {
+    DatePeriod {
+        start: Some(range(date(from, 1, 1), date(from, 12, 31), false)),
+        end: Some(range(date(to, 1, 1), date(to, 12, 31), false)),
+    }
+}
+
+/// From day `from` to day `to`
+pub fn date_period_days(from: NaiveDate, to: NaiveDate) -> DatePeriod {
+    let from = date(from.year(), from.month(), from.day());
+    let to = date(to.year(), to.month(), to.day());
+    DatePeriod {
+        start: Some(range(from.clone(), from, false)),
+        end: Some(range(to.clone(), to, false)),
+    }
+}
+
+fn fnv1a(value: &str) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+    for byte in value.bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+/// Stable id computed from a reference name (FNV-1a)
+pub(crate) fn synthetic_id(name: &str) -> i32 {
+    (SYNTHETIC_REFERENCE_ID + fnv1a(&name.trim().to_uppercase()) % SYNTHETIC_REFERENCE_ID) as i32
+}
+
+/// Script (ISO 15924 code and name) of the first letter of `value` which is not latin
+pub(crate) fn detect_script(value: &str) -> (&'static str, &'static str) {
+    for c in value.chars().filter(|c| c.is_alphabetic()) {
+        let script = match c as u32 {
+            0x0000..=0x024F | 0x1E00..=0x1EFF => continue,
+            0x0370..=0x03FF => ("Grek", "Greek"),
+            0x0400..=0x052F => ("Cyrl", "Cyrillic"),
+            0x0530..=0x058F => ("Armn", "Armenian"),
+            0x0590..=0x05FF => ("Hebr", "Hebrew"),
+            0x0600..=0x06FF | 0x0750..=0x077F | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => ("Arab", "Arabic"),
+            0x10A0..=0x10FF => ("Geor", "Georgian"),
+            0x1100..=0x11FF | 0xAC00..=0xD7AF => ("Hang", "Korean"),
+            0x3040..=0x30FF => ("Jpan", "Japanese"),
+            0x4E00..=0x9FFF | 0x3400..=0x4DBF => ("Hani", "Chinese"),
+            _ => ("Zyyy", "Other"),
+        };
+        return script;
+    }
+    ("Latn", "Latin")
+}
+
+#[cfg(test)]
+mod builder {
+    use super::*;
+    use crate::db::entity::dob;
+    use crate::db::entity::sdn::{self, DocumentEntity};
+
+    #[test]
+    fn build_a_party() {
+        let mut builder = OfacDocumentBuilder::new("", DocumentType::OFAC);
+        builder.date_of_issue(NaiveDate::from_ymd(2022, 11, 8));
+        let mut party = builder.party(36, PartyKind::Individual);
+        party.name(1, true, false, &[(NamePart::FirstName, "John"), (NamePart::LastName, "Doe")]);
+        party.name(2, false, true, &[(NamePart::LastName, "Джон Доу")]);
+        party.feature_date(3, 8, date_period_year(1966, true));
+        party.feature_location(4, 10, &Address { country: Some("France".to_owned()), ..Default::default() });
+        party.gender(5, true);
+        party.program(6, "SDGT");
+        party.finish();
+        let document = builder.build();
+
+        let (sdn, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[0], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!(36, sdn.fixed_ref);
+        assert_eq!("INDIVIDUAL", sdn.sdn_type);
+        assert_eq!(Some("MALE".to_owned()), sdn.gender);
+        assert_eq!(Some(NaiveDate::from_ymd(2022, 11, 8)), sdn.sanction_date);
+        assert_eq!(2, relations.names.len());
+        assert_eq!("NAME", relations.names[0].name_type);
+        assert_eq!(Some("Doe".to_owned()), relations.names[0].last_name);
+        assert_eq!("ALIAS", relations.names[1].name_type);
+        assert_ne!(LATIN_SCRIPT, relations.names[1].script);
+        assert_eq!(
+            vec![dob::Model {
+                id: 3,
+                dob: "CIRCA 1966".to_owned(),
+                earliest: Some(NaiveDate::from_ymd(1966, 1, 1)),
+                latest: Some(NaiveDate::from_ymd(1966, 12, 31)),
+                approximate: true,
+                reliability: 1,
+                comment: None,
+                topmaj: "N".to_owned()
+            }],
+            relations.dobs
+        );
+        assert_eq!(Some(document.references.area_codes[0].id), relations.nationalities[0].nationality);
+        assert_eq!("SDGT", relations.programs[0].program);
+    }
+
+    #[test]
+    fn date_periods_have_ofac_shapes() {
+        let month = date_period_month(2020, 2, false);
+        assert_eq!("29", month.end.unwrap().from.day);
+        let day = date_period_day(NaiveDate::from_ymd(1980, 1, 5), false);
+        assert_eq!(day.start, day.end);
+        assert_eq!("1", day.start.unwrap().from.month);
+    }
+
+    #[test]
+    fn synthetic_ids_are_stable() {
+        assert_eq!(synthetic_id("France"), synthetic_id(" FRANCE "));
+        assert_ne!(synthetic_id("France"), synthetic_id("Germany"));
+        assert!(synthetic_id("France") >= SYNTHETIC_REFERENCE_ID as i32);
+        let mut builder = OfacDocumentBuilder::new("", DocumentType::OFAC);
+        assert_eq!(1571, builder.document_type_id("passport"));
+    }
+}
@@ -0,0 +1,214 @@
+//! Validation and normalization of the identifiers published in features and documents
+//!
+//! Identifiers are published as free text (e.g. `IMO 9074729`, `HAVI GB 2L`), the normalized form only keeps
+//! the characters of the identifier in uppercase and is only set when the identifier is valid.
+
+use std::fmt;
+
+use super::known::{DocumentKind, FeatureKind, KnownValue};
+use super::OfacDocumentReferences;
+
+/// IDRegDocType of the Legal Entity Identifiers
+const LEI_DOCUMENT: &str = "Legal Entity Number";
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, PartialOrd, Ord)]
+pub enum IdentifierKind {
+    /// IMO ship identification number, 7 digits with a check digit
+    Imo,
+    /// ISO 6166, 12 characters with a Luhn check digit
+    Isin,
+    /// ISO 17442, 20 characters with ISO 7064 MOD 97-10 check digits
+    Lei,
+    /// ISO 9362 SWIFT/BIC, 8 or 11 characters
+    Bic,
+    /// Russian bank identification code, 9 digits starting with `04`
+    Bik,
+    /// Maritime Mobile Service Identity, 9 digits
+    Mmsi,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum IdentifierError {
+    Length { expected: &'static str, found: usize },
+    Format(&'static str),
+    CheckDigit,
+}
+
+impl fmt::Display for IdentifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentifierError::Length { expected, found } => write!(f, "{} characters expected, found {}", expected, found),
+            IdentifierError::Format(expected) => write!(f, "{} expected", expected),
+            IdentifierError::CheckDigit => write!(f, "invalid check digit"),
+        }
+    }
+}
+
+impl fmt::Display for IdentifierKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IdentifierKind::Imo => "IMO",
+            IdentifierKind::Isin => "ISIN",
+            IdentifierKind::Lei => "LEI",
+            IdentifierKind::Bic => "BIC",
+            IdentifierKind::Bik => "BIK",
+            IdentifierKind::Mmsi => "MMSI",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl IdentifierKind {
+    /// Identifier published in a feature of type `feature_type`, `None` for the features without identifier
+    pub fn of_feature(feature_type: i32, references: &OfacDocumentReferences) -> Option<IdentifierKind> {
+        match references.resolve::<FeatureKind>(feature_type)? {
+            FeatureKind::SwiftBic => Some(IdentifierKind::Bic),
+            FeatureKind::Bik => Some(IdentifierKind::Bik),
+            FeatureKind::Isin => Some(IdentifierKind::Isin),
+            _ => None,
+        }
+    }
+
+    /// Identifier published as the registration number of a document of type `doc_type`
+    pub fn of_document(doc_type: i32, references: &OfacDocumentReferences) -> Option<IdentifierKind> {
+        match references.resolve::<DocumentKind>(doc_type) {
+            Some(DocumentKind::VesselRegistration) => Some(IdentifierKind::Imo),
+            Some(DocumentKind::Mmsi) => Some(IdentifierKind::Mmsi),
+            _ => references.value_name(DocumentKind::SET, doc_type).filter(|name| name.trim().eq_ignore_ascii_case(LEI_DOCUMENT)).map(|_| IdentifierKind::Lei),
+        }
+    }
+
+    /// Identifier in uppercase without separators, the `IMO` prefix of the IMO numbers is removed
+    pub fn normalize(self, raw: &str) -> String {
+        let normalized: String = raw.chars().filter(char::is_ascii_alphanumeric).map(|c| c.to_ascii_uppercase()).collect();
+        match self {
+            IdentifierKind::Imo => normalized.strip_prefix("IMO").map(str::to_owned).unwrap_or(normalized),
+            _ => normalized,
+        }
+    }
+
+    /// Normalized identifier if `raw` is valid
+    pub fn validate(self, raw: &str) -> Result<String, IdentifierError> {
+        let normalized = self.normalize(raw);
+        let chars: Vec<char> = normalized.chars().collect();
+        match self {
+            IdentifierKind::Imo => {
+                check_length(&chars, &[7], "7")?;
+                check_digits(&chars)?;
+                let digits: Vec<u32> = chars.iter().filter_map(|c| c.to_digit(10)).collect();
+                let sum: u32 = digits[..6].iter().enumerate().map(|(i, digit)| digit * (7 - i as u32)).sum();
+                if sum % 10 != digits[6] {
+                    return Err(IdentifierError::CheckDigit);
+                }
+            }
+            IdentifierKind::Isin => {
+                check_length(&chars, &[12], "12")?;
+                if !chars[..2].iter().all(char::is_ascii_uppercase) || !chars[11].is_ascii_digit() {
+                    return Err(IdentifierError::Format("a country code and a check digit"));
+                }
+                if !luhn(&to_digits(&chars)) {
+                    return Err(IdentifierError::CheckDigit);
+                }
+            }
+            IdentifierKind::Lei => {
+                check_length(&chars, &[20], "20")?;
+                if !chars[18..].iter().all(char::is_ascii_digit) {
+                    return Err(IdentifierError::Format("2 check digits"));
+                }
+                if mod97(&to_digits(&chars)) != 1 {
+                    return Err(IdentifierError::CheckDigit);
+                }
+            }
+            IdentifierKind::Bic => {
+                check_length(&chars, &[8, 11], "8 or 11")?;
+                if !chars[..6].iter().all(char::is_ascii_uppercase) {
+                    return Err(IdentifierError::Format("a bank code and a country code of letters"));
+                }
+            }
+            IdentifierKind::Bik => {
+                check_length(&chars, &[9], "9")?;
+                check_digits(&chars)?;
+                if !normalized.starts_with("04") {
+                    return Err(IdentifierError::Format("a Russian code starting with 04"));
+                }
+            }
+            IdentifierKind::Mmsi => {
+                check_length(&chars, &[9], "9")?;
+                check_digits(&chars)?;
+            }
+        }
+        Ok(normalized)
+    }
+}
+
+fn check_length(chars: &[char], lengths: &[usize], expected: &'static str) -> Result<(), IdentifierError> {
+    if lengths.contains(&chars.len()) {
+        return Ok(());
+    }
+    Err(IdentifierError::Length { expected, found: chars.len() })
+}
+
+fn check_digits(chars: &[char]) -> Result<(), IdentifierError> {
+    if chars.iter().all(char::is_ascii_digit) {
+        return Ok(());
+    }
+    Err(IdentifierError::Format("digits only"))
+}
+
+/// Letters are replaced by their value in base 36 (A = 10)
+fn to_digits(chars: &[char]) -> Vec<u32> {
+    let mut digits = Vec::with_capacity(chars.len() * 2);
+    for value in chars.iter().filter_map(|c| c.to_digit(36)) {
+        if value >= 10 {
+            digits.push(value / 10);
+        }
+        digits.push(value % 10);
+    }
+    digits
+}
+
+fn luhn(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| match i % 2 {
+            0 => *digit,
+            _ if digit * 2 > 9 => digit * 2 - 9,
+            _ => digit * 2,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+fn mod97(digits: &[u32]) -> u32 {
+    digits.iter().fold(0, |remainder, digit| (remainder * 10 + digit) % 97)
+}
+
+#[cfg(test)]
+mod identifier {
+    use super::*;
+
+    #[test]
+    fn validate_check_digits() {
+        assert_eq!(Ok("9074729".to_owned()), IdentifierKind::Imo.validate("IMO 9074729"));
+        assert_eq!(Err(IdentifierError::CheckDigit), IdentifierKind::Imo.validate("IMO 9074728"));
+        assert_eq!(Ok("US0378331005".to_owned()), IdentifierKind::Isin.validate("us 0378331005"));
+        assert_eq!(Ok("RU000A0JX0J2".to_owned()), IdentifierKind::Isin.validate("RU000A0JX0J2"));
+        assert_eq!(Err(IdentifierError::CheckDigit), IdentifierKind::Isin.validate("US0378331006"));
+        assert_eq!(Ok("5493001KJTIIGC8Y1R12".to_owned()), IdentifierKind::Lei.validate("5493001KJTIIGC8Y1R12"));
+        assert_eq!(Err(IdentifierError::CheckDigit), IdentifierKind::Lei.validate("5493001KJTIIGC8Y1R13"));
+    }
+
+    #[test]
+    fn validate_structures() {
+        assert_eq!(Ok("HAVIGB2L".to_owned()), IdentifierKind::Bic.validate("HAVI GB 2L"));
+        assert_eq!(Ok("SABRRUMMXXX".to_owned()), IdentifierKind::Bic.validate("SABRRUMMXXX"));
+        assert_eq!(Err(IdentifierError::Length { expected: "8 or 11", found: 6 }), IdentifierKind::Bic.validate("HAVIGB"));
+        assert_eq!(Err(IdentifierError::Format("a bank code and a country code of letters")), IdentifierKind::Bic.validate("HAV1GB2L"));
+        assert_eq!(Ok("044525225".to_owned()), IdentifierKind::Bik.validate("044525225"));
+        assert_eq!(Err(IdentifierError::Format("a Russian code starting with 04")), IdentifierKind::Bik.validate("144525225"));
+        assert_eq!(Ok("273456789".to_owned()), IdentifierKind::Mmsi.validate("273 456 789"));
+        assert_eq!(Err(IdentifierError::Format("digits only")), IdentifierKind::Mmsi.validate("27345678A"));
+    }
+}
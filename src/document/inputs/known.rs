@@ -0,0 +1,397 @@
+//! Reference values the agent relies on
+//!
+//! Ofac ids are only the values published when the importer was written, a value is looked up by its
+//! name in the ReferenceValueSets of the document (or in the ref tables) and the id is only used when
+//! the name is not found.
+
+/// A value of a ReferenceValueSet known by the agent
+pub trait KnownValue: Sized + Copy + Eq + 'static {
+    /// Name of the ReferenceValueSet, as in the advanced xml (without the `Values` suffix)
+    const SET: &'static str;
+
+    /// Every value with the name published by Ofac, values without a name are only matched by id
+    const VALUES: &'static [(Self, Option<&'static str>)];
+
+    /// Other ids published for a value
+    const ALIASES: &'static [(i32, Self)] = &[];
+
+    /// Id published by Ofac
+    fn id(self) -> i32;
+
+    fn name(self) -> Option<&'static str> {
+        Self::VALUES.iter().find(|(value, _)| *value == self).and_then(|(_, name)| *name)
+    }
+
+    fn from_id(id: i32) -> Option<Self> {
+        match Self::VALUES.iter().find(|(value, _)| value.id() == id) {
+            Some((value, _)) => Some(*value),
+            None => Self::ALIASES.iter().find(|(alias, _)| *alias == id).map(|(_, value)| *value),
+        }
+    }
+
+    /// Names are compared case insensitively, the ref tables store them in uppercase
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::VALUES.iter().find(|(_, known)| known.is_some_and(|known| known.eq_ignore_ascii_case(name))).map(|(value, _)| *value)
+    }
+}
+
+/// FeatureTypes mapped by the importer
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum FeatureKind {
+    VesselCallSign = 1,
+    VesselType = 2,
+    VesselFlag = 3,
+    VesselOwner = 4,
+    VesselTonnage = 5,
+    VesselGrossRegisteredTonnage = 6,
+    Birthdate = 8,
+    PlaceOfBirth = 9,
+    NationalityCountry = 10,
+    CitizenshipCountry = 11,
+    SwiftBic = 13,
+    Website = 14,
+    EmailAddress = 21,
+    FormerVesselFlag = 24,
+    Location = 25,
+    Title = 26,
+    AircraftConstructionNumber = 44,
+    AircraftManufactureDate = 45,
+    AircraftTransponderCode = 46,
+    AircraftModel = 47,
+    AircraftOperator = 48,
+    PreviousAircraftTailNumber = 49,
+    AircraftManufacturerSerialNumber = 50,
+    AircraftTailNumber = 64,
+    IfcaDetermination = 104,
+    AdditionalSanctionsInformation = 125,
+    Bik = 164,
+    Eo13662Directive = 204,
+    Gender = 224,
+    Locode = 264,
+    MicexCode = 304,
+    DigitalCurrencyXbt = 344,
+    DigitalCurrencyEth = 345,
+    DunsNumber = 364,
+    NationalityOfRegistration = 365,
+    RegistrationCountry = 404,
+    OtherVesselFlag = 424,
+    OtherVesselCallSign = 425,
+    DigitalCurrencyXmr = 444,
+    SecondarySanctionsRisk = 504,
+    PhoneNumber = 524,
+    Caatsa235 = 525,
+    OtherVesselType = 526,
+    DigitalCurrencyLtc = 566,
+    Eo13846Information = 586,
+    TransactionsProhibited = 626,
+    OrganizationEstablishedDate = 646,
+    OrganizationType = 647,
+    DigitalCurrencyZec = 686,
+    DigitalCurrencyDash = 687,
+    DigitalCurrencyBtg = 688,
+    DigitalCurrencyEtc = 689,
+    DigitalCurrencyBsv = 706,
+    DigitalCurrencyBch = 726,
+    DigitalCurrencyXvg = 746,
+    EquityTicker = 766,
+    IssuerName = 767,
+    Isin = 806,
+    TargetType = 826,
+    PeesaInformation = 827,
+    CmicEffectiveDate = 867,
+    CmicSalesDate = 868,
+    CmicListingDate = 869,
+    DigitalCurrencyUsdt = 887,
+    DigitalCurrencyXrp = 907,
+    Eo14024Directive = 947,
+}
+
+impl KnownValue for FeatureKind {
+    const SET: &'static str = "FeatureType";
+
+    const VALUES: &'static [(Self, Option<&'static str>)] = &[
+        (FeatureKind::VesselCallSign, Some("Vessel Call Sign")),
+        (FeatureKind::VesselType, Some("Vessel Type")),
+        (FeatureKind::VesselFlag, Some("Vessel Flag")),
+        (FeatureKind::VesselOwner, Some("Vessel Owner")),
+        (FeatureKind::VesselTonnage, Some("Vessel Tonnage")),
+        (FeatureKind::VesselGrossRegisteredTonnage, Some("Vessel Gross Registered Tonnage")),
+        (FeatureKind::Birthdate, Some("Birthdate")),
+        (FeatureKind::PlaceOfBirth, Some("Place of Birth")),
+        (FeatureKind::NationalityCountry, Some("Nationality Country")),
+        (FeatureKind::CitizenshipCountry, Some("Citizenship Country")),
+        (FeatureKind::SwiftBic, Some("SWIFT/BIC")),
+        (FeatureKind::Website, Some("Website")),
+        (FeatureKind::EmailAddress, Some("Email Address")),
+        (FeatureKind::FormerVesselFlag, Some("Former Vessel Flag")),
+        (FeatureKind::Location, Some("Location")),
+        (FeatureKind::Title, Some("Title")),
+        (FeatureKind::AircraftConstructionNumber, Some("Aircraft Construction Number (also called L/N or S/N or F/N)")),
+        (FeatureKind::AircraftManufactureDate, Some("Aircraft Manufacture Date")),
+        (FeatureKind::AircraftTransponderCode, Some("Aircraft Mode S Transponder Code")),
+        (FeatureKind::AircraftModel, Some("Aircraft Model")),
+        (FeatureKind::AircraftOperator, Some("Aircraft Operator")),
+        (FeatureKind::PreviousAircraftTailNumber, Some("Previous Aircraft Tail Number")),
+        (FeatureKind::AircraftManufacturerSerialNumber, Some("Aircraft Manufacturer's Serial Number (MSN)")),
+        (FeatureKind::AircraftTailNumber, Some("Aircraft Tail Number")),
+        (FeatureKind::IfcaDetermination, Some("IFCA Determination -")),
+        (FeatureKind::AdditionalSanctionsInformation, Some("Additional Sanctions Information -")),
+        (FeatureKind::Bik, Some("BIK (RU)")),
+        (FeatureKind::Eo13662Directive, Some("Executive Order 13662 Directive Determination -")),
+        (FeatureKind::Gender, Some("Gender")),
+        (FeatureKind::Locode, Some("LOCODE")),
+        (FeatureKind::MicexCode, Some("MICEX Code")),
+        (FeatureKind::DigitalCurrencyXbt, Some("Digital Currency Address - XBT")),
+        (FeatureKind::DigitalCurrencyEth, Some("Digital Currency Address - ETH")),
+        (FeatureKind::DunsNumber, Some("D-U-N-S Number")),
+        (FeatureKind::NationalityOfRegistration, Some("Nationality of Registration")),
+        (FeatureKind::RegistrationCountry, None),
+        (FeatureKind::OtherVesselFlag, Some("Other Vessel Flag")),
+        (FeatureKind::OtherVesselCallSign, Some("Other Vessel Call Sign")),
+        (FeatureKind::DigitalCurrencyXmr, Some("Digital Currency Address - XMR")),
+        (FeatureKind::SecondarySanctionsRisk, Some("Secondary sanctions risk:")),
+        (FeatureKind::PhoneNumber, Some("Phone Number")),
+        (FeatureKind::Caatsa235, Some("CAATSA Section 235 Information:")),
+        (FeatureKind::OtherVesselType, Some("Other Vessel Type")),
+        (FeatureKind::DigitalCurrencyLtc, Some("Digital Currency Address - LTC")),
+        (FeatureKind::Eo13846Information, Some("Executive Order 13846 information:")),
+        (FeatureKind::TransactionsProhibited, Some("Transactions Prohibited For Persons Owned or Controlled By U.S. Financial Institutions:")),
+        (FeatureKind::OrganizationEstablishedDate, Some("Organization Established Date")),
+        (FeatureKind::OrganizationType, Some("Organization Type:")),
+        (FeatureKind::DigitalCurrencyZec, Some("Digital Currency Address - ZEC")),
+        (FeatureKind::DigitalCurrencyDash, Some("Digital Currency Address - DASH")),
+        (FeatureKind::DigitalCurrencyBtg, Some("Digital Currency Address - BTG")),
+        (FeatureKind::DigitalCurrencyEtc, Some("Digital Currency Address - ETC")),
+        (FeatureKind::DigitalCurrencyBsv, Some("Digital Currency Address - BSV")),
+        (FeatureKind::DigitalCurrencyBch, Some("Digital Currency Address - BCH")),
+        (FeatureKind::DigitalCurrencyXvg, Some("Digital Currency Address - XVG")),
+        (FeatureKind::EquityTicker, Some("Equity Ticker")),
+        (FeatureKind::IssuerName, Some("Issuer Name")),
+        (FeatureKind::Isin, Some("ISIN")),
+        (FeatureKind::TargetType, Some("Target Type")),
+        (FeatureKind::PeesaInformation, Some("PEESA Information:")),
+        (FeatureKind::CmicEffectiveDate, Some("CMIC Effective Date")),
+        (FeatureKind::CmicSalesDate, Some("CMIC Sales Date")),
+        (FeatureKind::CmicListingDate, Some("CMIC Listing Date")),
+        (FeatureKind::DigitalCurrencyUsdt, Some("Digital Currency Address - USDT")),
+        (FeatureKind::DigitalCurrencyXrp, Some("Digital Currency Address - XRP")),
+        (FeatureKind::Eo14024Directive, Some("Executive Order 14024 Directive Information -")),
+    ];
+
+    const ALIASES: &'static [(i32, Self)] = &[(948, FeatureKind::Eo14024Directive)];
+
+    fn id(self) -> i32 {
+        self as i32
+    }
+}
+
+/// NamePartTypes of the Ofac advanced format
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum NamePart {
+    LastName = 1520,
+    FirstName = 1521,
+    MiddleName = 1522,
+    MaidenName = 1523,
+    AircraftName = 1524,
+    EntityName = 1525,
+    VesselName = 1526,
+    Nickname = 1528,
+    Patronymic = 91708,
+    Matronymic = 91709,
+}
+
+impl KnownValue for NamePart {
+    const SET: &'static str = "NamePartType";
+
+    const VALUES: &'static [(Self, Option<&'static str>)] = &[
+        (NamePart::LastName, Some("Last Name")),
+        (NamePart::FirstName, Some("First Name")),
+        (NamePart::MiddleName, Some("Middle Name")),
+        (NamePart::MaidenName, Some("Maiden Name")),
+        (NamePart::AircraftName, Some("Aircraft Name")),
+        (NamePart::EntityName, Some("Entity Name")),
+        (NamePart::VesselName, Some("Vessel Name")),
+        (NamePart::Nickname, Some("Nickname")),
+        (NamePart::Patronymic, Some("Patronymic")),
+        (NamePart::Matronymic, Some("Matronymic")),
+    ];
+
+    fn id(self) -> i32 {
+        self as i32
+    }
+}
+
+/// LocPartTypes making an address
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum LocPartKind {
+    Region = 1450,
+    Address1 = 1451,
+    Address2 = 1452,
+    Address3 = 1453,
+    City = 1454,
+    StateProvince = 1455,
+    PostalCode = 1456,
+}
+
+impl KnownValue for LocPartKind {
+    const SET: &'static str = "LocPartType";
+
+    const VALUES: &'static [(Self, Option<&'static str>)] = &[
+        (LocPartKind::Region, Some("Region")),
+        (LocPartKind::Address1, Some("Address1")),
+        (LocPartKind::Address2, Some("Address2")),
+        (LocPartKind::Address3, Some("Address3")),
+        (LocPartKind::City, Some("City")),
+        (LocPartKind::StateProvince, Some("State/Province")),
+        (LocPartKind::PostalCode, Some("Postal Code")),
+    ];
+
+    fn id(self) -> i32 {
+        self as i32
+    }
+}
+
+/// IDRegDocTypes the exporters rely on
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum DocumentKind {
+    Cedula = 1570,
+    Passport = 1571,
+    Nit = 1572,
+    NationalId = 1584,
+    /// Only document exported in the INF of an aircraft
+    Aircraft = 1623,
+    VesselRegistration = 1626,
+    Mmsi = 91264,
+}
+
+impl KnownValue for DocumentKind {
+    const SET: &'static str = "IDRegDocType";
+
+    const VALUES: &'static [(Self, Option<&'static str>)] = &[
+        (DocumentKind::Cedula, Some("Cedula No.")),
+        (DocumentKind::Passport, Some("Passport")),
+        (DocumentKind::Nit, Some("NIT #")),
+        (DocumentKind::NationalId, Some("National ID No.")),
+        (DocumentKind::Aircraft, None),
+        (DocumentKind::VesselRegistration, Some("Vessel Registration Identification")),
+        (DocumentKind::Mmsi, Some("MMSI")),
+    ];
+
+    fn id(self) -> i32 {
+        self as i32
+    }
+}
+
+/// IDRegDocDateTypes of a document
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum DocumentDateKind {
+    Issue = 1480,
+    Expiration = 1481,
+}
+
+impl KnownValue for DocumentDateKind {
+    const SET: &'static str = "IDRegDocDateType";
+
+    const VALUES: &'static [(Self, Option<&'static str>)] = &[(DocumentDateKind::Issue, Some("Issue Date")), (DocumentDateKind::Expiration, Some("Expiration Date"))];
+
+    fn id(self) -> i32 {
+        self as i32
+    }
+}
+
+/// DetailReferences of the Gender feature
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum GenderKind {
+    Male = 91526,
+    Female = 91527,
+}
+
+impl KnownValue for GenderKind {
+    const SET: &'static str = "DetailReference";
+
+    const VALUES: &'static [(Self, Option<&'static str>)] = &[(GenderKind::Male, Some("Male")), (GenderKind::Female, Some("Female"))];
+
+    fn id(self) -> i32 {
+        self as i32
+    }
+}
+
+/// EntryEventTypes of a SanctionsEntry
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum EntryEventKind {
+    Created = 1,
+    Amended = 2,
+}
+
+impl KnownValue for EntryEventKind {
+    const SET: &'static str = "EntryEventType";
+
+    const VALUES: &'static [(Self, Option<&'static str>)] = &[(EntryEventKind::Created, Some("Created")), (EntryEventKind::Amended, Some("Amended"))];
+
+    fn id(self) -> i32 {
+        self as i32
+    }
+}
+
+/// Reliability of a FeatureVersion
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ReliabilityKind {
+    Reliable = 1,
+    Unknown = 2,
+    Fabricated = 3,
+}
+
+impl KnownValue for ReliabilityKind {
+    const SET: &'static str = "Reliability";
+
+    const VALUES: &'static [(Self, Option<&'static str>)] = &[
+        (ReliabilityKind::Reliable, Some("Reliable")),
+        (ReliabilityKind::Unknown, Some("Unknown")),
+        (ReliabilityKind::Fabricated, Some("Fabricated")),
+    ];
+
+    fn id(self) -> i32 {
+        self as i32
+    }
+}
+
+#[cfg(test)]
+mod known {
+    use super::*;
+    use crate::document::inputs::models::referencevaluesets::{ReferenceValue, ReferenceValueSet};
+    use crate::document::OfacDocumentReferences;
+
+    #[test]
+    fn resolve_by_name() {
+        let references = OfacDocumentReferences {
+            value_sets: vec![ReferenceValueSet {
+                name: "NamePartType".to_owned(),
+                values: vec![ReferenceValue { id: 1520, value: "First Name".to_owned() }, ReferenceValue { id: 7, value: "Last Name".to_owned() }],
+            }],
+            ..Default::default()
+        };
+        assert_eq!(Some(NamePart::FirstName), references.resolve::<NamePart>(1520));
+        assert_eq!(Some(NamePart::LastName), references.resolve::<NamePart>(7));
+        assert_eq!(Some(NamePart::EntityName), references.resolve::<NamePart>(1525));
+        assert_eq!(Some(LocPartKind::City), references.resolve::<LocPartKind>(1454));
+        assert_eq!(None, references.resolve::<NamePart>(8));
+    }
+
+    #[test]
+    fn known_values_lookup() {
+        assert_eq!(Some(FeatureKind::Birthdate), FeatureKind::from_name("BIRTHDATE"));
+        assert_eq!(Some(FeatureKind::Eo14024Directive), FeatureKind::from_id(948));
+        assert_eq!(Some(FeatureKind::RegistrationCountry), FeatureKind::from_id(404));
+        assert_eq!(None, FeatureKind::from_name(""));
+        assert_eq!(Some("Passport"), DocumentKind::Passport.name());
+        assert_eq!(1456, LocPartKind::PostalCode.id());
+        assert_eq!(Some(ReliabilityKind::Fabricated), ReliabilityKind::from_name("FABRICATED"));
+        for (value, name) in FeatureKind::VALUES {
+            assert_eq!(Some(*value), FeatureKind::from_id(value.id()));
+            if let Some(name) = name {
+                assert_eq!(Some(*value), FeatureKind::from_name(name));
+            }
+        }
+    }
+}
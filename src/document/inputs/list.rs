@@ -0,0 +1,244 @@
+use std::error::Error;
+
+use chrono::NaiveDate;
+
+use super::reader::{self, read_document};
+use super::source::SourceReader;
+use super::validation::{self, ValidationReport};
+use super::OfacDocument;
+use crate::document::eu::EuSource;
+use crate::document::legacy::LegacyFormat;
+use crate::document::uk::UkSource;
+use crate::document::un::UnSource;
+
+/// A sanctions list the agent imports and exports
+///
+/// Everything that differs from one list to another (file naming, connection settings, parser and export
+/// prefixes) is given by its implementation, a new list only has to be added to [`SOURCES`]. Its documents are
+/// typed with [`super::DocumentType::of`].
+pub trait ListSource: Sync {
+    /// Name of the list on the command line (`-t`)
+    fn name(&self) -> &'static str;
+
+    /// Name of the list in the exported DSG, ORI and REF fields
+    fn designation(&self) -> &'static str;
+
+    /// Four letters prefix of the exported OIDs
+    fn oid_prefix(&self) -> &'static str;
+
+    /// Prefix of the main record OID written in the alternative records (US2 and SYN), the OID prefix by default
+    fn alternative_oid_prefix(&self) -> &'static str {
+        self.oid_prefix()
+    }
+
+    /// Stem of the published file, searched in the data folder
+    fn file_stem(&self) -> &'static str;
+
+    /// Environment variable holding the database url
+    fn database_url_var(&self) -> &'static str;
+
+    /// Environment variable holding the data folder
+    fn data_folder_var(&self) -> &'static str;
+
+    /// Required lists are always processed with `ALL`, the others only when their database is configured
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    /// Lists exported before the others with `ALL`, see [`export_order`]
+    fn is_exported_first(&self) -> bool {
+        false
+    }
+
+    /// Stems of the files of the list in a legacy format, the first one is the main file
+    fn legacy_file_stems(&self, _format: LegacyFormat) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn has_legacy_files(&self) -> bool {
+        [LegacyFormat::Xml, LegacyFormat::Csv].iter().any(|format| !self.legacy_file_stems(*format).is_empty())
+    }
+
+    /// Whether DistinctParties can be streamed to the importer, see [`OfacDocument::load_streamed`]
+    fn is_streamable(&self) -> bool {
+        false
+    }
+
+    /// Check the published file before it is read, lists without rules report nothing
+    fn validate(&self, _reader: SourceReader) -> ValidationReport {
+        ValidationReport::default()
+    }
+
+    /// Read the published file into the document
+    fn read(&self, reader: SourceReader, document: &mut OfacDocument) -> Result<(), Box<dyn Error>>;
+
+    /// Date of issue of the published file, the whole file is read unless the list gives its date at the start of the file
+    fn read_date_of_issue(&self, reader: SourceReader) -> Result<NaiveDate, Box<dyn Error>> {
+        let mut document = OfacDocument::default();
+        self.read(reader, &mut document)?;
+        Ok(document.references.date_of_issue.to_sql_date())
+    }
+}
+
+/// Every list known by the agent, `ALL` imports them in this order
+pub static SOURCES: &[&dyn ListSource] = &[&OfacNsSource, &OfacSource, &EuSource, &UnSource, &UkSource];
+
+/// The lists in the order `ALL` exports them, [`SOURCES`] with the lists exported first moved ahead
+pub fn export_order() -> Vec<&'static dyn ListSource> {
+    let mut sources = SOURCES.to_vec();
+    sources.sort_by_key(|source| !source.is_exported_first());
+    sources
+}
+
+/// The list with the given command line name
+pub fn find(name: &str) -> Option<&'static dyn ListSource> {
+    SOURCES.iter().find(|source| source.name() == name).copied()
+}
+
+/// Ofac Specially Designated Nationals list (`sdn_advanced.xml`)
+pub struct OfacSource;
+
+impl ListSource for OfacSource {
+    fn name(&self) -> &'static str {
+        "OFAC"
+    }
+
+    fn designation(&self) -> &'static str {
+        "OFAC"
+    }
+
+    fn oid_prefix(&self) -> &'static str {
+        "OFAC"
+    }
+
+    fn file_stem(&self) -> &'static str {
+        "sdn_advanced"
+    }
+
+    fn database_url_var(&self) -> &'static str {
+        "OFAC_DATABASE_URL"
+    }
+
+    fn data_folder_var(&self) -> &'static str {
+        "OFAC_DATA_FOLDER"
+    }
+
+    fn is_required(&self) -> bool {
+        true
+    }
+
+    /// The SDN records have always been written before the consolidated ones
+    fn is_exported_first(&self) -> bool {
+        true
+    }
+
+    fn legacy_file_stems(&self, format: LegacyFormat) -> &'static [&'static str] {
+        match format {
+            LegacyFormat::Xml => &["sdn"],
+            LegacyFormat::Csv => &["sdn", "add", "alt"],
+        }
+    }
+
+    fn is_streamable(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, reader: SourceReader) -> ValidationReport {
+        validation::validate(reader)
+    }
+
+    fn read(&self, reader: SourceReader, document: &mut OfacDocument) -> Result<(), Box<dyn Error>> {
+        Ok(read_document(reader, document)?)
+    }
+
+    fn read_date_of_issue(&self, reader: SourceReader) -> Result<NaiveDate, Box<dyn Error>> {
+        Ok(reader::read_date_of_issue(reader)?.ok_or("DateOfIssue not found")?.to_sql_date())
+    }
+}
+
+/// Ofac Consolidated (non-SDN) list (`cons_advanced.xml`)
+pub struct OfacNsSource;
+
+impl ListSource for OfacNsSource {
+    fn name(&self) -> &'static str {
+        "OFACNS"
+    }
+
+    fn designation(&self) -> &'static str {
+        "OFAC-NS"
+    }
+
+    fn oid_prefix(&self) -> &'static str {
+        "OFNS"
+    }
+
+    /// The alternative records of the consolidated list have always pointed to `OFAC` OIDs, downstream systems rely on it
+    fn alternative_oid_prefix(&self) -> &'static str {
+        "OFAC"
+    }
+
+    fn file_stem(&self) -> &'static str {
+        "cons_advanced"
+    }
+
+    fn database_url_var(&self) -> &'static str {
+        "OFAC_NS_DATABASE_URL"
+    }
+
+    fn data_folder_var(&self) -> &'static str {
+        "OFAC_NS_DATA_FOLDER"
+    }
+
+    fn is_required(&self) -> bool {
+        true
+    }
+
+    fn legacy_file_stems(&self, format: LegacyFormat) -> &'static [&'static str] {
+        match format {
+            LegacyFormat::Xml => &["consolidated"],
+            LegacyFormat::Csv => &["cons_prim", "cons_add", "cons_alt"],
+        }
+    }
+
+    fn is_streamable(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, reader: SourceReader) -> ValidationReport {
+        validation::validate(reader)
+    }
+
+    fn read(&self, reader: SourceReader, document: &mut OfacDocument) -> Result<(), Box<dyn Error>> {
+        Ok(read_document(reader, document)?)
+    }
+
+    fn read_date_of_issue(&self, reader: SourceReader) -> Result<NaiveDate, Box<dyn Error>> {
+        Ok(reader::read_date_of_issue(reader)?.ok_or("DateOfIssue not found")?.to_sql_date())
+    }
+}
+
+#[cfg(test)]
+mod list {
+    use super::*;
+    use crate::document::inputs::DocumentType;
+
+    #[test]
+    fn sources_are_registered_once() {
+        for source in SOURCES {
+            assert_eq!(source.name(), find(source.name()).unwrap().name());
+            assert_eq!(source.name(), DocumentType::of(*source).source().name());
+            assert_eq!(4, source.oid_prefix().len());
+            assert_eq!(1, SOURCES.iter().filter(|other| other.oid_prefix() == source.oid_prefix()).count());
+        }
+        assert!(find("FOFDBOF").is_none());
+    }
+
+    #[test]
+    fn processing_order() {
+        let names = |sources: &[&dyn ListSource]| sources.iter().map(|source| source.name()).collect::<Vec<_>>();
+        assert_eq!(vec!["OFACNS", "OFAC", "EU", "UN", "UK"], names(SOURCES));
+        assert_eq!(vec!["OFAC", "OFACNS", "EU", "UN", "UK"], names(&export_order()));
+        assert_eq!(DocumentType::OFAC, DocumentType::of(find("OFAC").unwrap()));
+        assert_ne!(DocumentType::OFAC, DocumentType::OFACNS);
+    }
+}
@@ -1,35 +1,98 @@
-use chrono::Local;
-use log::info;
-use quick_xml::de::from_str;
-use quick_xml::events::Event;
-use quick_xml::name::QName;
-use quick_xml::Reader;
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::{info, warn};
+use quick_xml::DeError;
+use std::collections::HashMap;
 use std::error::Error;
+use std::fs::File;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task::JoinHandle;
 
+pub mod builder;
+pub mod identifier;
+pub mod known;
+pub mod list;
 pub mod models;
+pub mod reader;
+pub mod source;
+pub mod validation;
 
+use self::known::KnownValue;
+use self::list::ListSource;
 use self::models::areacode::AreaCode;
 use self::models::dateofissue::DateOfIssue;
-use self::models::distinctparty::DistinctParties;
-use self::models::document::IDRegDocuments;
-use self::models::location::Locations;
-use self::models::profilerelationship::ProfileRelationships;
-use self::models::referencevaluesets::{DetailReferenceValues, FeatureTypeValues, IDRegDocTypeValues, PartySubTypeValues, ReferenceValueSets, ScriptValues};
-use self::models::sanction::SanctionsEntries;
-
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
-pub enum DocumentType {
-    OFAC,
-    OFACNS,
+use self::models::distinctparty::{DistinctParties, DistinctParty};
+use self::models::document::{Document, IDRegDocuments};
+use self::models::index::Index;
+use self::models::location::{Location, Locations};
+use self::models::profilerelationship::{ProfileRelationship, ProfileRelationships};
+use self::models::referencevaluesets::{DetailReferenceValues, FeatureTypeValues, IDRegDocTypeValues, PartySubTypeValues, ReferenceValueSet, ReferenceValueSets, ScriptValues};
+use self::models::sanction::{SanctionsEntries, SanctionsEntry};
+use self::reader::{read_document, OfacDocumentVisitor};
+use self::source::{DocumentSource, InputFormat, SourceReader};
+use self::validation::ValidationReport;
+use super::legacy::{self, LegacyFormat, LegacyReport};
+use crate::db::mapping::FeatureMapping;
+
+/// The list of a document, given by its [`ListSource`]
+#[derive(Clone, Copy)]
+pub struct DocumentType(&'static dyn ListSource);
+
+impl DocumentType {
+    pub const OFAC: DocumentType = DocumentType(&list::OfacSource);
+    pub const OFACNS: DocumentType = DocumentType(&list::OfacNsSource);
+
+    pub const fn of(source: &'static dyn ListSource) -> DocumentType {
+        DocumentType(source)
+    }
+
+    pub fn source(&self) -> &'static dyn ListSource {
+        self.0
+    }
+
+    /// Name of the list in the exported DSG, ORI and REF fields
+    pub fn designation(&self) -> &'static str {
+        self.source().designation()
+    }
+
+    /// Four letters prefix of the exported OIDs
+    pub fn oid_prefix(&self) -> &'static str {
+        self.source().oid_prefix()
+    }
+
+    /// Prefix of the main record OID written in the alternative records
+    pub fn alternative_oid_prefix(&self) -> &'static str {
+        self.source().alternative_oid_prefix()
+    }
 }
 
 impl Default for DocumentType {
     fn default() -> Self {
-        Self::OFAC
+        DocumentType::OFAC
+    }
+}
+
+/// Two types are the same list when their sources have the same command line name
+impl PartialEq for DocumentType {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name() == other.0.name()
+    }
+}
+
+impl Eq for DocumentType {}
+
+impl std::fmt::Debug for DocumentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0.name())
     }
 }
 
+/// DistinctParties being read and the reading task, see [`OfacDocument::stream_distinct_parties`]
+pub type DistinctPartyStream = (Receiver<DistinctParty>, JoinHandle<Result<(), DeError>>);
+
 #[derive(Default)]
 pub struct OfacDocumentReferences {
     pub area_codes: Vec<AreaCode>,
@@ -39,6 +102,97 @@ pub struct OfacDocumentReferences {
     pub party_sub_type_values: PartySubTypeValues,
     pub reg_doc_types: IDRegDocTypeValues,
     pub script_values: ScriptValues,
+    /// ReferenceValueSets without their own model
+    pub value_sets: Vec<ReferenceValueSet>,
+    /// Mapping of the FeatureTypes given with `--mapping`, the built-in mapping is used when not set
+    pub mapping: Option<Arc<FeatureMapping>>,
+    index: ReferenceIndex,
+}
+
+/// Positions of the reference values by id, see [`OfacDocumentReferences::build_index`]
+#[derive(Default)]
+struct ReferenceIndex {
+    area_codes_by_id: Index<i32>,
+    area_codes_by_name: Index<String>,
+    detail_references: Index<i32>,
+    feature_types: Index<i32>,
+    reg_doc_types: Index<i32>,
+    party_sub_types: Index<i32>,
+    scripts_by_code: Index<String>,
+    value_sets: Index<String>,
+    /// Values of each ReferenceValueSet, in the order of `value_sets`
+    values: Vec<Index<i32>>,
+}
+
+impl OfacDocumentReferences {
+    /// Index the reference values once they are read, it must be called again after they change
+    pub fn build_index(&mut self) {
+        self.index = ReferenceIndex {
+            area_codes_by_id: Index::build(&self.area_codes, |area| area.id),
+            area_codes_by_name: Index::build(&self.area_codes, |area| area.name.clone()),
+            detail_references: Index::build(&self.detail_references.detail_references, |value| value.id),
+            feature_types: Index::build(&self.feature_types.types, |value| value.id),
+            reg_doc_types: Index::build(&self.reg_doc_types.reg_doc_types, |value| value.id),
+            party_sub_types: Index::build(&self.party_sub_type_values.values, |value| value.id),
+            scripts_by_code: Index::build(&self.script_values.scripts, |script| script.code.clone()),
+            value_sets: Index::build(&self.value_sets, |value_set| value_set.name.clone()),
+            values: self.value_sets.iter().map(|value_set| Index::build(&value_set.values, |value| value.id)).collect(),
+        };
+    }
+
+    pub fn area_code(&self, id: i32) -> Option<&AreaCode> {
+        self.index.area_codes_by_id.find(&self.area_codes, &id, |area| area.id == id)
+    }
+
+    pub fn area_code_by_name(&self, name: &str) -> Option<&AreaCode> {
+        self.index.area_codes_by_name.find(&self.area_codes, &name.to_owned(), |area| area.name == name)
+    }
+
+    /// Name of the value `id` of the ReferenceValueSet `set` (e.g. `NamePartType`)
+    pub fn value_name(&self, set: &str, id: i32) -> Option<&str> {
+        let index = &self.index;
+        match set {
+            "DetailReference" => index.detail_references.find(&self.detail_references.detail_references, &id, |value| value.id == id).map(|value| value.value.as_str()),
+            "FeatureType" => index.feature_types.find(&self.feature_types.types, &id, |value| value.id == id).map(|value| value.value.as_str()),
+            "IDRegDocType" => index.reg_doc_types.find(&self.reg_doc_types.reg_doc_types, &id, |value| value.id == id).map(|value| value.value.as_str()),
+            "PartySubType" => index.party_sub_types.find(&self.party_sub_type_values.values, &id, |value| value.id == id).map(|value| value.value.as_str()),
+            _ => {
+                let position = match index.value_sets.get(self.value_sets.len(), &set.to_owned()) {
+                    Some(positions) => positions.first().copied(),
+                    None => self.value_sets.iter().position(|value_set| value_set.name == set),
+                }?;
+                let values = &self.value_sets[position].values;
+                match index.values.get(position) {
+                    Some(values_index) => values_index.find(values, &id, |value| value.id == id),
+                    None => values.iter().find(|value| value.id == id),
+                }
+                .map(|value| value.value.as_str())
+            }
+        }
+    }
+
+    pub fn feature_mapping(&self) -> &FeatureMapping {
+        match self.mapping.as_deref() {
+            Some(mapping) => mapping,
+            None => FeatureMapping::built_in(),
+        }
+    }
+
+    /// The known value published with `id`, looked up by its name and by its Ofac id when the name is unknown
+    pub fn resolve<T: KnownValue>(&self, id: i32) -> Option<T> {
+        self.value_name(T::SET, id).and_then(T::from_name).or_else(|| T::from_id(id))
+    }
+
+    /// Id of the script `value` is written in, `None` when the script is not in the ScriptValues
+    pub fn detect_script_id(&self, value: &str) -> Option<i32> {
+        let (code, _) = builder::detect_script(value);
+        self.index.scripts_by_code.find(&self.script_values.scripts, &code.to_owned(), |script| script.code == code).map(|script| script.id)
+    }
+
+    /// Id of the latin script
+    pub fn latin_script_id(&self) -> i32 {
+        self.index.scripts_by_code.find(&self.script_values.scripts, &"Latn".to_owned(), |script| script.code == "Latn").map_or(builder::LATIN_SCRIPT, |script| script.id)
+    }
 }
 
 #[derive(Default)]
@@ -52,6 +206,15 @@ pub struct OfacDocument {
     pub root_folder: PathBuf,
     pub sanction_entries: SanctionsEntries,
     pub is_loaded: bool,
+    pub is_streamed: bool,
+    /// Explicit input file, the data folder is searched when not set
+    pub input: Option<PathBuf>,
+    /// File the document has been read from
+    pub source: Option<DocumentSource>,
+    /// Legacy format the document has been read from, see [`OfacDocument::load_legacy`]
+    pub legacy: Option<LegacyFormat>,
+    /// Files read along with the source (i.e legacy ADD.CSV and ALT.CSV)
+    pub attachments: Vec<DocumentSource>,
 }
 
 impl OfacDocument {
@@ -68,121 +231,312 @@ impl OfacDocument {
 
     /// Load the current document from file
     ///
-    /// Reading and loading xml with needed tag
+    /// The xml is read in a single pass and every section is kept in memory
     pub fn load(&mut self) -> Result<(), Box<dyn Error>> {
-        let filename = &self.file_name()[..];
-        let file = self.root_folder.join(filename);
-        info!("Reading xml file : {:?}", self.root_folder.join(filename));
-        let xml = match std::fs::read_to_string(&file) {
-            Ok(content) => content,
-            Err(err) => return Err(format!("Ofac document not found in {}, {}", file.to_string_lossy(), err).into()),
-        };
-        info!("Splitting xml ...");
-        let mut reader = Reader::from_str(&xml);
-        reader.trim_text(true).check_comments(false).expand_empty_elements(true).check_end_names(false);
-        let mut start = 0;
-        let mut buf = Vec::new();
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref e)) if e.local_name() == QName(b"DateOfIssue").into() => {
-                    // Buf doesn't contain [<>] delimiters so we have to add 2
-                    start = reader.buffer_position() - (buf.len() + 2);
-                }
-                Ok(Event::End(ref e)) if e.local_name() == QName(b"DateOfIssue").into() => {
-                    self.references.date_of_issue = from_str(&xml[start..reader.buffer_position()])?;
-                }
-                Ok(Event::Start(ref e)) if e.local_name() == QName(b"IDRegDocuments").into() => {
-                    start = reader.buffer_position() - (buf.len() + 2);
-                }
-                Ok(Event::End(ref e)) if e.local_name() == QName(b"IDRegDocuments").into() => {
-                    self.documents = from_str(&xml[start..reader.buffer_position()])?;
-                }
-                Ok(Event::Start(ref e)) if e.local_name() == QName(b"ReferenceValueSets").into() => {
-                    start = reader.buffer_position() - (buf.len() + 2);
-                }
-                Ok(Event::End(ref e)) if e.local_name() == QName(b"ReferenceValueSets").into() => {
-                    let references: ReferenceValueSets = from_str(&xml[start..reader.buffer_position()])?;
-                    self.references.area_codes = references.area_code_values.area_codes;
-                    self.references.detail_references = references.detail_reference_values;
-                    self.references.feature_types = references.feature_types;
-                    self.references.party_sub_type_values = references.party_sub_type_values;
-                    self.references.reg_doc_types = references.reg_doc_types_values;
-                    self.references.script_values = references.script_values;
-                }
-                Ok(Event::Start(ref e)) if e.local_name() == QName(b"DistinctParties").into() => {
-                    start = reader.buffer_position() - (buf.len() + 2);
-                }
-                Ok(Event::End(ref e)) if e.local_name() == QName(b"DistinctParties").into() => {
-                    self.distinct_parties = from_str(&xml[start..reader.buffer_position()])?;
-                }
-                Ok(Event::Start(ref e)) if e.local_name() == QName(b"ProfileRelationships").into() => {
-                    start = reader.buffer_position() - (buf.len() + 2);
-                }
-                Ok(Event::End(ref e)) if e.local_name() == QName(b"ProfileRelationships").into() => {
-                    self.profile_relationships = from_str(&xml[start..reader.buffer_position()])?;
-                }
-                Ok(Event::Start(ref e)) if e.local_name() == QName(b"Locations").into() => {
-                    start = reader.buffer_position() - (buf.len() + 2);
-                }
-                Ok(Event::End(ref e)) if e.local_name() == QName(b"Locations").into() => {
-                    self.locations = from_str(&xml[start..reader.buffer_position()]).unwrap();
-                }
-                Ok(Event::Start(ref e)) if e.local_name() == QName(b"SanctionsEntries").into() => {
-                    start = reader.buffer_position() - (buf.len() + 2);
-                }
-                Ok(Event::End(ref e)) if e.local_name() == QName(b"SanctionsEntries").into() => {
-                    self.sanction_entries = from_str(&xml[start..reader.buffer_position()])?;
+        let file = self.open()?;
+        self.document_type.source().read(file, self)?;
+        info!("Xml read, found {} DistinctParties, data processing has started ...", self.distinct_parties.parties.len());
+        self.build_indexes();
+        self.is_loaded = true;
+        Ok(())
+    }
+
+    /// Load the document issued on `date_of_issue` from the archive folder, see [`OfacDocument::cleanup`]
+    ///
+    /// Archived files are named after the time they were archived, only their date of issue is read from the newest
+    /// until one is issued on `date_of_issue`, then this file is loaded
+    pub fn load_archive(&mut self, date_of_issue: NaiveDate) -> Result<(), Box<dyn Error>> {
+        let archive_folder = self.root_folder.join("archive");
+        let stem = self.file_stem().to_lowercase();
+        let mut paths: Vec<(NaiveDateTime, PathBuf)> = std::fs::read_dir(&archive_folder)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter_map(|path| match archive_name_parts(&path) {
+                Some((archived_stem, archived)) if archived_stem == stem => Some((archived, path)),
+                _ => None,
+            })
+            .collect();
+        paths.sort();
+        for (_, path) in paths.into_iter().rev() {
+            let issued = DocumentSource::new(&path).open().and_then(|reader| self.document_type.source().read_date_of_issue(reader));
+            match issued {
+                Ok(issued) if issued == date_of_issue => {
+                    info!("Document issued on {} found in {}", date_of_issue, path.to_string_lossy());
+                    self.input = Some(path);
+                    self.source = None;
+                    return self.load();
                 }
-                Ok(Event::Eof) => break,
-                Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
-                _ => (),
+                Ok(_) => {}
+                Err(err) => warn!("Archived document {} can not be read : {}", path.to_string_lossy(), err),
             }
-            buf.clear();
         }
-        info!("Xml splitted, data processing has started ...");
+        Err(format!("No document issued on {} in {}", date_of_issue, archive_folder.to_string_lossy()).into())
+    }
+
+    /// Check the current document before it is loaded, see [`validation::validate`]
+    pub fn validate(&mut self) -> Result<ValidationReport, Box<dyn Error>> {
+        let file = self.open()?;
+        let report = self.document_type.source().validate(file);
+        info!("Validation done, {} violations found", report.violations.len());
+        Ok(report)
+    }
+
+    /// Load every section of the current document but the DistinctParties
+    ///
+    /// DistinctParties are then given one at a time to the importer with [`OfacDocument::stream_distinct_parties`]
+    ///
+    /// Only Ofac advanced documents can be streamed, other lists are fully loaded
+    pub fn load_streamed(&mut self) -> Result<(), Box<dyn Error>> {
+        if !self.document_type.source().is_streamable() {
+            return self.load();
+        }
+        self.is_streamed = true;
+        let file = self.open()?;
+        read_document(file, self)?;
+        info!("Xml read, DistinctParties will be streamed ...");
+        self.build_indexes();
         self.is_loaded = true;
         Ok(())
     }
 
+    /// Load the current document from the legacy files (`sdn.xml` or `SDN.CSV`, `ADD.CSV` and `ALT.CSV`)
+    ///
+    /// Legacy uids are used as FixedRefs, the fields that could not be mapped are logged and returned
+    pub fn load_legacy(&mut self, format: LegacyFormat) -> Result<LegacyReport, Box<dyn Error>> {
+        let mut sources = format.find(&self.root_folder, self.input.as_ref(), self.document_type)?.into_iter();
+        let source = sources.next().ok_or("Legacy files not found")?;
+        let attachments: Vec<DocumentSource> = sources.collect();
+        info!("Reading legacy {:?} file : {:?}", format, source.path);
+        let mut report = LegacyReport::default();
+        let files: Vec<DocumentSource> = std::iter::once(source.clone()).chain(attachments.iter().cloned()).collect();
+        let document = legacy::load(format, &files, &self.root_folder, self.document_type, &mut report)?;
+        self.set_content(document);
+        self.source = Some(source);
+        self.attachments = attachments;
+        self.legacy = Some(format);
+        report.log();
+        info!("Legacy file read, found {} DistinctParties, data processing has started ...", self.distinct_parties.parties.len());
+        self.build_indexes();
+        self.is_loaded = true;
+        Ok(report)
+    }
+
+    /// Index the sections looked up while the DistinctParties are processed, so that they are not searched for every party
+    pub fn build_indexes(&mut self) {
+        self.locations.build_index();
+        self.sanction_entries.build_index();
+        self.documents.build_index();
+        self.references.build_index();
+    }
+
+    /// Keep the content of a document built from another format
+    pub(crate) fn set_content(&mut self, document: OfacDocument) {
+        self.distinct_parties = document.distinct_parties;
+        self.documents = document.documents;
+        self.locations = document.locations;
+        self.profile_relationships = document.profile_relationships;
+        self.references = document.references;
+        self.sanction_entries = document.sanction_entries;
+    }
+
+    /// Read the DistinctParties of the current document on a blocking thread
+    ///
+    /// Parties are sent through the returned channel as soon as they are deserialized, at most `capacity`
+    /// of them are waiting to be processed. The other sections are skipped, and the reading stops once the receiver is
+    /// dropped. The handle resolves once the document has been read.
+    pub fn stream_distinct_parties(&self, capacity: usize) -> Result<DistinctPartyStream, Box<dyn Error>> {
+        let file = match &self.source {
+            Some(source) => source.open()?,
+            None => return Err("The document must be loaded before its DistinctParties are streamed".into()),
+        };
+        let (sender, receiver) = mpsc::channel(capacity);
+        let handle = tokio::task::spawn_blocking(move || read_document(file, &mut DistinctPartySender { sender, stopped: false }));
+        Ok((receiver, handle))
+    }
+
+    /// Open the input file, searching the data folder when no input was given
+    ///
+    /// The file found is kept so the document is always read from the same source
+    fn open(&mut self) -> Result<SourceReader, Box<dyn Error>> {
+        let source = match (&self.source, &self.input) {
+            (Some(source), _) => source.clone(),
+            (None, Some(input)) => DocumentSource::new(input),
+            (None, None) => DocumentSource::find(&self.root_folder, self.file_stem())?,
+        };
+        info!("Reading {:?} file : {:?}", source.format, source.path);
+        let reader = source.open()?;
+        self.source = Some(source);
+        Ok(reader)
+    }
+
     /// Get the filename for archive purpose from the current Ofac document type
-    fn archive_file_name(&self) -> String {
-        match self.document_type {
-            DocumentType::OFAC => {
-                format!("sdn_advanced{}.xml", Local::now().format("%Y%m%d_%H%M%S"))
-            }
-            DocumentType::OFACNS => {
-                format!("cons_advanced{}.xml", Local::now().format("%Y%m%d_%H%M%S"))
-            }
+    ///
+    /// Legacy files keep their own name (e.g. `add20221108_101010.csv.gz`)
+    fn archive_file_name(&self, source: &DocumentSource) -> String {
+        let date = Local::now().format("%Y%m%d_%H%M%S");
+        let file_name = source.path.file_name().map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default();
+        match (self.legacy, file_name.split_once('.')) {
+            (Some(_), Some((stem, extension))) if source.format == InputFormat::Xml => format!("{}{}.{}.gz", stem, date, extension),
+            (Some(_), Some((stem, _))) => format!("{}{}.{}", stem, date, source.format.archive_extension()),
+            _ => format!("{}{}.{}", self.file_stem(), date, source.format.archive_extension()),
         }
     }
 
-    /// Get the filename from the current Ofac document type
-    fn file_name(&self) -> String {
-        match self.document_type {
-            DocumentType::OFAC => "sdn_advanced.xml".to_owned(),
-            DocumentType::OFACNS => "cons_advanced.xml".to_owned(),
-        }
+    /// Get the filename without extension from the current Ofac document type
+    fn file_stem(&self) -> &'static str {
+        self.document_type.source().file_stem()
     }
 
     /// Cleans the current ofac data folder.
     ///
-    /// The current document loaded is moved to archive folder in the data folder and
-    /// oldest archived files are removed if there is more than 5 files in the archive folder
+    /// A copy of the current document loaded is saved to archive folder in the data folder (plain xml is gzipped) and
+    /// oldest archived files of each stem are removed if there is more than 5 of them, see [`archives_to_prune`]
     pub fn cleanup(&self) -> Result<(), std::io::Error> {
         let archive_folder = &self.root_folder.join("archive");
         std::fs::create_dir_all(archive_folder)?;
-        let mut paths: Vec<_> = std::fs::read_dir(archive_folder).unwrap().map(|r| r.unwrap()).collect();
-        paths.sort_by_key(|dir| dir.path());
-        let count = paths.len();
-        for (i, path) in paths.iter().enumerate() {
-            if count < 5 || i >= count - 5 {
-                break;
+        let paths: Vec<PathBuf> = std::fs::read_dir(archive_folder)?.map(|entry| entry.map(|entry| entry.path())).collect::<Result<_, _>>()?;
+        for path in archives_to_prune(paths) {
+            std::fs::remove_file(path)?;
+        }
+        for source in self.source.iter().chain(self.attachments.iter()) {
+            if source.path.parent() == Some(archive_folder) {
+                continue;
+            }
+            let to = archive_folder.join(self.archive_file_name(source));
+            info!("Backing up {} to {}", source.path.to_string_lossy(), to.to_string_lossy());
+            match source.format {
+                InputFormat::Xml => {
+                    let mut encoder = GzEncoder::new(File::create(to)?, Compression::default());
+                    std::io::copy(&mut File::open(&source.path)?, &mut encoder)?;
+                    encoder.finish()?;
+                }
+                InputFormat::Gzip | InputFormat::Zip => {
+                    std::fs::copy(&source.path, to)?;
+                }
             }
-            std::fs::remove_file(path.path())?;
         }
-        let to = archive_folder.join(self.archive_file_name());
-        info!("Backing up {} to {}", self.file_name(), to.to_string_lossy());
-        //std::fs::rename(&self.root_folder.join(self.file_name()), to)?;
         Ok(())
     }
 }
+
+/// Number of archived files kept for each stem
+const ARCHIVES_KEPT: usize = 5;
+
+/// Split an archived file name (e.g. `sdn_advanced20221108_101010.xml.gz`) into its lowercase stem and the time it was
+/// archived, see [`OfacDocument::archive_file_name`]
+fn archive_name_parts(path: &Path) -> Option<(String, NaiveDateTime)> {
+    let file_name = path.file_name()?.to_string_lossy().to_lowercase();
+    let name = file_name.split('.').next()?;
+    let split = name.len().checked_sub("yyyymmdd_hhmmss".len())?;
+    let archived = NaiveDateTime::parse_from_str(name.get(split..)?, "%Y%m%d_%H%M%S").ok()?;
+    Some((name[..split].to_string(), archived))
+}
+
+/// Get the archived files to remove: the oldest ones of each stem when there are more than [`ARCHIVES_KEPT`] of them
+///
+/// Files not named like an archive are kept
+fn archives_to_prune(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut stems: HashMap<String, Vec<(NaiveDateTime, PathBuf)>> = HashMap::new();
+    for path in paths {
+        if let Some((stem, archived)) = archive_name_parts(&path) {
+            stems.entry(stem).or_default().push((archived, path));
+        }
+    }
+    let mut pruned = Vec::new();
+    for mut archives in stems.into_values() {
+        archives.sort();
+        let count = archives.len().saturating_sub(ARCHIVES_KEPT);
+        pruned.extend(archives.into_iter().take(count).map(|(_, path)| path));
+    }
+    pruned.sort();
+    pruned
+}
+
+impl OfacDocumentVisitor for OfacDocument {
+    fn visit_date_of_issue(&mut self, date_of_issue: DateOfIssue) {
+        self.references.date_of_issue = date_of_issue;
+    }
+
+    fn visit_references(&mut self, references: ReferenceValueSets) {
+        self.references.area_codes = references.area_code_values.area_codes;
+        self.references.detail_references = references.detail_reference_values;
+        self.references.feature_types = references.feature_types;
+        self.references.party_sub_type_values = references.party_sub_type_values;
+        self.references.reg_doc_types = references.reg_doc_types_values;
+        self.references.script_values = references.script_values;
+        self.references.value_sets = references.value_sets;
+    }
+
+    fn visit_location(&mut self, location: Location) {
+        self.locations.locations.push(location);
+    }
+
+    fn visit_document(&mut self, document: Document) {
+        self.documents.documents.push(document);
+    }
+
+    fn visit_distinct_party(&mut self, party: DistinctParty) {
+        self.distinct_parties.parties.push(party);
+    }
+
+    fn visit_profile_relationship(&mut self, relationship: ProfileRelationship) {
+        self.profile_relationships.profile_relationships.push(relationship);
+    }
+
+    fn visit_sanctions_entry(&mut self, entry: SanctionsEntry) {
+        self.sanction_entries.entries.push(entry);
+    }
+
+    fn wants_distinct_parties(&self) -> bool {
+        !self.is_streamed
+    }
+}
+
+/// Forwards each DistinctParty to the importer, waiting when it is too far behind
+/// Sends the DistinctParties of the second pass, the sections already loaded are skipped
+struct DistinctPartySender {
+    sender: Sender<DistinctParty>,
+    stopped: bool,
+}
+
+impl OfacDocumentVisitor for DistinctPartySender {
+    fn visit_distinct_party(&mut self, party: DistinctParty) {
+        // The receiver is only dropped when the import has been aborted, remaining parties are then useless
+        self.stopped = self.sender.blocking_send(party).is_err();
+    }
+
+    fn wants_other_sections(&self) -> bool {
+        false
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+}
+
+#[cfg(test)]
+mod inputs {
+    use super::*;
+
+    #[test]
+    fn archive_name_is_split() {
+        let excepted = Some(("sdn_advanced".to_string(), NaiveDate::from_ymd(2022, 11, 8).and_hms(10, 10, 10)));
+        assert_eq!(excepted, archive_name_parts(Path::new("archive/SDN_ADVANCED20221108_101010.xml.gz")));
+        assert_eq!(None, archive_name_parts(Path::new("archive/readme.txt")));
+        assert_eq!(None, archive_name_parts(Path::new("archive/sdn20221399_101010.csv.gz")));
+    }
+
+    #[test]
+    fn oldest_archives_are_pruned_per_stem() {
+        let mut paths: Vec<PathBuf> = (1..=7).map(|day| PathBuf::from(format!("sdn_advanced2022110{}_101010.xml.gz", day))).collect();
+        // Legacy archives sort before sdn_advanced by name, they must not be pruned in its place
+        paths.extend((1..=3).map(|day| PathBuf::from(format!("add2022110{}_101010.csv.gz", day))));
+        paths.extend((1..=6).map(|hour| PathBuf::from(format!("sdn20221108_0{}0000.csv.gz", hour))));
+        paths.push(PathBuf::from("readme.txt"));
+        paths.reverse();
+        let excepted: Vec<PathBuf> = ["sdn20221108_010000.csv.gz", "sdn_advanced20221101_101010.xml.gz", "sdn_advanced20221102_101010.xml.gz"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(excepted, archives_to_prune(paths));
+    }
+}
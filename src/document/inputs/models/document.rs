@@ -1,11 +1,24 @@
 use serde::Deserialize;
 
 use super::feature::DatePeriod;
+use super::index::Index;
 
 #[derive(Debug, Deserialize, PartialEq, Default, Eq)]
 pub struct IDRegDocuments {
     #[serde(rename = "IDRegDocument")]
     pub documents: Vec<Document>,
+    #[serde(skip)]
+    by_identity: Index<i32>,
+}
+
+impl IDRegDocuments {
+    pub fn build_index(&mut self) {
+        self.by_identity = Index::build(&self.documents, |document| document.identity_id);
+    }
+
+    pub fn by_identity(&self, identity_id: i32) -> Vec<&Document> {
+        self.by_identity.filter(&self.documents, &identity_id, |document| document.identity_id == identity_id)
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -1,6 +1,6 @@
 use std::fmt::Write;
 
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -19,6 +19,8 @@ pub struct FeatureVersion {
     pub id: i32,
     #[serde(rename = "ReliabilityID")]
     pub reliability_id: i32,
+    #[serde(rename = "Comment", default)]
+    pub comment: Option<String>,
     #[serde(rename = "VersionDetail", skip_serializing_if = "Option::is_none")]
     pub detail: Option<VersionDetail>,
     #[serde(rename = "VersionLocation", skip_serializing_if = "Option::is_none")]
@@ -27,6 +29,13 @@ pub struct FeatureVersion {
     pub date_period: Option<DatePeriod>,
 }
 
+impl FeatureVersion {
+    /// Comment of the version, `None` when the xml only has an empty `<Comment />`
+    pub fn comment(&self) -> Option<String> {
+        self.comment.as_deref().map(str::trim).filter(|comment| !comment.is_empty()).map(str::to_uppercase)
+    }
+}
+
 #[derive(Debug, Deserialize, PartialEq, Copy, Clone, Eq)]
 pub struct VersionLocation {
     #[serde(rename = "LocationID")]
@@ -41,12 +50,88 @@ pub struct DatePeriod {
     pub end: Option<DatePeriodRange>,
 }
 
+/// Shape of a DatePeriod, classified from its earliest and latest dates
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DatePeriodShape {
+    Day(NaiveDate),
+    Month(NaiveDate),
+    Year(i32),
+    DayRange(NaiveDate, NaiveDate),
+    MonthRange(NaiveDate, NaiveDate),
+    YearRange(i32, i32),
+}
+
+impl DatePeriodShape {
+    /// Every period is a range of days, it is widened to months or years when its bounds fall on them
+    pub fn classify(earliest: NaiveDate, latest: NaiveDate) -> DatePeriodShape {
+        let starts_month = earliest.day() == 1;
+        let ends_month = latest.succ_opt().is_none_or(|next| next.day() == 1);
+        let starts_year = starts_month && earliest.month() == 1;
+        let ends_year = ends_month && latest.month() == 12;
+        if earliest == latest {
+            DatePeriodShape::Day(earliest)
+        } else if starts_year && ends_year {
+            if earliest.year() == latest.year() {
+                DatePeriodShape::Year(earliest.year())
+            } else {
+                DatePeriodShape::YearRange(earliest.year(), latest.year())
+            }
+        } else if starts_month && ends_month {
+            if (earliest.year(), earliest.month()) == (latest.year(), latest.month()) {
+                DatePeriodShape::Month(earliest)
+            } else {
+                DatePeriodShape::MonthRange(earliest, latest)
+            }
+        } else {
+            DatePeriodShape::DayRange(earliest, latest)
+        }
+    }
+
+    /// Formatted as in the Ofac legacy files (`13 APR 1975`, `FEB 1968`, `1984 TO 1986`...)
+    pub fn to_ofac_string(&self) -> String {
+        let dmy = |date: &NaiveDate| date.format("%d %b %Y").to_string().to_uppercase();
+        let my = |date: &NaiveDate| date.format("%b %Y").to_string().to_uppercase();
+        match self {
+            DatePeriodShape::Day(day) => dmy(day),
+            DatePeriodShape::Month(month) => my(month),
+            DatePeriodShape::Year(year) => year.to_string(),
+            DatePeriodShape::DayRange(from, to) => format!("{} TO {}", dmy(from), dmy(to)),
+            DatePeriodShape::MonthRange(from, to) => format!("{} TO {}", my(from), my(to)),
+            DatePeriodShape::YearRange(from, to) => format!("{} TO {}", from, to),
+        }
+    }
+
+    /// Ranges are already imprecise, only a single day, month or year is written as CIRCA
+    pub fn to_ofac_string_approximate(&self, approximate: bool) -> String {
+        match self {
+            DatePeriodShape::Day(_) | DatePeriodShape::Month(_) | DatePeriodShape::Year(_) if approximate => format!("CIRCA {}", self.to_ofac_string()),
+            _ => self.to_ofac_string(),
+        }
+    }
+}
+
 impl DatePeriod {
+    /// Earliest and latest dates of the period, `None` when a bound is missing or is not a valid date
+    pub fn bounds(&self) -> Option<(NaiveDate, NaiveDate)> {
+        let earliest = self.start.as_ref()?.from.to_naive_date()?;
+        let latest = self.end.as_ref()?.to.to_naive_date()?;
+        Some((earliest, latest))
+    }
+
+    pub fn is_approximate(&self) -> bool {
+        self.start.as_ref().is_some_and(|start| start.approximate) || self.end.as_ref().is_some_and(|end| end.approximate)
+    }
+
+    pub fn shape(&self) -> Option<DatePeriodShape> {
+        self.bounds().map(|(earliest, latest)| DatePeriodShape::classify(earliest, latest))
+    }
+
+    /// Exact date of the period, ranges are only kept by `bounds`
     pub fn parse_from_to(&self) -> Option<NaiveDate> {
-        let start = self.start.as_ref().unwrap();
-        let end = self.end.as_ref().unwrap();
+        let start = self.start.as_ref()?;
+        let end = self.end.as_ref()?;
         if start.from == start.to && end.from == end.to {
-            return Some(start.from.to_sql_date());
+            return start.from.to_naive_date();
         }
         None
     }
@@ -77,6 +162,10 @@ impl Date {
         NaiveDate::from_ymd(self.year.parse::<i32>().unwrap(), self.month.parse::<u32>().unwrap(), self.day.parse::<u32>().unwrap())
     }
 
+    pub fn to_naive_date(&self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.year.parse().ok()?, self.month.parse().ok()?, self.day.parse().ok()?)
+    }
+
     pub fn to_string_date(&self) -> String {
         let mut sql_date = String::new();
         sql_date.push_str(&self.year);
@@ -87,49 +176,47 @@ impl Date {
         sql_date
     }
 
-    pub fn to_ofac_string_dmy(&self) -> String {
+    /// `None` when the month is not recognized
+    pub fn to_ofac_string_dmy(&self) -> Option<String> {
         let mut ofac_date = String::new();
         write!(ofac_date, "{:0>2}", &self.day).unwrap();
         ofac_date.push(' ');
-        ofac_date.push_str(&self.format_month());
+        ofac_date.push_str(self.format_month()?);
         ofac_date.push(' ');
         ofac_date.push_str(&self.year);
-        ofac_date
+        Some(ofac_date)
     }
 
-    pub fn to_ofac_string_my(&self) -> String {
+    /// `None` when the month is not recognized
+    pub fn to_ofac_string_my(&self) -> Option<String> {
         let mut ofac_date = String::new();
-        ofac_date.push_str(&self.format_month());
+        ofac_date.push_str(self.format_month()?);
         ofac_date.push(' ');
         ofac_date.push_str(&self.year);
-        ofac_date
+        Some(ofac_date)
     }
 
-    fn format_month(&self) -> String {
+    fn format_month(&self) -> Option<&'static str> {
         match &format!("{:0>2}", &self.month)[..] {
-            "01" => "JAN".to_owned(),
-            "02" => "FEB".to_owned(),
-            "03" => "MAR".to_owned(),
-            "04" => "APR".to_owned(),
-            "05" => "MAY".to_owned(),
-            "06" => "JUN".to_owned(),
-            "07" => "JUL".to_owned(),
-            "08" => "AUG".to_owned(),
-            "09" => "SEP".to_owned(),
-            "10" => "OCT".to_owned(),
-            "11" => "NOV".to_owned(),
-            "12" => "DEC".to_owned(),
-            _ => panic!("Month format not recognized {:?} {}", self, self.month),
+            "01" => Some("JAN"),
+            "02" => Some("FEB"),
+            "03" => Some("MAR"),
+            "04" => Some("APR"),
+            "05" => Some("MAY"),
+            "06" => Some("JUN"),
+            "07" => Some("JUL"),
+            "08" => Some("AUG"),
+            "09" => Some("SEP"),
+            "10" => Some("OCT"),
+            "11" => Some("NOV"),
+            "12" => Some("DEC"),
+            _ => None,
         }
     }
 
+    /// `false` when the date is not a calendar date
     pub fn is_last_day_of_month(&self) -> bool {
-        let year = self.year.parse().unwrap();
-        let month = self.month.parse().unwrap();
-        let last_day = if month == 12 { NaiveDate::from_ymd(year + 1, 1, 1) } else { NaiveDate::from_ymd(year, month + 1, 1) }
-            .signed_duration_since(NaiveDate::from_ymd(year, month, 1))
-            .num_days();
-        last_day == self.day.parse::<i64>().unwrap()
+        self.to_naive_date().is_some_and(|date| date.succ_opt().is_none_or(|next| next.day() == 1))
     }
 
     pub fn is_first_day_of_month(&self) -> bool {
@@ -154,3 +241,68 @@ pub struct VersionDetail {
     #[serde(rename = "$value")]
     pub value: Option<String>,
 }
+
+#[cfg(test)]
+mod feature {
+    use super::*;
+
+    fn shape(earliest: (i32, u32, u32), latest: (i32, u32, u32)) -> DatePeriodShape {
+        DatePeriodShape::classify(NaiveDate::from_ymd(earliest.0, earliest.1, earliest.2), NaiveDate::from_ymd(latest.0, latest.1, latest.2))
+    }
+
+    #[test]
+    fn classify_every_shape() {
+        let excepted = [
+            (DatePeriodShape::Day(NaiveDate::from_ymd(1975, 4, 13)), "13 APR 1975"),
+            (DatePeriodShape::Month(NaiveDate::from_ymd(1968, 2, 1)), "FEB 1968"),
+            (DatePeriodShape::Month(NaiveDate::from_ymd(1967, 2, 1)), "FEB 1967"),
+            (DatePeriodShape::Year(1961), "1961"),
+            (DatePeriodShape::DayRange(NaiveDate::from_ymd(1946, 9, 26), NaiveDate::from_ymd(1946, 12, 7)), "26 SEP 1946 TO 07 DEC 1946"),
+            (DatePeriodShape::MonthRange(NaiveDate::from_ymd(1960, 3, 1), NaiveDate::from_ymd(1961, 1, 31)), "MAR 1960 TO JAN 1961"),
+            (DatePeriodShape::YearRange(1984, 1986), "1984 TO 1986"),
+            (DatePeriodShape::DayRange(NaiveDate::from_ymd(1970, 1, 1), NaiveDate::from_ymd(1970, 12, 30)), "01 JAN 1970 TO 30 DEC 1970"),
+        ];
+        let shapes = [
+            shape((1975, 4, 13), (1975, 4, 13)),
+            shape((1968, 2, 1), (1968, 2, 29)),
+            shape((1967, 2, 1), (1967, 2, 28)),
+            shape((1961, 1, 1), (1961, 12, 31)),
+            shape((1946, 9, 26), (1946, 12, 7)),
+            shape((1960, 3, 1), (1961, 1, 31)),
+            shape((1984, 1, 1), (1986, 12, 31)),
+            shape((1970, 1, 1), (1970, 12, 30)),
+        ];
+        for ((excepted, display), shape) in excepted.iter().zip(shapes) {
+            assert_eq!(*excepted, shape);
+            assert_eq!(*display, shape.to_ofac_string());
+        }
+        assert_eq!("CIRCA 1948", DatePeriodShape::Year(1948).to_ofac_string_approximate(true));
+        assert_eq!("1984 TO 1986", DatePeriodShape::YearRange(1984, 1986).to_ofac_string_approximate(true));
+    }
+
+    #[test]
+    fn bounds_of_invalid_dates() {
+        let date = |year: &str, month: &str, day: &str| Date {
+            year: year.to_owned(),
+            month: month.to_owned(),
+            day: day.to_owned(),
+        };
+        let period = DatePeriod {
+            start: Some(DatePeriodRange {
+                from: date("1970", "2", "30"),
+                to: date("1970", "2", "30"),
+                approximate: false,
+            }),
+            end: Some(DatePeriodRange {
+                from: date("1970", "12", "31"),
+                to: date("1970", "12", "31"),
+                approximate: true,
+            }),
+        };
+        assert_eq!(None, period.bounds());
+        assert_eq!(None, period.shape());
+        assert!(period.is_approximate());
+        assert_eq!(None, period.parse_from_to());
+        assert_eq!(None, DatePeriod { start: None, end: period.end }.parse_from_to());
+    }
+}
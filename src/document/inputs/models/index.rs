@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Positions of the elements of a Vec by key, built once the document is loaded
+///
+/// Callers search the Vec until the index is built. The index must be built again after every change of
+/// the Vec (see [`crate::document::OfacDocument::build_indexes`]), a stale index gives wrong positions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Index<K: Hash + Eq> {
+    /// Length of the Vec when the index was built, `None` until it is built
+    len: Option<usize>,
+    positions: HashMap<K, Vec<usize>>,
+}
+
+impl<K: Hash + Eq> Default for Index<K> {
+    fn default() -> Self {
+        Index { len: None, positions: HashMap::new() }
+    }
+}
+
+impl<K: Hash + Eq> Index<K> {
+    pub fn build<T>(values: &[T], key: impl Fn(&T) -> K) -> Index<K> {
+        let mut positions: HashMap<K, Vec<usize>> = HashMap::with_capacity(values.len());
+        for (position, value) in values.iter().enumerate() {
+            positions.entry(key(value)).or_default().push(position);
+        }
+        Index { len: Some(values.len()), positions }
+    }
+
+    /// Positions of `key` in the indexed Vec of `len` elements, `None` when the index is not built
+    pub fn get(&self, len: usize, key: &K) -> Option<&[usize]> {
+        let indexed_len = self.len?;
+        debug_assert_eq!(indexed_len, len, "the index must be built again after its Vec changes");
+        if indexed_len != len {
+            return None;
+        }
+        Some(self.positions.get(key).map_or(&[], |positions| &positions[..]))
+    }
+
+    /// First element of `values` with `key`, `values` are searched with `matches` when the index is not built for them
+    pub fn find<'a, T>(&self, values: &'a [T], key: &K, matches: impl Fn(&T) -> bool) -> Option<&'a T> {
+        match self.get(values.len(), key) {
+            Some(positions) => positions.first().map(|&position| &values[position]),
+            None => values.iter().find(|&value| matches(value)),
+        }
+    }
+
+    /// Every element of `values` with `key`, see [`Index::find`]
+    pub fn filter<'a, T>(&self, values: &'a [T], key: &K, matches: impl Fn(&T) -> bool) -> Vec<&'a T> {
+        match self.get(values.len(), key) {
+            Some(positions) => positions.iter().map(|&position| &values[position]).collect(),
+            None => values.iter().filter(|&value| matches(value)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod index {
+    use super::*;
+    use crate::document::models::areacode::AreaCode;
+    use crate::document::models::referencevaluesets::{ReferenceValue, ReferenceValueSet};
+    use crate::document::OfacDocumentReferences;
+
+    #[test]
+    fn index_is_only_used_once_built() {
+        let mut values = vec![(1, "a"), (2, "b"), (1, "c")];
+        assert_eq!(None, Index::<i32>::default().get(values.len(), &1));
+        assert_eq!(Some(&(2, "b")), Index::default().find(&values, &2, |value| value.0 == 2));
+        let index = Index::build(&values, |value| value.0);
+        assert_eq!(Some(&[0, 2][..]), index.get(values.len(), &1));
+        assert_eq!(Some(&[][..]), index.get(values.len(), &3));
+        values.push((3, "d"));
+        let index = Index::build(&values, |value| value.0);
+        assert_eq!(Some(&(3, "d")), index.find(&values, &3, |value| value.0 == 3));
+        assert_eq!(vec![&(1, "a"), &(1, "c")], index.filter(&values, &1, |value| value.0 == 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "built again")]
+    #[cfg(debug_assertions)]
+    fn stale_index_is_detected() {
+        let mut values = vec![(1, "a")];
+        let index = Index::build(&values, |value| value.0);
+        values.push((2, "b"));
+        index.get(values.len(), &2);
+    }
+
+    #[test]
+    fn references_are_found_after_the_index_is_built_again() {
+        let mut references = OfacDocumentReferences {
+            area_codes: vec![AreaCode { id: 11, name: "France".to_owned() }],
+            value_sets: vec![ReferenceValueSet {
+                name: "NamePartType".to_owned(),
+                values: vec![ReferenceValue { id: 1520, value: "First Name".to_owned() }],
+            }],
+            ..Default::default()
+        };
+        references.build_index();
+        assert_eq!(Some(11), references.area_code_by_name("France").map(|area| area.id));
+        assert_eq!(Some("First Name"), references.value_name("NamePartType", 1520));
+        assert_eq!(None, references.value_name("NamePartType", 7));
+        references.area_codes.push(AreaCode { id: 12, name: "Italy".to_owned() });
+        references.value_sets[0].values[0] = ReferenceValue { id: 7, value: "Last Name".to_owned() };
+        references.build_index();
+        assert_eq!(None, references.value_name("NamePartType", 1520));
+        assert_eq!(Some("Italy"), references.area_code(12).map(|area| area.name.as_str()));
+        assert_eq!(Some("Last Name"), references.value_name("NamePartType", 7));
+    }
+}
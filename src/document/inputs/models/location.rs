@@ -1,9 +1,23 @@
 use serde::Deserialize;
 
+use super::index::Index;
+
 #[derive(Debug, Deserialize, PartialEq, Default, Eq)]
 pub struct Locations {
     #[serde(rename = "Location")]
     pub locations: Vec<Location>,
+    #[serde(skip)]
+    by_id: Index<i32>,
+}
+
+impl Locations {
+    pub fn build_index(&mut self) {
+        self.by_id = Index::build(&self.locations, |location| location.id);
+    }
+
+    pub fn get(&self, id: i32) -> Option<&Location> {
+        self.by_id.find(&self.locations, &id, |location| location.id == id)
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -42,6 +56,8 @@ pub struct LocationPart {
 pub struct LocationPartValue {
     #[serde(rename = "Primary")]
     pub primary: bool,
+    #[serde(rename = "ScriptID", default)]
+    pub script_id: Option<i32>,
     #[serde(rename = "Value")]
     pub value: String,
 }
@@ -4,6 +4,7 @@ pub mod dateofissue;
 pub mod distinctparty;
 pub mod document;
 pub mod feature;
+pub mod index;
 pub mod location;
 pub mod profilerelationship;
 pub mod referencevaluesets;
@@ -1,21 +1,95 @@
-use serde::Deserialize;
+use std::fmt;
+
+use serde::de::{Error, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
 
 use super::areacode::AreaCodeValues;
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+/// Every ReferenceValueSet of the document
+///
+/// Sets used by the importer have their own model, the others are kept in `value_sets`.
+#[derive(Debug, PartialEq, Eq)]
 pub struct ReferenceValueSets {
-    #[serde(rename = "AreaCodeValues")]
     pub area_code_values: AreaCodeValues,
-    #[serde(rename = "DetailReferenceValues")]
     pub detail_reference_values: DetailReferenceValues,
-    #[serde(rename = "FeatureTypeValues")]
     pub feature_types: FeatureTypeValues,
-    #[serde(rename = "PartySubTypeValues")]
     pub party_sub_type_values: PartySubTypeValues,
-    #[serde(rename = "IDRegDocTypeValues")]
     pub reg_doc_types_values: IDRegDocTypeValues,
-    #[serde(rename = "ScriptValues")]
     pub script_values: ScriptValues,
+    pub value_sets: Vec<ReferenceValueSet>,
+}
+
+impl<'de> Deserialize<'de> for ReferenceValueSets {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ReferenceValueSetsVisitor)
+    }
+}
+
+struct ReferenceValueSetsVisitor;
+
+impl<'de> Visitor<'de> for ReferenceValueSetsVisitor {
+    type Value = ReferenceValueSets;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("ReferenceValueSets")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut area_code_values = None;
+        let mut detail_reference_values = None;
+        let mut feature_types = None;
+        let mut party_sub_type_values = None;
+        let mut reg_doc_types_values = None;
+        let mut script_values = None;
+        let mut value_sets = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            match &key[..] {
+                "AreaCodeValues" => area_code_values = Some(map.next_value()?),
+                "DetailReferenceValues" => detail_reference_values = Some(map.next_value()?),
+                "FeatureTypeValues" => feature_types = Some(map.next_value()?),
+                "PartySubTypeValues" => party_sub_type_values = Some(map.next_value()?),
+                "IDRegDocTypeValues" => reg_doc_types_values = Some(map.next_value()?),
+                "ScriptValues" => script_values = Some(map.next_value()?),
+                _ => {
+                    let values: ReferenceValues = map.next_value()?;
+                    value_sets.push(ReferenceValueSet {
+                        name: key.strip_suffix("Values").unwrap_or(&key).to_owned(),
+                        values: values.values,
+                    });
+                }
+            }
+        }
+        Ok(ReferenceValueSets {
+            area_code_values: area_code_values.ok_or_else(|| A::Error::missing_field("AreaCodeValues"))?,
+            detail_reference_values: detail_reference_values.ok_or_else(|| A::Error::missing_field("DetailReferenceValues"))?,
+            feature_types: feature_types.ok_or_else(|| A::Error::missing_field("FeatureTypeValues"))?,
+            party_sub_type_values: party_sub_type_values.ok_or_else(|| A::Error::missing_field("PartySubTypeValues"))?,
+            reg_doc_types_values: reg_doc_types_values.ok_or_else(|| A::Error::missing_field("IDRegDocTypeValues"))?,
+            script_values: script_values.ok_or_else(|| A::Error::missing_field("ScriptValues"))?,
+            value_sets,
+        })
+    }
+}
+
+/// A ReferenceValueSet without its own model, named after its values (e.g. `NamePartType`, `Reliability`)
+#[derive(Debug, PartialEq, Clone, Eq)]
+pub struct ReferenceValueSet {
+    pub name: String,
+    pub values: Vec<ReferenceValue>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default, Eq)]
+pub struct ReferenceValues {
+    #[serde(rename = "$value", default)]
+    pub values: Vec<ReferenceValue>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Eq)]
+pub struct ReferenceValue {
+    #[serde(rename = "ID")]
+    pub id: i32,
+    #[serde(rename = "$value", default)]
+    pub value: String,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Clone, Default, Eq)]
@@ -89,3 +163,54 @@ pub struct Script {
     #[serde(rename = "$value")]
     pub value: String,
 }
+
+#[cfg(test)]
+mod referencevaluesets {
+    use super::*;
+
+    #[test]
+    fn read_every_value_set() {
+        let xml = r#"<ReferenceValueSets>
+    <AliasTypeValues>
+      <AliasType ID="1400">A.K.A.</AliasType>
+      <AliasType ID="1403">Name</AliasType>
+    </AliasTypeValues>
+    <AreaCodeValues>
+      <AreaCode ID="1" CountryID="11" Description="Afghanistan">AF</AreaCode>
+    </AreaCodeValues>
+    <DetailReferenceValues>
+      <DetailReference ID="91526">Male</DetailReference>
+    </DetailReferenceValues>
+    <ExRefValues />
+    <FeatureTypeValues>
+      <FeatureType ID="8" FeatureTypeGroupID="1">Birthdate</FeatureType>
+    </FeatureTypeValues>
+    <IDRegDocTypeValues>
+      <IDRegDocType ID="1571">Passport</IDRegDocType>
+    </IDRegDocTypeValues>
+    <LegalBasisValues>
+      <LegalBasis ID="1" LegalBasisShortRef="EO13224" LegalBasisTypeID="1" SanctionsProgramID="1">Executive Order 13224 (Terrorism)</LegalBasis>
+    </LegalBasisValues>
+    <PartySubTypeValues>
+      <PartySubType ID="4" PartyTypeID="1">Unknown</PartySubType>
+    </PartySubTypeValues>
+    <ScriptValues>
+      <Script ID="215" ScriptCode="Latn">Latin</Script>
+    </ScriptValues>
+  </ReferenceValueSets>"#;
+        let references: ReferenceValueSets = quick_xml::de::from_str(xml).unwrap();
+        let excepted = vec![
+            ReferenceValueSet {
+                name: "AliasType".to_owned(),
+                values: vec![ReferenceValue { id: 1400, value: "A.K.A.".to_owned() }, ReferenceValue { id: 1403, value: "Name".to_owned() }],
+            },
+            ReferenceValueSet { name: "ExRef".to_owned(), values: vec![] },
+            ReferenceValueSet {
+                name: "LegalBasis".to_owned(),
+                values: vec![ReferenceValue { id: 1, value: "Executive Order 13224 (Terrorism)".to_owned() }],
+            },
+        ];
+        assert_eq!(excepted, references.value_sets);
+        assert_eq!(1571, references.reg_doc_types_values.reg_doc_types[0].id);
+    }
+}
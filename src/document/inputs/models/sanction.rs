@@ -1,11 +1,25 @@
 use serde::Deserialize;
 
 use super::feature::Date;
+use super::index::Index;
 
 #[derive(Debug, Deserialize, PartialEq, Eq, Default)]
 pub struct SanctionsEntries {
     #[serde(rename = "SanctionsEntry")]
     pub entries: Vec<SanctionsEntry>,
+    #[serde(skip)]
+    by_profile: Index<i32>,
+}
+
+impl SanctionsEntries {
+    pub fn build_index(&mut self) {
+        self.by_profile = Index::build(&self.entries, |entry| entry.profile_id);
+    }
+
+    /// SanctionsEntry of the profile (i.e the FixedRef of the DistinctParty)
+    pub fn by_profile(&self, profile_id: i32) -> Option<&SanctionsEntry> {
+        self.by_profile.find(&self.entries, &profile_id, |entry| entry.profile_id == profile_id)
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -20,8 +34,15 @@ pub struct SanctionsEntry {
     pub measures: Vec<SanctionsMeasure>,
 }
 
+/// Designation, amendment or delisting of the entry
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 pub struct EntryEvent {
+    #[serde(rename = "ID")]
+    pub id: i32,
+    #[serde(rename = "EntryEventTypeID")]
+    pub event_type_id: i32,
+    #[serde(rename = "LegalBasisID")]
+    pub legal_basis_id: Option<i32>,
     #[serde(rename = "Date")]
     pub date: Date,
 }
@@ -30,6 +51,8 @@ pub struct EntryEvent {
 pub struct SanctionsMeasure {
     #[serde(rename = "ID")]
     pub id: i32,
+    #[serde(rename = "SanctionsTypeID")]
+    pub sanctions_type_id: i32,
     #[serde(rename = "Comment")]
     pub program: Option<String>,
 }
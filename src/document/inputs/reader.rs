@@ -0,0 +1,474 @@
+use std::io::BufRead;
+use std::marker::PhantomData;
+
+use quick_xml::de::Deserializer;
+use quick_xml::events::Event;
+use quick_xml::{DeError, Reader};
+use serde::de::{DeserializeOwned, DeserializeSeed, Error as _, IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+use super::models::dateofissue::DateOfIssue;
+use super::models::distinctparty::DistinctParty;
+use super::models::document::Document;
+use super::models::location::Location;
+use super::models::profilerelationship::ProfileRelationship;
+use super::models::referencevaluesets::ReferenceValueSets;
+use super::models::sanction::SanctionsEntry;
+
+/// Receives the content of an Ofac advanced xml document while it is being read
+///
+/// Every item is handed over as soon as it has been deserialized, so an implementation
+/// only keeps in memory what it decides to keep.
+pub trait OfacDocumentVisitor {
+    fn visit_date_of_issue(&mut self, _date_of_issue: DateOfIssue) {}
+    fn visit_references(&mut self, _references: ReferenceValueSets) {}
+    fn visit_location(&mut self, _location: Location) {}
+    fn visit_document(&mut self, _document: Document) {}
+    fn visit_distinct_party(&mut self, _party: DistinctParty) {}
+    fn visit_profile_relationship(&mut self, _relationship: ProfileRelationship) {}
+    fn visit_sanctions_entry(&mut self, _entry: SanctionsEntry) {}
+
+    /// When false, the DistinctParties section is skipped without being deserialized
+    fn wants_distinct_parties(&self) -> bool {
+        true
+    }
+
+    /// When false, every section but the DistinctParties is skipped without being deserialized
+    fn wants_other_sections(&self) -> bool {
+        true
+    }
+
+    /// When true, the document is not read any further (e.g. nobody receives the items anymore)
+    fn is_stopped(&self) -> bool {
+        false
+    }
+}
+
+/// Error returned by the items of a section once the visitor is stopped, [`read_document`] then returns `Ok`
+const STOPPED: &str = "Reading stopped by the visitor";
+
+/// Reads an Ofac advanced xml document in a single pass
+///
+/// * `reader` - Any buffered reader over the xml content
+/// * `visitor` - Receives each section item in document order
+pub fn read_document<R: BufRead, V: OfacDocumentVisitor>(reader: R, visitor: &mut V) -> Result<(), DeError> {
+    let mut deserializer = Deserializer::from_reader(reader);
+    match DocumentSeed(visitor).deserialize(&mut deserializer) {
+        Err(DeError::Custom(message)) if message == STOPPED => Ok(()),
+        result => result,
+    }
+}
+
+/// Reads the DateOfIssue of an Ofac advanced xml document, the sections after it are not read
+pub fn read_date_of_issue<R: BufRead>(reader: R) -> Result<Option<DateOfIssue>, quick_xml::Error> {
+    let mut reader = Reader::from_reader(reader);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    let mut date_of_issue: Option<DateOfIssue> = None;
+    let mut part = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(element) if element.local_name().as_ref() == b"DateOfIssue" => date_of_issue = Some(DateOfIssue::default()),
+            Event::Start(element) => part = element.local_name().as_ref().to_vec(),
+            Event::Text(text) => {
+                if let Some(date_of_issue) = date_of_issue.as_mut() {
+                    let value = text.unescape()?.into_owned();
+                    match &part[..] {
+                        b"Year" => date_of_issue.year = value,
+                        b"Month" => date_of_issue.month = value,
+                        b"Day" => date_of_issue.day = value,
+                        _ => {}
+                    }
+                }
+            }
+            Event::End(element) if element.local_name().as_ref() == b"DateOfIssue" => return Ok(date_of_issue),
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Reads the attribute `name` of the root element of an xml document, the elements after it are not read
+pub fn read_root_attribute<R: BufRead>(reader: R, name: &str) -> Result<Option<String>, quick_xml::Error> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(element) | Event::Empty(element) => {
+                return match element.try_get_attribute(name)? {
+                    Some(attribute) => Ok(Some(attribute.unescape_value()?.into_owned())),
+                    None => Ok(None),
+                }
+            }
+            Event::Eof => return Ok(None),
+            _ => buf.clear(),
+        }
+    }
+}
+
+struct DocumentSeed<'a, V>(&'a mut V);
+
+impl<'de, 'a, V: OfacDocumentVisitor> DeserializeSeed<'de> for DocumentSeed<'a, V> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct("Sanctions", &[], self)
+    }
+}
+
+impl<'de, 'a, V: OfacDocumentVisitor> Visitor<'de> for DocumentSeed<'a, V> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an Ofac advanced xml document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let visitor = self.0;
+        while let Some(key) = map.next_key::<String>()? {
+            let others = visitor.wants_other_sections();
+            match &key[..] {
+                "DateOfIssue" if others => visitor.visit_date_of_issue(map.next_value()?),
+                "ReferenceValueSets" if others => visitor.visit_references(map.next_value()?),
+                "Locations" if others => map.next_value_seed(SectionSeed::new(&["Location"], |location| {
+                    visitor.visit_location(location);
+                    !visitor.is_stopped()
+                }))?,
+                "IDRegDocuments" if others => map.next_value_seed(SectionSeed::new(&["IDRegDocument"], |document| {
+                    visitor.visit_document(document);
+                    !visitor.is_stopped()
+                }))?,
+                "DistinctParties" if visitor.wants_distinct_parties() => map.next_value_seed(SectionSeed::new(&["DistinctParty"], |party| {
+                    visitor.visit_distinct_party(party);
+                    !visitor.is_stopped()
+                }))?,
+                "ProfileRelationships" if others => map.next_value_seed(SectionSeed::new(&["ProfileRelationship"], |relationship| {
+                    visitor.visit_profile_relationship(relationship);
+                    !visitor.is_stopped()
+                }))?,
+                "SanctionsEntries" if others => map.next_value_seed(SectionSeed::new(&["SanctionsEntry"], |entry| {
+                    visitor.visit_sanctions_entry(entry);
+                    !visitor.is_stopped()
+                }))?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+            if visitor.is_stopped() {
+                return Err(A::Error::custom(STOPPED));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A section holding a list of items (e.g. DistinctParties holding DistinctParty elements)
+///
+/// Each item is deserialized on its own and given to `consumer`, the section itself is never stored. The reading
+/// stops when `consumer` returns false.
+struct SectionSeed<T, F> {
+    item: &'static [&'static str],
+    consumer: F,
+    marker: PhantomData<T>,
+}
+
+impl<T, F: FnMut(T) -> bool> SectionSeed<T, F> {
+    fn new(item: &'static [&'static str], consumer: F) -> Self {
+        SectionSeed {
+            item,
+            consumer,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: DeserializeOwned, F: FnMut(T) -> bool> DeserializeSeed<'de> for SectionSeed<T, F> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct("Section", self.item, self)
+    }
+}
+
+impl<'de, T: DeserializeOwned, F: FnMut(T) -> bool> Visitor<'de> for SectionSeed<T, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a list of {}", self.item[0])
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<Self::Value, A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == self.item[0] {
+                map.next_value_seed(ItemsSeed(&mut self.consumer, PhantomData))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct ItemsSeed<'a, T, F>(&'a mut F, PhantomData<T>);
+
+impl<'de, 'a, T: DeserializeOwned, F: FnMut(T) -> bool> DeserializeSeed<'de> for ItemsSeed<'a, T, F> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a, T: DeserializeOwned, F: FnMut(T) -> bool> Visitor<'de> for ItemsSeed<'a, T, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a sequence of items")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while let Some(item) = seq.next_element::<T>()? {
+            if !(self.0)(item) {
+                return Err(A::Error::custom(STOPPED));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod reader {
+    use super::*;
+
+    const DOCUMENT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<Sanctions xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns="http://www.un.org/sanctions/1.0">
+  <DateOfIssue>
+    <Year>2022</Year>
+    <Month>11</Month>
+    <Day>8</Day>
+  </DateOfIssue>
+  <ReferenceValueSets>
+    <AliasTypeValues>
+      <AliasType ID="1400">A.K.A.</AliasType>
+    </AliasTypeValues>
+    <AreaCodeValues>
+      <AreaCode ID="1" CountryID="11" Description="Afghanistan">AF</AreaCode>
+    </AreaCodeValues>
+    <DetailReferenceValues>
+      <DetailReference ID="91526">Male</DetailReference>
+    </DetailReferenceValues>
+    <FeatureTypeValues>
+      <FeatureType ID="8" FeatureTypeGroupID="1">Birthdate</FeatureType>
+    </FeatureTypeValues>
+    <IDRegDocTypeValues>
+      <IDRegDocType ID="1571">Passport</IDRegDocType>
+    </IDRegDocTypeValues>
+    <PartySubTypeValues>
+      <PartySubType ID="4" PartyTypeID="1">Unknown</PartySubType>
+    </PartySubTypeValues>
+    <ScriptValues>
+      <Script ID="215" ScriptCode="Latn">Latin</Script>
+    </ScriptValues>
+  </ReferenceValueSets>
+  <Locations>
+    <Location ID="1">
+      <LocationCountry CountryID="11" />
+    </Location>
+    <Location ID="2">
+      <LocationCountry CountryID="11" />
+    </Location>
+  </Locations>
+  <IDRegDocuments>
+    <IDRegDocument ID="10" IDRegDocTypeID="1571" IdentityID="100">
+      <IDRegistrationNo>123</IDRegistrationNo>
+    </IDRegDocument>
+  </IDRegDocuments>
+  <DistinctParties>
+    <DistinctParty FixedRef="36">
+      <Profile ID="36" PartySubTypeID="4">
+        <Identity ID="100" FixedRef="36" Primary="true" False="false">
+          <Alias FixedRef="36" AliasTypeID="1403" Primary="true" LowQuality="false">
+            <DocumentedName ID="1000" FixedRef="36" DocNameStatusID="1">
+              <DocumentedNamePart>
+                <NamePartValue NamePartGroupID="20" ScriptID="215" ScriptStatusID="1" Acronym="false">AEROCARIBBEAN AIRLINES</NamePartValue>
+              </DocumentedNamePart>
+            </DocumentedName>
+          </Alias>
+          <NamePartGroups>
+            <MasterNamePartGroup>
+              <NamePartGroup ID="20" NamePartTypeID="1525" />
+            </MasterNamePartGroup>
+          </NamePartGroups>
+        </Identity>
+      </Profile>
+    </DistinctParty>
+    <DistinctParty FixedRef="173">
+      <Profile ID="173" PartySubTypeID="4">
+        <Identity ID="101" FixedRef="173" Primary="true" False="false">
+          <Alias FixedRef="173" AliasTypeID="1403" Primary="true" LowQuality="false">
+            <DocumentedName ID="1001" FixedRef="173" DocNameStatusID="1">
+              <DocumentedNamePart>
+                <NamePartValue NamePartGroupID="21" ScriptID="215" ScriptStatusID="1" Acronym="false">ANGLO-CARIBBEAN CO., LTD.</NamePartValue>
+              </DocumentedNamePart>
+            </DocumentedName>
+          </Alias>
+          <NamePartGroups>
+            <MasterNamePartGroup>
+              <NamePartGroup ID="21" NamePartTypeID="1525" />
+            </MasterNamePartGroup>
+          </NamePartGroups>
+        </Identity>
+      </Profile>
+    </DistinctParty>
+  </DistinctParties>
+  <ProfileRelationships>
+    <ProfileRelationship ID="1" From-ProfileID="36" To-ProfileID="173" RelationTypeID="1555" RelationQualityID="1" Former="false" SanctionsEntryID="36" />
+  </ProfileRelationships>
+  <SanctionsEntries>
+    <SanctionsEntry ID="36" ProfileID="36" ListID="1550">
+      <EntryEvent ID="36" EntryEventTypeID="1" LegalBasisID="1">
+        <Date CalendarTypeID="1">
+          <Year>1986</Year>
+          <Month>12</Month>
+          <Day>10</Day>
+        </Date>
+      </EntryEvent>
+      <SanctionsMeasure ID="36" SanctionsTypeID="1">
+        <Comment>CUBA</Comment>
+      </SanctionsMeasure>
+    </SanctionsEntry>
+  </SanctionsEntries>
+  <SanctionsEntryLinks />
+</Sanctions>"#;
+
+    #[derive(Default)]
+    struct Counter {
+        date_of_issue: Option<DateOfIssue>,
+        script_values: usize,
+        locations: Vec<i32>,
+        documents: Vec<i32>,
+        parties: Vec<i32>,
+        relationships: Vec<i32>,
+        entries: Vec<i32>,
+        skip_parties: bool,
+        skip_others: bool,
+        /// Stop once this number of DistinctParties is read
+        stop_after: Option<usize>,
+    }
+
+    impl OfacDocumentVisitor for Counter {
+        fn visit_date_of_issue(&mut self, date_of_issue: DateOfIssue) {
+            self.date_of_issue = Some(date_of_issue);
+        }
+        fn visit_references(&mut self, references: ReferenceValueSets) {
+            self.script_values = references.script_values.scripts.len();
+        }
+        fn visit_location(&mut self, location: Location) {
+            self.locations.push(location.id);
+        }
+        fn visit_document(&mut self, document: Document) {
+            self.documents.push(document.id);
+        }
+        fn visit_distinct_party(&mut self, party: DistinctParty) {
+            self.parties.push(party.fixed_ref);
+        }
+        fn visit_profile_relationship(&mut self, relationship: ProfileRelationship) {
+            self.relationships.push(relationship.id);
+        }
+        fn visit_sanctions_entry(&mut self, entry: SanctionsEntry) {
+            self.entries.push(entry.id);
+        }
+        fn wants_distinct_parties(&self) -> bool {
+            !self.skip_parties
+        }
+        fn wants_other_sections(&self) -> bool {
+            !self.skip_others
+        }
+        fn is_stopped(&self) -> bool {
+            self.stop_after.is_some_and(|count| self.parties.len() >= count)
+        }
+    }
+
+    #[test]
+    fn read_every_section_in_one_pass() {
+        let mut counter = Counter::default();
+        read_document(DOCUMENT.as_bytes(), &mut counter).unwrap();
+        assert_eq!(
+            Some(DateOfIssue {
+                year: "2022".to_owned(),
+                month: "11".to_owned(),
+                day: "8".to_owned()
+            }),
+            counter.date_of_issue
+        );
+        assert_eq!(1, counter.script_values);
+        assert_eq!(vec![1, 2], counter.locations);
+        assert_eq!(vec![10], counter.documents);
+        assert_eq!(vec![36, 173], counter.parties);
+        assert_eq!(vec![1], counter.relationships);
+        assert_eq!(vec![36], counter.entries);
+    }
+
+    #[test]
+    fn read_only_the_date_of_issue() {
+        let excepted = DateOfIssue {
+            year: "2022".to_owned(),
+            month: "11".to_owned(),
+            day: "8".to_owned(),
+        };
+        let truncated = &DOCUMENT[..DOCUMENT.find("<DistinctParties>").unwrap()];
+        assert_eq!(Some(excepted), read_date_of_issue(truncated.as_bytes()).unwrap());
+        assert_eq!(None, read_date_of_issue("<Sanctions><Locations /></Sanctions>".as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn read_only_the_root_attribute() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?><export generationDate="2022-11-08T18:00:01.360+01:00"><sanctionEntity>"#;
+        assert_eq!(Some("2022-11-08T18:00:01.360+01:00".to_owned()), read_root_attribute(xml.as_bytes(), "generationDate").unwrap());
+        assert_eq!(None, read_root_attribute(xml.as_bytes(), "dateGenerated").unwrap());
+    }
+
+    #[test]
+    fn skip_distinct_parties() {
+        let mut counter = Counter {
+            skip_parties: true,
+            ..Default::default()
+        };
+        read_document(DOCUMENT.as_bytes(), &mut counter).unwrap();
+        assert!(counter.parties.is_empty());
+        assert_eq!(vec![36], counter.entries);
+    }
+
+    #[test]
+    fn skip_other_sections() {
+        let mut counter = Counter {
+            skip_others: true,
+            ..Default::default()
+        };
+        read_document(DOCUMENT.as_bytes(), &mut counter).unwrap();
+        assert_eq!(None, counter.date_of_issue);
+        assert_eq!(0, counter.script_values);
+        assert!(counter.locations.is_empty() && counter.documents.is_empty() && counter.relationships.is_empty() && counter.entries.is_empty());
+        assert_eq!(vec![36, 173], counter.parties);
+    }
+
+    #[test]
+    fn stop_reading_once_stopped() {
+        let mut counter = Counter {
+            stop_after: Some(1),
+            ..Default::default()
+        };
+        // The rest of the document is not read, it can not fail
+        let truncated = &DOCUMENT[..DOCUMENT.find("<ProfileRelationships>").unwrap()];
+        read_document(truncated.as_bytes(), &mut counter).unwrap();
+        assert_eq!(vec![36], counter.parties);
+        assert!(counter.entries.is_empty());
+    }
+
+    #[test]
+    fn report_malformed_document() {
+        let mut counter = Counter::default();
+        let truncated = &DOCUMENT[..DOCUMENT.find("<ProfileRelationships>").unwrap()];
+        assert!(read_document(truncated.as_bytes(), &mut counter).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+use zip::{CompressionMethod, ZipArchive};
+
+/// A reader over the decompressed xml content
+pub type SourceReader = Box<dyn BufRead + Send>;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum InputFormat {
+    Xml,
+    Gzip,
+    Zip,
+}
+
+impl InputFormat {
+    /// Guess the format of a file from its extension, anything else than `.gz` or `.zip` is read as xml
+    pub fn from_path<P: AsRef<Path>>(path: P) -> InputFormat {
+        match path.as_ref().extension().map(|ext| ext.to_string_lossy().to_lowercase()).as_deref() {
+            Some("gz") => InputFormat::Gzip,
+            Some("zip") => InputFormat::Zip,
+            _ => InputFormat::Xml,
+        }
+    }
+
+    /// Extension appended to archived copies of this format
+    pub fn archive_extension(&self) -> &'static str {
+        match self {
+            InputFormat::Xml | InputFormat::Gzip => "xml.gz",
+            InputFormat::Zip => "zip",
+        }
+    }
+}
+
+/// A file holding an Ofac document, either plain or compressed
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DocumentSource {
+    pub path: PathBuf,
+    pub format: InputFormat,
+}
+
+impl DocumentSource {
+    pub fn new<P: AsRef<Path>>(path: P) -> DocumentSource {
+        DocumentSource {
+            path: path.as_ref().to_owned(),
+            format: InputFormat::from_path(&path),
+        }
+    }
+
+    /// Find the document named `stem` in `folder`
+    ///
+    /// `stem.xml`, `stem.xml.gz`, `stem.gz`, `stem.xml.zip` and `stem.zip` are looked up (case insensitive),
+    /// when several of them exist the most recently modified one is used.
+    pub fn find<P: AsRef<Path>>(folder: P, stem: &str) -> Result<DocumentSource, Box<dyn Error>> {
+        DocumentSource::find_any(folder, stem, &["xml", "xml.gz", "gz", "xml.zip", "zip"])
+    }
+
+    /// Find the csv file named `stem` in `folder`, either plain or gzipped
+    pub fn find_csv<P: AsRef<Path>>(folder: P, stem: &str) -> Result<DocumentSource, Box<dyn Error>> {
+        DocumentSource::find_any(folder, stem, &["csv", "csv.gz"])
+    }
+
+    fn find_any<P: AsRef<Path>>(folder: P, stem: &str, extensions: &[&str]) -> Result<DocumentSource, Box<dyn Error>> {
+        let folder = folder.as_ref();
+        let candidates: Vec<String> = extensions.iter().map(|ext| format!("{}.{}", stem, ext).to_lowercase()).collect();
+        let entries = match std::fs::read_dir(folder) {
+            Ok(entries) => entries,
+            Err(err) => return Err(format!("Ofac document not found in {}, {}", folder.to_string_lossy(), err).into()),
+        };
+        let mut found = None;
+        for entry in entries {
+            let entry = entry?;
+            if !candidates.contains(&entry.file_name().to_string_lossy().to_lowercase()) || !entry.file_type()?.is_file() {
+                continue;
+            }
+            let modified = entry.metadata()?.modified()?;
+            match &found {
+                Some((newest, _)) if *newest >= modified => {}
+                _ => found = Some((modified, entry.path())),
+            }
+        }
+        match found {
+            Some((_, path)) => Ok(DocumentSource::new(path)),
+            None => Err(format!("Ofac document {} not found in {}", stem, folder.to_string_lossy()).into()),
+        }
+    }
+
+    /// Open the source, decompressing it on the fly
+    ///
+    /// For zip files, the first `.xml` entry is read.
+    pub fn open(&self) -> Result<SourceReader, Box<dyn Error>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) => return Err(format!("Ofac document not found in {}, {}", self.path.to_string_lossy(), err).into()),
+        };
+        match self.format {
+            InputFormat::Xml => Ok(Box::new(BufReader::new(file))),
+            InputFormat::Gzip => Ok(Box::new(BufReader::new(MultiGzDecoder::new(file)))),
+            InputFormat::Zip => open_zip_entry(file),
+        }
+    }
+}
+
+/// The archive entry is read straight from the file so the returned reader doesn't borrow the archive
+fn open_zip_entry(file: File) -> Result<SourceReader, Box<dyn Error>> {
+    let mut archive = ZipArchive::new(file)?;
+    let name = match archive.file_names().find(|name| name.to_lowercase().ends_with(".xml")) {
+        Some(name) => name.to_owned(),
+        None => return Err("No xml file found in zip archive".into()),
+    };
+    let (start, size, compression) = {
+        let entry = archive.by_name(&name)?;
+        (entry.data_start(), entry.compressed_size(), entry.compression())
+    };
+    let mut file = archive.into_inner();
+    file.seek(SeekFrom::Start(start))?;
+    let data = file.take(size);
+    match compression {
+        CompressionMethod::Stored => Ok(Box::new(BufReader::new(data))),
+        CompressionMethod::Deflated => Ok(Box::new(BufReader::new(DeflateDecoder::new(data)))),
+        method => Err(format!("Zip compression {} is not supported", method).into()),
+    }
+}
+
+#[cfg(test)]
+mod source {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    const CONTENT: &str = "<Sanctions><DateOfIssue><Year>2022</Year></DateOfIssue></Sanctions>";
+
+    fn test_folder(name: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("ofacxmlagent_source_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    fn read_all(source: &DocumentSource) -> String {
+        let mut content = String::new();
+        source.open().unwrap().read_to_string(&mut content).unwrap();
+        content
+    }
+
+    #[test]
+    fn detect_format_from_extension() {
+        assert_eq!(InputFormat::Xml, InputFormat::from_path("data/sdn_advanced.xml"));
+        assert_eq!(InputFormat::Gzip, InputFormat::from_path("data/archive/sdn_advanced20221108_101010.xml.gz"));
+        assert_eq!(InputFormat::Zip, InputFormat::from_path("data/SDN_ADVANCED.ZIP"));
+    }
+
+    #[test]
+    fn read_gzip_document() {
+        let folder = test_folder("gzip");
+        let mut encoder = GzEncoder::new(File::create(folder.join("sdn_advanced.xml.gz")).unwrap(), Compression::default());
+        encoder.write_all(CONTENT.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let source = DocumentSource::find(&folder, "sdn_advanced").unwrap();
+        assert_eq!(InputFormat::Gzip, source.format);
+        assert_eq!(CONTENT, read_all(&source));
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn read_zip_document() {
+        let folder = test_folder("zip");
+        for (name, method) in [("SDN_ADVANCED.ZIP", CompressionMethod::Deflated), ("cons_advanced.zip", CompressionMethod::Stored)] {
+            let mut writer = ZipWriter::new(File::create(folder.join(name)).unwrap());
+            writer.start_file("readme.txt", FileOptions::default()).unwrap();
+            writer.write_all(b"not the document").unwrap();
+            writer.start_file("SDN_ADVANCED.XML", FileOptions::default().compression_method(method)).unwrap();
+            writer.write_all(CONTENT.as_bytes()).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let source = DocumentSource::find(&folder, "sdn_advanced").unwrap();
+        assert_eq!(InputFormat::Zip, source.format);
+        assert_eq!(CONTENT, read_all(&source));
+        assert_eq!(CONTENT, read_all(&DocumentSource::find(&folder, "cons_advanced").unwrap()));
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn missing_document() {
+        let folder = test_folder("missing");
+        std::fs::write(folder.join("sdn.xml"), CONTENT).unwrap();
+        assert!(DocumentSource::find(&folder, "sdn_advanced").is_err());
+        assert_eq!(CONTENT, read_all(&DocumentSource::find(&folder, "sdn").unwrap()));
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,513 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{BufRead, Read};
+
+use log::{error, warn};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+enum Kind {
+    Int,
+    Bool,
+}
+
+/// Attributes the importer can not do without, built from the Ofac advanced XSD
+const REQUIRED_ATTRIBUTES: &[(&str, &[(&str, Kind)])] = &[
+    ("Location", &[("ID", Kind::Int)]),
+    ("LocationCountry", &[("CountryID", Kind::Int)]),
+    ("LocationPart", &[("LocPartTypeID", Kind::Int)]),
+    ("LocationPartValue", &[("Primary", Kind::Bool)]),
+    ("IDRegDocument", &[("ID", Kind::Int), ("IDRegDocTypeID", Kind::Int), ("IdentityID", Kind::Int)]),
+    ("DocumentDate", &[("IDRegDocDateTypeID", Kind::Int)]),
+    ("DistinctParty", &[("FixedRef", Kind::Int)]),
+    ("Profile", &[("PartySubTypeID", Kind::Int)]),
+    ("Identity", &[("ID", Kind::Int)]),
+    ("Alias", &[("AliasTypeID", Kind::Int), ("Primary", Kind::Bool), ("LowQuality", Kind::Bool)]),
+    ("DocumentedName", &[("ID", Kind::Int), ("DocNameStatusID", Kind::Int)]),
+    ("NamePartValue", &[("ScriptID", Kind::Int), ("NamePartGroupID", Kind::Int)]),
+    ("NamePartGroup", &[("ID", Kind::Int), ("NamePartTypeID", Kind::Int)]),
+    ("Feature", &[("ID", Kind::Int), ("FeatureTypeID", Kind::Int)]),
+    ("FeatureVersion", &[("ID", Kind::Int), ("ReliabilityID", Kind::Int)]),
+    ("VersionLocation", &[("LocationID", Kind::Int)]),
+    ("Start", &[("Approximate", Kind::Bool)]),
+    ("End", &[("Approximate", Kind::Bool)]),
+    ("ProfileRelationship", &[("ID", Kind::Int), ("From-ProfileID", Kind::Int), ("To-ProfileID", Kind::Int), ("RelationTypeID", Kind::Int), ("SanctionsEntryID", Kind::Int)]),
+    ("SanctionsEntry", &[("ID", Kind::Int), ("ProfileID", Kind::Int)]),
+    ("EntryEvent", &[("ID", Kind::Int), ("EntryEventTypeID", Kind::Int)]),
+    ("SanctionsMeasure", &[("ID", Kind::Int), ("SanctionsTypeID", Kind::Int)]),
+];
+
+/// Children the importer can not do without
+const REQUIRED_CHILDREN: &[(&str, &[&str])] = &[
+    ("DateOfIssue", &["Year", "Month", "Day"]),
+    ("LocationPart", &["LocationPartValue"]),
+    ("LocationPartValue", &["Value"]),
+    ("DocumentDate", &["DatePeriod"]),
+    ("DistinctParty", &["Profile"]),
+    ("Profile", &["Identity"]),
+    ("Identity", &["Alias", "NamePartGroups"]),
+    ("Alias", &["DocumentedName"]),
+    ("DocumentedName", &["DocumentedNamePart"]),
+    ("DocumentedNamePart", &["NamePartValue"]),
+    ("NamePartGroups", &["MasterNamePartGroup"]),
+    ("MasterNamePartGroup", &["NamePartGroup"]),
+    ("Feature", &["FeatureVersion"]),
+    ("Start", &["From", "To"]),
+    ("End", &["From", "To"]),
+    ("From", &["Year", "Month", "Day"]),
+    ("To", &["Year", "Month", "Day"]),
+    ("Date", &["Year", "Month", "Day"]),
+    ("SanctionsEntry", &["EntryEvent", "SanctionsMeasure"]),
+    ("EntryEvent", &["Date"]),
+];
+
+/// Sections whose content is checked, reference values are read as they come
+const CHECKED_SECTIONS: &[&str] = &["DateOfIssue", "Locations", "IDRegDocuments", "DistinctParties", "ProfileRelationships", "SanctionsEntries"];
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Severity {
+    /// The document can not be imported
+    Blocking,
+    /// The document can be imported, some data may be missing
+    Warning,
+}
+
+/// A rule the document does not follow
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Violation {
+    pub severity: Severity,
+    /// e.g. `/Sanctions/DistinctParties/DistinctParty/Profile`
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub fixed_ref: Option<i32>,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} line {} column {}", self.path, self.line, self.column)?;
+        if let Some(fixed_ref) = self.fixed_ref {
+            write!(f, " (FixedRef {})", fixed_ref)?;
+        }
+        write!(f, " : {}", self.message)
+    }
+}
+
+/// Violations found in a document, see [`validate`]
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ValidationReport {
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn has_blocking(&self) -> bool {
+        self.violations.iter().any(|violation| violation.severity == Severity::Blocking)
+    }
+
+    pub fn log(&self) {
+        for violation in self.violations.iter() {
+            match violation.severity {
+                Severity::Blocking => error!("Validation error at {}", violation),
+                Severity::Warning => warn!("Validation warning at {}", violation),
+            }
+        }
+    }
+}
+
+/// Keeps the offset of every line read, so a position in the document can be given as line and column
+struct LineCounter<R> {
+    inner: R,
+    offset: usize,
+    line_starts: Vec<usize>,
+}
+
+impl<R> LineCounter<R> {
+    fn line_column(&self, position: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|start| *start <= position);
+        (line, position - self.line_starts[line - 1] + 1)
+    }
+}
+
+impl<R: Read> Read for LineCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        for (i, byte) in buf[..read].iter().enumerate() {
+            if *byte == b'\n' {
+                self.line_starts.push(self.offset + i + 1);
+            }
+        }
+        self.offset += read;
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for LineCounter<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Ok(buf) = self.inner.fill_buf() {
+            for (i, byte) in buf[..amt.min(buf.len())].iter().enumerate() {
+                if *byte == b'\n' {
+                    self.line_starts.push(self.offset + i + 1);
+                }
+            }
+        }
+        self.offset += amt;
+        self.inner.consume(amt);
+    }
+}
+
+/// An element being read
+struct Frame {
+    name: String,
+    fixed_ref: Option<i32>,
+    position: (usize, usize),
+    children: Vec<String>,
+    text: String,
+    /// A VersionDetail given as a DetailReferenceID
+    has_reference: bool,
+}
+
+/// Reference checks done once the whole document has been read
+#[derive(Default)]
+struct References {
+    location_ids: HashSet<i32>,
+    version_locations: Vec<(i32, Violation)>,
+    party_fixed_refs: Vec<(i32, Violation)>,
+    entry_profile_ids: HashMap<i32, Violation>,
+    name_part_groups: HashSet<i32>,
+    name_part_values: Vec<(i32, Violation)>,
+}
+
+struct Validator {
+    report: ValidationReport,
+    stack: Vec<Frame>,
+    references: References,
+}
+
+impl Validator {
+    fn path(&self) -> String {
+        self.stack.iter().fold(String::new(), |path, frame| format!("{}/{}", path, frame.name))
+    }
+
+    fn violation(&self, severity: Severity, message: String) -> Violation {
+        let frame = self.stack.last();
+        Violation {
+            severity,
+            path: self.path(),
+            line: frame.map(|frame| frame.position.0).unwrap_or(1),
+            column: frame.map(|frame| frame.position.1).unwrap_or(1),
+            fixed_ref: self.stack.iter().rev().find_map(|frame| frame.fixed_ref),
+            message,
+        }
+    }
+
+    fn push(&mut self, severity: Severity, message: String) {
+        let violation = self.violation(severity, message);
+        self.report.violations.push(violation);
+    }
+
+    fn is_checked(&self) -> bool {
+        self.stack.get(1).map(|section| CHECKED_SECTIONS.contains(&section.name.as_str())).unwrap_or(false)
+    }
+
+    fn start(&mut self, element: &BytesStart, position: (usize, usize)) {
+        let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children.push(name.clone());
+        }
+        let mut attributes = HashMap::new();
+        for attribute in element.attributes().flatten() {
+            let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned();
+            attributes.insert(key, String::from_utf8_lossy(&attribute.value).into_owned());
+        }
+        let int = |key: &str| attributes.get(key).and_then(|value| value.trim().parse::<i32>().ok());
+        let fixed_ref = match name.as_str() {
+            "SanctionsEntry" => int("ProfileID"),
+            _ => int("FixedRef"),
+        };
+        self.stack.push(Frame {
+            name: name.clone(),
+            fixed_ref,
+            position,
+            children: Vec::new(),
+            text: String::new(),
+            has_reference: attributes.contains_key("DetailReferenceID"),
+        });
+        if self.stack.len() == 1 {
+            if name != "Sanctions" {
+                self.push(Severity::Blocking, format!("Root element must be Sanctions, found {}", name));
+            }
+            return;
+        }
+        if !self.is_checked() {
+            return;
+        }
+        for (_, required) in REQUIRED_ATTRIBUTES.iter().filter(|(element, _)| *element == name) {
+            for (key, kind) in required.iter() {
+                let valid = match (attributes.get(*key).map(|value| value.trim()), kind) {
+                    (None, _) => {
+                        self.push(Severity::Blocking, format!("Missing attribute {}", key));
+                        continue;
+                    }
+                    (Some(value), Kind::Int) => value.parse::<i32>().is_ok(),
+                    (Some(value), Kind::Bool) => ["true", "false", "1", "0"].contains(&value),
+                };
+                if !valid {
+                    self.push(Severity::Blocking, format!("Attribute {}=\"{}\" is not a valid {:?}", key, attributes[*key], kind));
+                }
+            }
+        }
+        match name.as_str() {
+            "Location" if self.stack.len() == 3 => self.references.location_ids.extend(int("ID")),
+            "VersionLocation" => {
+                if let Some(id) = int("LocationID") {
+                    let violation = self.violation(Severity::Blocking, format!("Location {} not found", id));
+                    self.references.version_locations.push((id, violation));
+                }
+            }
+            "DistinctParty" => {
+                self.references.name_part_groups.clear();
+                if let Some(fixed_ref) = fixed_ref {
+                    let violation = self.violation(Severity::Blocking, "No SanctionsEntry for this DistinctParty".to_owned());
+                    self.references.party_fixed_refs.push((fixed_ref, violation));
+                }
+            }
+            "NamePartGroup" => self.references.name_part_groups.extend(int("ID")),
+            "NamePartValue" => {
+                if let Some(id) = int("NamePartGroupID") {
+                    let violation = self.violation(Severity::Blocking, format!("NamePartGroup {} not found", id));
+                    self.references.name_part_values.push((id, violation));
+                }
+            }
+            "SanctionsEntry" => {
+                if let Some(profile_id) = fixed_ref {
+                    let violation = self.violation(Severity::Warning, format!("No DistinctParty for Profile {}", profile_id));
+                    self.references.entry_profile_ids.insert(profile_id, violation);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self) {
+        if self.is_checked() {
+            let frame = self.stack.last().unwrap();
+            let name = frame.name.clone();
+            let text = frame.text.trim().to_owned();
+            let has_reference = frame.has_reference;
+            let missing: Vec<&str> = REQUIRED_CHILDREN
+                .iter()
+                .filter(|(element, _)| *element == name)
+                .flat_map(|(_, required)| required.iter())
+                .filter(|child| !frame.children.iter().any(|c| c == *child))
+                .copied()
+                .collect();
+            for child in missing {
+                self.push(Severity::Blocking, format!("Missing element {}", child));
+            }
+            let range = match name.as_str() {
+                "Year" => Some(1..=9999),
+                "Month" => Some(1..=12),
+                "Day" => Some(1..=31),
+                _ => None,
+            };
+            if let Some(range) = range {
+                if !text.parse::<i32>().map(|value| range.contains(&value)).unwrap_or(false) {
+                    self.push(Severity::Blocking, format!("{} \"{}\" is not valid", name, text));
+                }
+            }
+            match name.as_str() {
+                "VersionDetail" if text.is_empty() && !has_reference => self.push(Severity::Blocking, "VersionDetail has neither a value nor a DetailReferenceID".to_owned()),
+                "DistinctParty" => {
+                    let references = &mut self.references;
+                    let unknown = references.name_part_values.drain(..).filter(|(id, _)| !references.name_part_groups.contains(id));
+                    self.report.violations.extend(unknown.map(|(_, violation)| violation));
+                }
+                _ => {}
+            }
+        }
+        self.stack.pop();
+    }
+
+    fn finish(mut self) -> ValidationReport {
+        let references = self.references;
+        for (id, violation) in references.version_locations.into_iter() {
+            if !references.location_ids.contains(&id) {
+                self.report.violations.push(violation);
+            }
+        }
+        let mut entry_profile_ids = references.entry_profile_ids;
+        for (fixed_ref, violation) in references.party_fixed_refs.into_iter() {
+            if entry_profile_ids.remove(&fixed_ref).is_none() {
+                self.report.violations.push(violation);
+            }
+        }
+        let mut orphans: Vec<Violation> = entry_profile_ids.into_values().collect();
+        orphans.sort_by_key(|violation| (violation.line, violation.column));
+        self.report.violations.extend(orphans);
+        self.report
+    }
+}
+
+/// Position of the `<` of the element just read, `length` being the length of the whole tag
+fn start_position<R>(reader: &Reader<LineCounter<R>>, length: usize) -> (usize, usize) {
+    reader.get_ref().line_column(reader.buffer_position().saturating_sub(length))
+}
+
+/// Check an Ofac advanced xml against the rules the importer relies on
+///
+/// The whole document is read without being deserialized, every violation is reported with its
+/// element path, its position and the FixedRef of the enclosing DistinctParty or SanctionsEntry.
+pub fn validate<R: BufRead>(reader: R) -> ValidationReport {
+    let mut reader = Reader::from_reader(LineCounter {
+        inner: reader,
+        offset: 0,
+        line_starts: vec![0],
+    });
+    reader.trim_text(true);
+    let mut validator = Validator {
+        report: ValidationReport::default(),
+        stack: Vec::new(),
+        references: References::default(),
+    };
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) => validator.start(&element, start_position(&reader, element.len() + 2)),
+            Ok(Event::Empty(element)) => {
+                validator.start(&element, start_position(&reader, element.len() + 3));
+                validator.end();
+            }
+            Ok(Event::End(_)) => validator.end(),
+            Ok(Event::Text(text)) => {
+                if let (Some(frame), Ok(text)) = (validator.stack.last_mut(), text.unescape()) {
+                    frame.text.push_str(&text);
+                }
+            }
+            Ok(Event::CData(text)) => {
+                if let Some(frame) = validator.stack.last_mut() {
+                    frame.text.push_str(&String::from_utf8_lossy(&text));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(err) => {
+                let (line, column) = reader.get_ref().line_column(reader.buffer_position());
+                let mut violation = validator.violation(Severity::Blocking, format!("Malformed xml, {}", err));
+                violation.line = line;
+                violation.column = column;
+                validator.report.violations.push(violation);
+                return validator.report;
+            }
+        }
+        buf.clear();
+    }
+    if !validator.stack.is_empty() {
+        validator.push(Severity::Blocking, "Unexpected end of document".to_owned());
+    }
+    validator.finish()
+}
+
+#[cfg(test)]
+mod validation {
+    use super::*;
+
+    const DOCUMENT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<Sanctions xmlns="http://www.un.org/sanctions/1.0">
+  <DateOfIssue>
+    <Year>2022</Year>
+    <Month>11</Month>
+    <Day>8</Day>
+  </DateOfIssue>
+  <Locations>
+    <Location ID="1">
+      <LocationCountry CountryID="11" />
+    </Location>
+  </Locations>
+  <DistinctParties>
+    <DistinctParty FixedRef="36">
+      <Profile ID="36" PartySubTypeID="4">
+        <Identity ID="100" FixedRef="36" Primary="true" False="false">
+          <Alias FixedRef="36" AliasTypeID="1403" Primary="true" LowQuality="false">
+            <DocumentedName ID="1000" FixedRef="36" DocNameStatusID="1">
+              <DocumentedNamePart>
+                <NamePartValue NamePartGroupID="20" ScriptID="215">AEROCARIBBEAN AIRLINES</NamePartValue>
+              </DocumentedNamePart>
+            </DocumentedName>
+          </Alias>
+          <NamePartGroups>
+            <MasterNamePartGroup>
+              <NamePartGroup ID="20" NamePartTypeID="1525" />
+            </MasterNamePartGroup>
+          </NamePartGroups>
+        </Identity>
+        <Feature ID="5" FeatureTypeID="25">
+          <FeatureVersion ID="6" ReliabilityID="1">
+            <VersionLocation LocationID="1" />
+          </FeatureVersion>
+        </Feature>
+      </Profile>
+    </DistinctParty>
+  </DistinctParties>
+  <SanctionsEntries>
+    <SanctionsEntry ID="36" ProfileID="36" ListID="1550">
+      <EntryEvent ID="36" EntryEventTypeID="1" LegalBasisID="1">
+        <Date CalendarTypeID="1">
+          <Year>1986</Year>
+          <Month>12</Month>
+          <Day>10</Day>
+        </Date>
+      </EntryEvent>
+      <SanctionsMeasure ID="36" SanctionsTypeID="1" />
+    </SanctionsEntry>
+  </SanctionsEntries>
+</Sanctions>"#;
+
+    #[test]
+    fn validate_valid_document() {
+        let report = validate(DOCUMENT.as_bytes());
+        assert_eq!(ValidationReport::default(), report);
+    }
+
+    #[test]
+    fn validate_broken_document() {
+        let broken = DOCUMENT
+            .replace("<Month>12</Month>", "<Month>13</Month>")
+            .replace("NamePartGroupID=\"20\"", "NamePartGroupID=\"21\"")
+            .replace("LocationID=\"1\"", "LocationID=\"2\"")
+            .replace("LowQuality=\"false\"", "")
+            .replace("ProfileID=\"36\"", "ProfileID=\"37\"");
+        let report = validate(broken.as_bytes());
+        assert!(report.has_blocking());
+        let messages: Vec<String> = report.violations.iter().map(|violation| violation.to_string()).collect();
+        let excepted = vec![
+            "/Sanctions/DistinctParties/DistinctParty/Profile/Identity/Alias line 17 column 11 (FixedRef 36) : Missing attribute LowQuality".to_owned(),
+            "/Sanctions/DistinctParties/DistinctParty/Profile/Identity/Alias/DocumentedName/DocumentedNamePart/NamePartValue line 20 column 17 (FixedRef 36) : NamePartGroup 21 not found".to_owned(),
+            "/Sanctions/SanctionsEntries/SanctionsEntry/EntryEvent/Date/Month line 43 column 11 (FixedRef 37) : Month \"13\" is not valid".to_owned(),
+            "/Sanctions/DistinctParties/DistinctParty/Profile/Feature/FeatureVersion/VersionLocation line 32 column 13 (FixedRef 36) : Location 2 not found".to_owned(),
+            "/Sanctions/DistinctParties/DistinctParty line 14 column 5 (FixedRef 36) : No SanctionsEntry for this DistinctParty".to_owned(),
+            "/Sanctions/SanctionsEntries/SanctionsEntry line 39 column 5 (FixedRef 37) : No DistinctParty for Profile 37".to_owned(),
+        ];
+        assert_eq!(excepted, messages);
+        assert_eq!(Severity::Warning, report.violations[5].severity);
+    }
+
+    #[test]
+    fn validate_malformed_document() {
+        let report = validate(&DOCUMENT.as_bytes()[..DOCUMENT.find("</DistinctParty>").unwrap()]);
+        assert!(report.has_blocking());
+        let truncated = DOCUMENT.replace("</Identity>", "</Identiti>");
+        let report = validate(truncated.as_bytes());
+        assert!(report.violations[0].message.starts_with("Malformed xml"));
+        assert_eq!(Some(36), report.violations[0].fixed_ref);
+    }
+}
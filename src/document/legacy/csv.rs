@@ -0,0 +1,249 @@
+use std::io::Read;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use ::csv::{ByteRecord, ReaderBuilder};
+
+use super::xml::name_parts;
+use super::{add_identifier, parse_date, party_kind, LegacyReport};
+use crate::document::inputs::builder::{Address, OfacDocumentBuilder, PartyBuilder, PartyKind};
+use crate::document::inputs::known::{DocumentKind, FeatureKind, KnownValue};
+use crate::document::inputs::{DocumentType, OfacDocument};
+
+/// Records of a legacy csv file (no header, `-0-` means no value)
+fn records<R: Read>(reader: R) -> impl Iterator<Item = Result<(i32, Vec<Option<String>>), ::csv::Error>> {
+    let reader = ReaderBuilder::new().has_headers(false).flexible(true).from_reader(reader);
+    reader.into_byte_records().filter_map(|record| match record {
+        Ok(record) => parse_record(&record).map(Ok),
+        Err(err) => Some(Err(err)),
+    })
+}
+
+/// The ent_num and the values of a record, the end of file marker and blank lines are skipped
+fn parse_record(record: &ByteRecord) -> Option<(i32, Vec<Option<String>>)> {
+    let values: Vec<Option<String>> = record
+        .iter()
+        .map(|value| String::from_utf8_lossy(value).trim().to_owned())
+        .map(|value| if value.is_empty() || value == "-0-" { None } else { Some(value) })
+        .collect();
+    let ent_num = values.first()?.as_ref()?.parse().ok()?;
+    Some((ent_num, values))
+}
+
+fn value(values: &[Option<String>], i: usize) -> Option<&str> {
+    values.get(i).and_then(|value| value.as_deref())
+}
+
+/// Legacy csv names of individuals are written `LAST, First`
+fn split_name(kind: PartyKind, name: &str) -> (Option<&str>, Option<&str>) {
+    match (kind, name.split_once(',')) {
+        (PartyKind::Individual, Some((last_name, first_name))) => (Some(first_name), Some(last_name)),
+        _ => (None, Some(name)),
+    }
+}
+
+/// Read the legacy `SDN.CSV`, `ADD.CSV` and `ALT.CSV` into an OfacDocument
+///
+/// The csv files have no publish date, the given `date_of_issue` is used instead
+#[allow(clippy::too_many_arguments)]
+pub fn read<R: Read, P: AsRef<Path>>(sdn: R, addresses: R, aliases: R, date_of_issue: NaiveDate, folder: P, document_type: DocumentType, report: &mut LegacyReport) -> Result<OfacDocument, ::csv::Error> {
+    let mut builder = OfacDocumentBuilder::new(folder, document_type);
+    builder.date_of_issue(date_of_issue);
+    let mut addresses_by_sdn = Vec::new();
+    for record in records(addresses) {
+        addresses_by_sdn.push(record?);
+    }
+    let mut aliases_by_sdn = Vec::new();
+    for record in records(aliases) {
+        aliases_by_sdn.push(record?);
+    }
+    for record in records(sdn) {
+        let (ent_num, values) = record?;
+        let sdn_type = value(&values, 2).unwrap_or_default();
+        let kind = match party_kind(sdn_type) {
+            Some(kind) => kind,
+            None => {
+                report.unmapped(format!("SDN_Type {}", sdn_type), ent_num);
+                continue;
+            }
+        };
+        let mut party = builder.party(ent_num, kind);
+        if let Some(name) = value(&values, 1) {
+            let (first_name, last_name) = split_name(kind, name);
+            party.name(ent_num, true, false, &name_parts(kind, first_name, last_name));
+        }
+        for program in value(&values, 3).unwrap_or_default().split("] [") {
+            party.program(None, program.trim_matches(|c| c == '[' || c == ']'));
+        }
+        for (i, feature_type) in [(4, 26), (5, 1), (7, 5), (8, 6), (9, 3), (10, 4)] {
+            if let Some(value) = value(&values, i) {
+                party.feature_value(None, feature_type, value);
+            }
+        }
+        if let Some(vessel_type) = value(&values, 6) {
+            party.feature_reference(None, 2, vessel_type);
+        }
+        if let Some(remarks) = value(&values, 11) {
+            add_remarks(&mut party, report, remarks);
+        }
+        for (_, values) in addresses_by_sdn.iter().filter(|(id, _)| *id == ent_num) {
+            let add_num: i32 = match value(values, 1).and_then(|add_num| add_num.parse().ok()) {
+                Some(add_num) => add_num,
+                None => continue,
+            };
+            party.feature_location(
+                add_num,
+                25,
+                &Address {
+                    address: value(values, 2).into_iter().map(|address| address.to_owned()).collect(),
+                    // City, state and postal code are written in a single field
+                    city: value(values, 3).map(|city| city.to_owned()),
+                    country: value(values, 4).map(|country| country.to_owned()),
+                    ..Default::default()
+                },
+            );
+            if value(values, 5).is_some() {
+                report.unmapped("ADD remarks", ent_num);
+            }
+        }
+        for (_, values) in aliases_by_sdn.iter().filter(|(id, _)| *id == ent_num) {
+            let (alt_num, name) = match (value(values, 1).and_then(|alt_num| alt_num.parse::<i32>().ok()), value(values, 3)) {
+                (Some(alt_num), Some(name)) => (alt_num, name),
+                _ => continue,
+            };
+            match value(values, 2) {
+                Some(alt_type) if alt_type.eq_ignore_ascii_case("aka") => {}
+                alt_type => report.unmapped(format!("alt_type {}", alt_type.unwrap_or_default()), ent_num),
+            }
+            let (first_name, last_name) = split_name(kind, name);
+            party.name(alt_num, false, false, &name_parts(kind, first_name, last_name));
+            if value(values, 4).is_some() {
+                report.unmapped("ALT remarks", ent_num);
+            }
+        }
+        party.finish();
+    }
+    Ok(builder.build())
+}
+
+/// Legacy csv files give the details of an sdn in its remarks, e.g. `DOB 10 Dec 1948; POB Safed, Palestine; Passport A123456 (Syria).`
+///
+/// Known details are mapped as they are in the legacy xml, the others are kept in the comment
+fn add_remarks(party: &mut PartyBuilder, report: &mut LegacyReport, remarks: &str) {
+    let fixed_ref = party.fixed_ref();
+    for remark in remarks.split(';') {
+        let remark = remark.trim().trim_end_matches('.').trim();
+        let lowercase = remark.to_lowercase();
+        let detail = |prefix: &str| if lowercase.starts_with(prefix) { Some(remark[prefix.len()..].trim()) } else { None };
+        if ["a.k.a", "f.k.a", "n.k.a"].iter().any(|prefix| lowercase.starts_with(prefix)) || remark.is_empty() {
+            // Aliases are read from ALT.CSV
+        } else if let Some(dob) = detail("dob ") {
+            match parse_date(dob) {
+                Some(period) => party.feature_date(None, 8, period),
+                None => report.unmapped("DOB (date not recognized)", fixed_ref),
+            }
+        } else if let Some(pob) = detail("pob ") {
+            party.feature_value(None, 9, pob);
+        } else if let Some(country) = detail("nationality ") {
+            party.feature_location(None, 10, &Address { country: Some(country.to_owned()), ..Default::default() });
+        } else if let Some(country) = detail("citizen ") {
+            party.feature_location(None, 11, &Address { country: Some(country.to_owned()), ..Default::default() });
+        } else if let Some((id_type, value)) = known_prefix(remark) {
+            let (value, expiration) = split_suffix(value, " expires ");
+            let (value, issued) = split_suffix(value, " issued ");
+            let (number, country) = match value.strip_suffix(')').and_then(|value| value.rsplit_once(" (")) {
+                Some((number, country)) => (number, Some(country)),
+                None => (value, None),
+            };
+            add_identifier(party, report, None, id_type, number, country, issued, expiration);
+        } else {
+            report.unmapped(format!("Remarks {}", remark.split_whitespace().take_while(|word| !word.contains(char::is_numeric)).take(3).collect::<Vec<_>>().join(" ")), fixed_ref);
+            party.comment(&format!("{}.", remark));
+        }
+    }
+}
+
+/// The feature or document type a remark starts with, and the remaining value
+fn known_prefix(remark: &str) -> Option<(&'static str, &str)> {
+    let names = FeatureKind::VALUES.iter().filter_map(|(_, name)| *name).chain(DocumentKind::VALUES.iter().filter_map(|(_, name)| *name));
+    names
+        .filter(|name| remark.len() > name.len() && remark.is_char_boundary(name.len()) && remark[..name.len()].eq_ignore_ascii_case(name) && remark[name.len()..].starts_with(' '))
+        .max_by_key(|name| name.len())
+        .map(|name| (name, remark[name.len()..].trim()))
+}
+
+fn split_suffix<'a>(value: &'a str, separator: &str) -> (&'a str, Option<&'a str>) {
+    match value.split_once(separator) {
+        Some((value, suffix)) => (value.trim(), Some(suffix.trim())),
+        None => (value, None),
+    }
+}
+
+#[cfg(test)]
+mod csv {
+    use super::*;
+    use crate::db::entity::sdn::{self, DocumentEntity};
+
+    const SDN_CSV: &str = "36,\"AEROCARIBBEAN AIRLINES\",-0- ,\"CUBA\",-0- ,-0- ,-0- ,-0- ,-0- ,-0- ,-0- ,-0- \r\n\
+2674,\"ABBAS, Abu\",\"individual\",\"SDGT] [SDT\",-0- ,-0- ,-0- ,-0- ,-0- ,-0- ,-0- ,\"DOB 10 Dec 1948; POB Safed, Palestine; Passport A123456 (Syria) issued 01 Jan 2010; Gender Male; Linked To: PALESTINE LIBERATION FRONT.\"\r\n\
+\x1A\r\n";
+    const ADD_CSV: &str = "36,25,-0- ,\"Havana\",\"Cuba\",-0- \r\n";
+    const ALT_CSV: &str = "36,12,\"aka\",\"AERO-CARIBBEAN\",-0- \r\n2674,1004,\"fka\",\"ABU KHALED\",-0- \r\n";
+
+    #[test]
+    fn read_legacy_csv() {
+        let mut report = LegacyReport::default();
+        let date_of_issue = NaiveDate::from_ymd(2022, 11, 8);
+        let document = read(SDN_CSV.as_bytes(), ADD_CSV.as_bytes(), ALT_CSV.as_bytes(), date_of_issue, "", DocumentType::OFAC, &mut report).unwrap();
+        assert_eq!(2, document.distinct_parties.parties.len());
+        assert_eq!(1, document.documents.documents.len());
+        assert_eq!(Some("A123456".to_owned()), document.documents.documents[0].registration_number);
+
+        let (entity, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[0], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!(36, entity.fixed_ref);
+        assert_eq!("ENTITY", entity.sdn_type);
+        assert_eq!(2, relations.names.len());
+        assert_eq!(Some("HAVANA".to_owned()), relations.address[0].city);
+
+        let (individual, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[1], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!(Some("ABBAS".to_owned()), relations.names[0].last_name);
+        assert_eq!(Some("Abu".to_owned()), relations.names[0].first_name);
+        assert_eq!(Some("MALE".to_owned()), individual.gender);
+        assert_eq!(Some("LINKED TO: PALESTINE LIBERATION FRONT.".to_owned()), individual.comment);
+        assert_eq!("10 DEC 1948", relations.dobs[0].dob);
+        assert_eq!(2, relations.programs.len());
+
+        let excepted = LegacyReport {
+            unmapped: [("Remarks Linked To: PALESTINE".to_owned(), (1, 2674)), ("alt_type fka".to_owned(), (1, 2674))].into_iter().collect(),
+        };
+        assert_eq!(excepted, report);
+    }
+
+    #[test]
+    fn items_have_unique_ids() {
+        let sdn_csv = "15036,\"ADRIAN DARYA 1\",\"vessel\",\"IRAN\",\"Captain\",\"9HA2000\",\"Crude Oil Tanker\",\"150000\",\"81000\",\"Panama\",\"Owner Co\",\"Nationality Cuba; Citizen Cuba\"\r\n";
+        let add_csv = "15036,1503600,-0- ,\"Havana\",\"Cuba\",-0- \r\n";
+        let mut report = LegacyReport::default();
+        let document = read(sdn_csv.as_bytes(), add_csv.as_bytes(), "".as_bytes(), NaiveDate::from_ymd(2022, 11, 8), "", DocumentType::OFAC, &mut report).unwrap();
+        let features = document.distinct_parties.parties[0].profile.feature.as_ref().unwrap();
+        let mut ids: Vec<i32> = features.iter().map(|feature| feature.id).collect();
+        ids.push(document.distinct_parties.parties[0].profile.identity.alias[0].documented_name[0].id);
+        ids.extend(document.sanction_entries.entries[0].measures.iter().map(|measure| measure.id));
+        let count = ids.len();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(count, ids.len());
+        assert_eq!(10, features.len());
+        assert_eq!(3, document.locations.locations.len());
+        let address = features.iter().find(|feature| feature.feature_type == 25).unwrap();
+        assert_eq!(1503600, address.id);
+        assert_eq!(15036, document.distinct_parties.parties[0].profile.identity.alias[0].documented_name[0].id);
+    }
+
+    #[test]
+    fn find_known_prefix() {
+        assert_eq!(Some(("Passport", "A123 (Syria)")), known_prefix("Passport A123 (Syria)"));
+        assert_eq!(Some(("Digital Currency Address - XBT", "1abc")), known_prefix("Digital Currency Address - XBT 1abc"));
+        assert_eq!(None, known_prefix("Passports"));
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use log::warn;
+
+use crate::document::inputs::builder::{date_period_day, date_period_days, date_period_month, date_period_year, date_period_years, Address, PartyBuilder, PartyKind, DATE_FEATURE_TYPES, REFERENCE_FEATURE_TYPES};
+use crate::document::inputs::known::{FeatureKind, KnownValue};
+use crate::document::inputs::models::feature::DatePeriod;
+use crate::document::inputs::source::DocumentSource;
+use crate::document::inputs::{DocumentType, OfacDocument};
+
+pub mod csv;
+pub mod xml;
+
+/// The classic Ofac formats, published before the advanced xml
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum LegacyFormat {
+    /// `sdn.xml` or `consolidated.xml`
+    Xml,
+    /// `SDN.CSV`, `ADD.CSV` and `ALT.CSV` (or their `cons_` counterparts)
+    Csv,
+}
+
+impl LegacyFormat {
+    /// File stems of the files holding the given list, the first one is the main file
+    pub fn file_stems(&self, document_type: DocumentType) -> &'static [&'static str] {
+        document_type.source().legacy_file_stems(*self)
+    }
+
+    /// Find the files of the given list in `folder`
+    ///
+    /// When `input` is given, it is used as the main file and the other files are searched next to it
+    pub fn find<P: AsRef<Path>>(&self, folder: P, input: Option<&PathBuf>, document_type: DocumentType) -> Result<Vec<DocumentSource>, Box<dyn Error>> {
+        let folder = match input.and_then(|input| input.parent()) {
+            Some(parent) => parent.to_owned(),
+            None => folder.as_ref().to_owned(),
+        };
+        let mut sources = Vec::new();
+        for (i, stem) in self.file_stems(document_type).iter().enumerate() {
+            let source = match (i, input, self) {
+                (0, Some(input), _) => DocumentSource::new(input),
+                (_, _, LegacyFormat::Xml) => DocumentSource::find(&folder, stem)?,
+                (_, _, LegacyFormat::Csv) => DocumentSource::find_csv(&folder, stem)?,
+            };
+            sources.push(source);
+        }
+        Ok(sources)
+    }
+}
+
+/// Read the legacy files of the given list into an OfacDocument, see [`LegacyFormat::find`]
+///
+/// Csv files have no publish date, the modification date of the main file is used as date of issue
+pub fn load<P: AsRef<Path>>(format: LegacyFormat, sources: &[DocumentSource], folder: P, document_type: DocumentType, report: &mut LegacyReport) -> Result<OfacDocument, Box<dyn Error>> {
+    match (format, sources) {
+        (LegacyFormat::Xml, [sdn]) => Ok(xml::read(sdn.open()?, folder, document_type, report)?),
+        (LegacyFormat::Csv, [sdn, addresses, aliases]) => {
+            let modified: DateTime<Local> = std::fs::metadata(&sdn.path)?.modified()?.into();
+            Ok(csv::read(sdn.open()?, addresses.open()?, aliases.open()?, modified.date_naive(), folder, document_type, report)?)
+        }
+        _ => Err(format!("Legacy {:?} files are missing", format).into()),
+    }
+}
+
+/// Legacy fields that could not be mapped to the advanced model
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct LegacyReport {
+    /// Number of occurrences and first uid of each unmapped field
+    pub unmapped: BTreeMap<String, (usize, i32)>,
+}
+
+impl LegacyReport {
+    pub fn unmapped<S: Into<String>>(&mut self, field: S, uid: i32) {
+        self.unmapped.entry(field.into()).or_insert((0, uid)).0 += 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unmapped.is_empty()
+    }
+
+    pub fn log(&self) {
+        for (field, (count, uid)) in self.unmapped.iter() {
+            warn!("Legacy field not mapped : {} ({} occurrences, e.g. uid {})", field, count, uid);
+        }
+    }
+}
+
+/// Kind of party from the legacy sdnType (`-0-` or empty is an entity)
+fn party_kind(sdn_type: &str) -> Option<PartyKind> {
+    match sdn_type.trim().to_lowercase().as_str() {
+        "individual" => Some(PartyKind::Individual),
+        "entity" | "-0-" | "" => Some(PartyKind::Entity),
+        "vessel" => Some(PartyKind::Vessel),
+        "aircraft" => Some(PartyKind::Aircraft),
+        _ => None,
+    }
+}
+
+/// A day written like `10 Oct 1966`
+fn parse_day(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%d %b %Y").ok()
+}
+
+/// A legacy date, e.g. `10 Oct 1966`, `Oct 1966`, `1966`, `circa 1966` or `1962 to 1966`
+fn parse_date(value: &str) -> Option<DatePeriod> {
+    let value = value.trim();
+    let (value, approximate) = match value.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("circa ") => (value[6..].trim(), true),
+        _ => (value, false),
+    };
+    if let Some((from, to)) = value.split_once(" to ") {
+        return match (from.trim().parse::<i32>(), to.trim().parse::<i32>()) {
+            (Ok(from), Ok(to)) => Some(date_period_years(from, to)),
+            _ => Some(date_period_days(parse_day(from)?, parse_day(to)?)),
+        };
+    }
+    if let Some(day) = parse_day(value) {
+        return Some(date_period_day(day, approximate));
+    }
+    if let Ok(month) = NaiveDate::parse_from_str(&format!("1 {}", value), "%d %b %Y") {
+        return Some(date_period_month(month.year(), month.month(), approximate));
+    }
+    match value.parse::<i32>() {
+        Ok(year) if value.len() == 4 => Some(date_period_year(year, approximate)),
+        _ => None,
+    }
+}
+
+/// Maps a legacy id (`idType`, `idNumber`...) to a feature when Ofac publishes it as a feature in the advanced format,
+/// and to an IDRegDocument otherwise
+#[allow(clippy::too_many_arguments)]
+fn add_identifier(party: &mut PartyBuilder, report: &mut LegacyReport, uid: Option<i32>, id_type: &str, number: &str, country: Option<&str>, issued: Option<&str>, expiration: Option<&str>) {
+    let id_type = id_type.trim();
+    if id_type.eq_ignore_ascii_case("Gender") {
+        match number.trim().to_lowercase().as_str() {
+            "male" => party.gender(uid, true),
+            "female" => party.gender(uid, false),
+            _ => report.unmapped(format!("Gender {}", number.trim()), party.fixed_ref()),
+        }
+        return;
+    }
+    let feature_type = FeatureKind::from_name(id_type).map(|kind| kind.id());
+    match feature_type {
+        Some(feature_type) if DATE_FEATURE_TYPES.contains(&feature_type) => match parse_date(number) {
+            Some(period) => party.feature_date(uid, feature_type, period),
+            None => report.unmapped(format!("{} (date not recognized)", id_type), party.fixed_ref()),
+        },
+        Some(feature_type) if REFERENCE_FEATURE_TYPES.contains(&feature_type) => party.feature_reference(uid, feature_type, number),
+        Some(feature_type @ (10 | 11 | 365)) => {
+            let country = country.filter(|country| !country.trim().is_empty()).unwrap_or(number);
+            party.feature_location(uid, feature_type, &Address { country: Some(country.to_owned()), ..Default::default() });
+        }
+        Some(feature_type) => party.feature_value(uid, feature_type, number),
+        None => {
+            let mut parse = |date: Option<&str>, name: &str| match date.map(|date| date.trim()).filter(|date| !date.is_empty()) {
+                Some(date) => {
+                    let day = parse_day(date);
+                    if day.is_none() {
+                        report.unmapped(format!("{} {} (date not recognized)", id_type, name), party.fixed_ref());
+                    }
+                    day
+                }
+                None => None,
+            };
+            let issued = parse(issued, "issue date");
+            let expiration = parse(expiration, "expiration date");
+            party.document(uid, id_type, number, country, issued, expiration);
+        }
+    }
+}
+
+#[cfg(test)]
+mod legacy {
+    use super::*;
+
+    #[test]
+    fn parse_legacy_dates() {
+        let day = parse_date("10 Oct 1966").unwrap();
+        assert_eq!(Some(NaiveDate::from_ymd(1966, 10, 10)), day.parse_from_to());
+        let month = parse_date("Feb 1966").unwrap();
+        assert_eq!("28", month.end.unwrap().from.day);
+        let year = parse_date("circa 1966").unwrap();
+        assert!(year.start.as_ref().unwrap().approximate);
+        assert_eq!("12", year.end.unwrap().to.month);
+        let range = parse_date("1962 to 1966").unwrap();
+        assert_eq!("1962", range.start.unwrap().from.year);
+        assert_eq!("1966", range.end.unwrap().to.year);
+        assert!(parse_date("sometime in the 60s").is_none());
+    }
+
+    #[test]
+    fn parse_party_kind() {
+        assert_eq!(Some(PartyKind::Entity), party_kind("-0- "));
+        assert_eq!(Some(PartyKind::Individual), party_kind("Individual"));
+        assert_eq!(None, party_kind("Organization"));
+    }
+}
@@ -0,0 +1,407 @@
+use std::io::BufRead;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use quick_xml::DeError;
+use serde::Deserialize;
+
+use super::{add_identifier, parse_date, party_kind, LegacyReport};
+use crate::document::inputs::builder::{Address, NamePart, OfacDocumentBuilder, PartyKind};
+use crate::document::inputs::{DocumentType, OfacDocument};
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct SdnList {
+    #[serde(rename = "publshInformation")]
+    pub publish_information: PublishInformation,
+    #[serde(rename = "sdnEntry", default)]
+    pub entries: Vec<SdnEntry>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct PublishInformation {
+    /// MM/DD/YYYY
+    #[serde(rename = "Publish_Date")]
+    pub publish_date: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct SdnEntry {
+    pub uid: i32,
+    #[serde(rename = "firstName")]
+    pub first_name: Option<String>,
+    #[serde(rename = "lastName")]
+    pub last_name: Option<String>,
+    pub title: Option<String>,
+    #[serde(rename = "sdnType")]
+    pub sdn_type: String,
+    pub remarks: Option<String>,
+    #[serde(rename = "programList")]
+    pub programs: Option<ProgramList>,
+    #[serde(rename = "idList")]
+    pub ids: Option<IdList>,
+    #[serde(rename = "akaList")]
+    pub akas: Option<AkaList>,
+    #[serde(rename = "addressList")]
+    pub addresses: Option<AddressList>,
+    #[serde(rename = "nationalityList")]
+    pub nationalities: Option<CountryList>,
+    #[serde(rename = "citizenshipList")]
+    pub citizenships: Option<CitizenshipList>,
+    #[serde(rename = "dateOfBirthList")]
+    pub dates_of_birth: Option<DateOfBirthList>,
+    #[serde(rename = "placeOfBirthList")]
+    pub places_of_birth: Option<PlaceOfBirthList>,
+    #[serde(rename = "vesselInfo")]
+    pub vessel_info: Option<VesselInfo>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct ProgramList {
+    #[serde(default)]
+    pub program: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct IdList {
+    #[serde(default)]
+    pub id: Vec<Id>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Id {
+    pub uid: i32,
+    #[serde(rename = "idType")]
+    pub id_type: String,
+    #[serde(rename = "idNumber", default)]
+    pub id_number: String,
+    #[serde(rename = "idCountry")]
+    pub id_country: Option<String>,
+    #[serde(rename = "issueDate")]
+    pub issue_date: Option<String>,
+    #[serde(rename = "expirationDate")]
+    pub expiration_date: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct AkaList {
+    #[serde(default)]
+    pub aka: Vec<Aka>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Aka {
+    pub uid: i32,
+    #[serde(rename = "type")]
+    pub aka_type: String,
+    pub category: String,
+    #[serde(rename = "firstName")]
+    pub first_name: Option<String>,
+    #[serde(rename = "lastName")]
+    pub last_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct AddressList {
+    #[serde(default)]
+    pub address: Vec<LegacyAddress>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct LegacyAddress {
+    pub uid: i32,
+    pub address1: Option<String>,
+    pub address2: Option<String>,
+    pub address3: Option<String>,
+    pub city: Option<String>,
+    #[serde(rename = "stateOrProvince")]
+    pub state_or_province: Option<String>,
+    #[serde(rename = "postalCode")]
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CountryList {
+    #[serde(default)]
+    pub nationality: Vec<Country>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CitizenshipList {
+    #[serde(default)]
+    pub citizenship: Vec<Country>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Country {
+    pub uid: i32,
+    pub country: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct DateOfBirthList {
+    #[serde(rename = "dateOfBirthItem", default)]
+    pub items: Vec<DateOfBirthItem>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct DateOfBirthItem {
+    pub uid: i32,
+    #[serde(rename = "dateOfBirth")]
+    pub date_of_birth: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct PlaceOfBirthList {
+    #[serde(rename = "placeOfBirthItem", default)]
+    pub items: Vec<PlaceOfBirthItem>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct PlaceOfBirthItem {
+    pub uid: i32,
+    #[serde(rename = "placeOfBirth")]
+    pub place_of_birth: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct VesselInfo {
+    #[serde(rename = "callSign")]
+    pub call_sign: Option<String>,
+    #[serde(rename = "vesselType")]
+    pub vessel_type: Option<String>,
+    #[serde(rename = "vesselFlag")]
+    pub vessel_flag: Option<String>,
+    #[serde(rename = "vesselOwner")]
+    pub vessel_owner: Option<String>,
+    pub tonnage: Option<String>,
+    #[serde(rename = "grossRegisteredTonnage")]
+    pub gross_registered_tonnage: Option<String>,
+}
+
+/// Name parts of a legacy name, legacy entities only have a last name
+pub(super) fn name_parts<'a>(kind: PartyKind, first_name: Option<&'a str>, last_name: Option<&'a str>) -> Vec<(NamePart, &'a str)> {
+    let mut parts = Vec::new();
+    let last_name_part = match kind {
+        PartyKind::Individual => {
+            if let Some(first_name) = first_name {
+                parts.push((NamePart::FirstName, first_name));
+            }
+            NamePart::LastName
+        }
+        PartyKind::Entity => NamePart::EntityName,
+        PartyKind::Vessel => NamePart::VesselName,
+        PartyKind::Aircraft => NamePart::AircraftName,
+    };
+    if let Some(last_name) = last_name {
+        parts.push((last_name_part, last_name));
+    }
+    parts
+}
+
+/// Read a legacy `sdn.xml` into an OfacDocument
+pub fn read<R: BufRead, P: AsRef<Path>>(reader: R, folder: P, document_type: DocumentType, report: &mut LegacyReport) -> Result<OfacDocument, DeError> {
+    let list: SdnList = quick_xml::de::from_reader(reader)?;
+    let mut builder = OfacDocumentBuilder::new(folder, document_type);
+    match NaiveDate::parse_from_str(list.publish_information.publish_date.trim(), "%m/%d/%Y") {
+        Ok(date) => builder.date_of_issue(date),
+        Err(_) => return Err(DeError::Custom(format!("Publish_Date not recognized {}", list.publish_information.publish_date))),
+    }
+    for entry in list.entries.iter() {
+        let kind = match party_kind(&entry.sdn_type) {
+            Some(kind) => kind,
+            None => {
+                report.unmapped(format!("sdnType {}", entry.sdn_type), entry.uid);
+                continue;
+            }
+        };
+        let mut party = builder.party(entry.uid, kind);
+        party.name(entry.uid, true, false, &name_parts(kind, entry.first_name.as_deref(), entry.last_name.as_deref()));
+        for aka in entry.akas.iter().flat_map(|akas| akas.aka.iter()) {
+            if !aka.aka_type.eq_ignore_ascii_case("a.k.a.") {
+                report.unmapped(format!("aka type {}", aka.aka_type), entry.uid);
+            }
+            party.name(aka.uid, false, aka.category.eq_ignore_ascii_case("weak"), &name_parts(kind, aka.first_name.as_deref(), aka.last_name.as_deref()));
+        }
+        if let Some(title) = &entry.title {
+            party.feature_value(None, 26, title);
+        }
+        if let Some(remarks) = &entry.remarks {
+            party.comment(remarks);
+        }
+        for program in entry.programs.iter().flat_map(|programs| programs.program.iter()) {
+            party.program(None, program);
+        }
+        for id in entry.ids.iter().flat_map(|ids| ids.id.iter()) {
+            add_identifier(&mut party, report, Some(id.uid), &id.id_type, &id.id_number, id.id_country.as_deref(), id.issue_date.as_deref(), id.expiration_date.as_deref());
+        }
+        for address in entry.addresses.iter().flat_map(|addresses| addresses.address.iter()) {
+            let lines = [&address.address1, &address.address2, &address.address3].into_iter().flatten().cloned().collect();
+            party.feature_location(
+                address.uid,
+                25,
+                &Address {
+                    address: lines,
+                    city: address.city.clone(),
+                    state: address.state_or_province.clone(),
+                    postal_code: address.postal_code.clone(),
+                    country: address.country.clone(),
+                    ..Default::default()
+                },
+            );
+        }
+        for nationality in entry.nationalities.iter().flat_map(|list| list.nationality.iter()) {
+            party.feature_location(nationality.uid, 10, &Address { country: Some(nationality.country.to_owned()), ..Default::default() });
+        }
+        for citizenship in entry.citizenships.iter().flat_map(|list| list.citizenship.iter()) {
+            party.feature_location(citizenship.uid, 11, &Address { country: Some(citizenship.country.to_owned()), ..Default::default() });
+        }
+        for dob in entry.dates_of_birth.iter().flat_map(|list| list.items.iter()) {
+            match parse_date(&dob.date_of_birth) {
+                Some(period) => party.feature_date(dob.uid, 8, period),
+                None => report.unmapped("dateOfBirth (date not recognized)", entry.uid),
+            }
+        }
+        for pob in entry.places_of_birth.iter().flat_map(|list| list.items.iter()) {
+            party.feature_value(pob.uid, 9, &pob.place_of_birth);
+        }
+        if let Some(vessel) = &entry.vessel_info {
+            let features = [(1, &vessel.call_sign), (3, &vessel.vessel_flag), (4, &vessel.vessel_owner), (5, &vessel.tonnage), (6, &vessel.gross_registered_tonnage)];
+            for (feature_type, value) in features {
+                if let Some(value) = value {
+                    party.feature_value(None, feature_type, value);
+                }
+            }
+            if let Some(vessel_type) = &vessel.vessel_type {
+                party.feature_reference(None, 2, vessel_type);
+            }
+        }
+        party.finish();
+    }
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod xml {
+    use super::*;
+    use crate::db::entity::sdn::{self, DocumentEntity};
+
+    pub(crate) const SDN_XML: &str = r#"<?xml version="1.0" standalone="yes"?>
+<sdnList xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns="http://tempuri.org/sdnList.xsd">
+  <publshInformation>
+    <Publish_Date>11/08/2022</Publish_Date>
+    <Record_Count>2</Record_Count>
+  </publshInformation>
+  <sdnEntry>
+    <uid>36</uid>
+    <lastName>AEROCARIBBEAN AIRLINES</lastName>
+    <sdnType>Entity</sdnType>
+    <programList>
+      <program>CUBA</program>
+    </programList>
+    <akaList>
+      <aka>
+        <uid>12</uid>
+        <type>a.k.a.</type>
+        <category>strong</category>
+        <lastName>AERO-CARIBBEAN</lastName>
+      </aka>
+    </akaList>
+    <addressList>
+      <address>
+        <uid>25</uid>
+        <city>Havana</city>
+        <country>Cuba</country>
+      </address>
+    </addressList>
+  </sdnEntry>
+  <sdnEntry>
+    <uid>2674</uid>
+    <firstName>Abu</firstName>
+    <lastName>ABBAS</lastName>
+    <sdnType>Individual</sdnType>
+    <remarks>Director of PALESTINE LIBERATION FRONT.</remarks>
+    <programList>
+      <program>SDGT</program>
+      <program>SDT</program>
+    </programList>
+    <idList>
+      <id>
+        <uid>1001</uid>
+        <idType>Passport</idType>
+        <idNumber>A123456</idNumber>
+        <idCountry>Syria</idCountry>
+        <issueDate>01 Jan 2010</issueDate>
+      </id>
+      <id>
+        <uid>1002</uid>
+        <idType>Gender</idType>
+        <idNumber>Male</idNumber>
+      </id>
+      <id>
+        <uid>1003</uid>
+        <idType>Website</idType>
+        <idNumber>www.example.com</idNumber>
+      </id>
+    </idList>
+    <akaList>
+      <aka>
+        <uid>1004</uid>
+        <type>f.k.a.</type>
+        <category>weak</category>
+        <lastName>ABU KHALED</lastName>
+      </aka>
+    </akaList>
+    <nationalityList>
+      <nationality>
+        <uid>1005</uid>
+        <country>Syria</country>
+        <mainEntry>true</mainEntry>
+      </nationality>
+    </nationalityList>
+    <dateOfBirthList>
+      <dateOfBirthItem>
+        <uid>1006</uid>
+        <dateOfBirth>10 Dec 1948</dateOfBirth>
+        <mainEntry>true</mainEntry>
+      </dateOfBirthItem>
+    </dateOfBirthList>
+    <placeOfBirthList>
+      <placeOfBirthItem>
+        <uid>1007</uid>
+        <placeOfBirth>Safed, Palestine</placeOfBirth>
+        <mainEntry>true</mainEntry>
+      </placeOfBirthItem>
+    </placeOfBirthList>
+  </sdnEntry>
+</sdnList>"#;
+
+    #[test]
+    fn read_legacy_xml() {
+        let mut report = LegacyReport::default();
+        let document = read(SDN_XML.as_bytes(), "", DocumentType::OFAC, &mut report).unwrap();
+        assert_eq!(NaiveDate::from_ymd(2022, 11, 8), document.references.date_of_issue.to_sql_date());
+        assert_eq!(2, document.distinct_parties.parties.len());
+        assert_eq!(1, document.documents.documents.len());
+
+        let (entity, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[0], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!(36, entity.fixed_ref);
+        assert_eq!("ENTITY", entity.sdn_type);
+        assert_eq!(Some("AEROCARIBBEAN AIRLINES".to_owned()), relations.names[0].entity_name);
+        assert_eq!(Some("HAVANA".to_owned()), relations.address[0].city);
+
+        let (individual, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[1], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!(2674, individual.fixed_ref);
+        assert_eq!(Some("MALE".to_owned()), individual.gender);
+        assert_eq!(Some("DIRECTOR OF PALESTINE LIBERATION FRONT.".to_owned()), individual.comment);
+        assert_eq!(Some("LOW".to_owned()), relations.names[1].quality);
+        assert_eq!("10 DEC 1948", relations.dobs[0].dob);
+        assert_eq!(2, relations.programs.len());
+        assert_eq!(1, relations.websites.len());
+
+        let excepted = LegacyReport {
+            unmapped: [("aka type f.k.a.".to_owned(), (1, 2674))].into_iter().collect(),
+        };
+        assert_eq!(excepted, report);
+    }
+}
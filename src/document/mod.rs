@@ -1,5 +1,9 @@
+pub mod eu;
 pub mod inputs;
+pub mod legacy;
 pub mod outputs;
+pub mod uk;
+pub mod un;
 
 pub use inputs::*;
 pub use outputs::*;
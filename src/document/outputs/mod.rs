@@ -1,24 +1,24 @@
 use lazy_static::lazy_static;
-use log::warn;
+use log::{error, warn};
 use regex::Regex;
 use std::{error::Error, fmt::Display};
 
+use super::inputs::identifier::IdentifierKind;
+use super::inputs::known::DocumentKind;
 use super::DocumentType;
 use crate::db::entity::ddc_name::Model as DdcName;
 use crate::processor::entity::sdn::{SdnRecord, SdnRecordAddress, SdnRecordDocument};
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+/// Length of the exported OIDs, the prefix of the list followed by the zero padded FixedRef or address id
+const OID_LENGTH: usize = 10;
+
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
 pub enum RecordType {
+    #[default]
     Main,
     Alternative,
 }
 
-impl Default for RecordType {
-    fn default() -> Self {
-        Self::Main
-    }
-}
-
 #[derive(Default, Debug, Eq, PartialEq)]
 pub struct FofnasyRecord {
     pub doc_type: DocumentType,
@@ -93,45 +93,51 @@ impl FofdbofRecord {
         let mut records = Vec::new();
         if db_record.addresses.is_empty() {
             let fake_address = SdnRecordAddress { is_primary: true, ..Default::default() };
-            FofdbofRecord::construct_record(db_record, &fake_address, doc_type, &mut records, &mut 0, other_names);
+            if let Err(err) = FofdbofRecord::construct_record(db_record, &fake_address, doc_type, &mut records, &mut 0, other_names) {
+                error!("FixedRef {} is not exported : {}", db_record.fixed_ref, err);
+                return Vec::new();
+            }
         }
         let mut main_fixed_ref = db_record.fixed_ref;
         for address in db_record.addresses.iter() {
-            FofdbofRecord::construct_record(db_record, address, doc_type, &mut records, &mut main_fixed_ref, other_names);
+            if let Err(err) = FofdbofRecord::construct_record(db_record, address, doc_type, &mut records, &mut main_fixed_ref, other_names) {
+                error!("FixedRef {} is not exported : {}", db_record.fixed_ref, err);
+                return Vec::new();
+            }
         }
         records
     }
 
-    fn construct_record(db_record: &SdnRecord, address: &SdnRecordAddress, doc_type: &DocumentType, records: &mut Vec<FofdbofRecord>, main_fixed_ref: &mut i32, other_names: &[DdcName]) {
+    fn construct_record(db_record: &SdnRecord, address: &SdnRecordAddress, doc_type: &DocumentType, records: &mut Vec<FofdbofRecord>, main_fixed_ref: &mut i32, other_names: &[DdcName]) -> Result<(), Box<dyn Error>> {
         let mut record = FofdbofRecord {
             t_add: address.address.clone(),
             t_cit: address.city.clone(),
             t_sta: address.state.clone(),
             t_bad: '0',
-            t_dsg: if doc_type == &DocumentType::OFAC { "OFAC".to_owned() } else { "OFAC-NS".to_owned() },
+            t_dsg: doc_type.designation().to_owned(),
             t_typ: 'V',
             t_syc: String::from(""),
             t_syk: String::from(""),
             t_sys: String::from(""),
-            t_ori: if doc_type == &DocumentType::OFAC { "OFAC".to_owned() } else { "OFAC-NS".to_owned() },
+            t_ori: doc_type.designation().to_owned(),
             t_name: db_record.name.to_uppercase(),
             record_type: if address.is_primary { RecordType::Main } else { RecordType::Alternative },
             doc_type: *doc_type,
             ..Default::default()
         };
         if record.record_type == RecordType::Alternative {
-            record.t_us2 = Some(FofdbofRecord::compute_oid_alternative(*main_fixed_ref));
+            record.t_us2 = Some(record.compute_oid_alternative(*main_fixed_ref)?);
         }
         if record.record_type == RecordType::Main {
             *main_fixed_ref = db_record.fixed_ref;
         }
-        record.compute_oid(*main_fixed_ref, address.id);
+        record.compute_oid(*main_fixed_ref, address.id)?;
         record.compute_typ(db_record.partysubtypeid, &db_record.programs, other_names);
         let mut all_aliases = db_record.normal_aliases.clone();
         all_aliases.append(&mut db_record.low_aliases.clone());
         all_aliases.append(&mut db_record.ddc_low_aliases.clone());
         all_aliases.append(&mut db_record.ddc_normal_aliases.clone());
-        record.compute_syn(!all_aliases.is_empty(), db_record.fixed_ref);
+        record.compute_syn(!all_aliases.is_empty(), db_record.fixed_ref)?;
         match record.t_typ {
             'A' | 'P' => {}
             'C' => record.compute_shk(db_record.partysubtypeid, &db_record.documents),
@@ -150,6 +156,7 @@ impl FofdbofRecord {
         record.compute_ctr(address);
         record.compute_gdr(&db_record.gender);
         records.push(record);
+        Ok(())
     }
 
     fn compute_gdr(&mut self, gender: &str) {
@@ -211,22 +218,22 @@ impl FofdbofRecord {
         let mut cedula = String::new();
         let mut other_docs = String::new();
         for document in documents {
-            if document.doc_type == 1570 {
+            if document.kind() == Some(DocumentKind::Cedula) {
                 document.extract_inf(&mut cedula);
             }
-            if document.doc_type == 1571 {
+            if document.kind() == Some(DocumentKind::Passport) {
                 document.extract_inf(&mut passports);
             }
-            if partysubtypeid == 1 && [1626, 91264].contains(&document.doc_type) {
+            if partysubtypeid == 1 && matches!(document.kind(), Some(DocumentKind::VesselRegistration | DocumentKind::Mmsi)) {
                 document.extract_inf_with_doc_name(&mut other_docs);
             }
-            if partysubtypeid == 2 && document.doc_type == 1623 {
+            if partysubtypeid == 2 && document.kind() == Some(DocumentKind::Aircraft) {
                 document.extract_inf_with_doc_name(&mut other_docs);
             }
-            if partysubtypeid == 3 && document.doc_type != 1626 {
+            if partysubtypeid == 3 && document.kind() != Some(DocumentKind::VesselRegistration) {
                 document.extract_inf_with_doc_name(&mut other_docs);
             }
-            if partysubtypeid == 4 && ![1571, 1570, 1584].contains(&document.doc_type) {
+            if partysubtypeid == 4 && !matches!(document.kind(), Some(DocumentKind::Passport | DocumentKind::Cedula | DocumentKind::NationalId)) {
                 document.extract_inf_with_doc_name(&mut other_docs);
             }
         }
@@ -259,6 +266,7 @@ impl FofdbofRecord {
         let vessel_owner = Vec::from([db_record.vessel_owner.to_owned()]);
         let other_vessel_call_sign = Vec::from([db_record.other_vessel_call_sign.to_owned()]);
         let manufacturer_serial_number = Vec::from([db_record.msn.to_owned()]);
+        let digital_currency_addresses: Vec<String> = db_record.digital_currency_addresses.iter().map(|address| format!("{} {}", address.currency, address.address)).collect();
 
         let mut fields: AddInfoFields = Vec::from([
             (
@@ -279,6 +287,22 @@ impl FofdbofRecord {
                 },
                 &db_record.dobs[..],
             ),
+            (
+                2,
+                AddInfoTemplate {
+                    title: "FABRICATED DOB".to_owned(),
+                    ..Default::default()
+                },
+                &db_record.fabricated_dobs[..],
+            ),
+            (
+                2,
+                AddInfoTemplate {
+                    title: "DOB OF UNKNOWN RELIABILITY".to_owned(),
+                    ..Default::default()
+                },
+                &db_record.unknown_reliability_dobs[..],
+            ),
             (
                 3,
                 AddInfoTemplate {
@@ -442,6 +466,14 @@ impl FofdbofRecord {
                 },
                 &gender[..],
             ),
+            (
+                23,
+                AddInfoTemplate {
+                    title: "DIGITAL CURRENCY ADDRESS".to_owned(),
+                    ..Default::default()
+                },
+                &digital_currency_addresses[..],
+            ),
             (
                 25,
                 AddInfoTemplate {
@@ -586,7 +618,7 @@ impl FofdbofRecord {
             },
             &other,
         ));
-        fields.sort_by(|f1, f2| f1.0.cmp(&f2.0));
+        fields.sort_by_key(|f| f.0);
         for field in fields {
             self.extract_inf(&field);
         }
@@ -632,11 +664,11 @@ impl FofdbofRecord {
         match self.t_typ {
             'A' => {}
             'P' => {
-                self.t_nid = filter_documents(documents, &[1570, 1572, 1584], ' ', Some(&RE_REPLACE_DOCUMENTS));
+                self.t_nid = filter_documents(documents, &[DocumentKind::Cedula, DocumentKind::Nit, DocumentKind::NationalId], ' ', Some(&RE_REPLACE_DOCUMENTS));
             }
             'V' => {
                 if partysubtypeid == 1 {
-                    self.t_nid = filter_documents(documents, &[1626], ' ', Some(&RE_REPLACE_DOCUMENTS));
+                    self.t_nid = filter_documents(documents, &[DocumentKind::VesselRegistration], ' ', Some(&RE_REPLACE_DOCUMENTS));
                 } else if partysubtypeid == 2 {
                     if msn.is_empty() {
                         self.t_nid = Some(format!("MSN{}", self.t_name));
@@ -656,7 +688,7 @@ impl FofdbofRecord {
     fn compute_shk(&mut self, partysubtypeid: i32, documents: &[SdnRecordDocument]) {
         match partysubtypeid {
             1 => {
-                let documents: Vec<&SdnRecordDocument> = documents.iter().filter(|d| d.doc_type == 1626).collect();
+                let documents: Vec<&SdnRecordDocument> = documents.iter().filter(|d| d.kind() == Some(DocumentKind::VesselRegistration)).collect();
                 for document in documents {
                     self.t_shk.push_str(&document.registration_number);
                 }
@@ -692,7 +724,7 @@ impl FofdbofRecord {
             self.t_pob.pop();
         }
     }
-    fn compute_syn(&mut self, is_alias: bool, fixed_ref: i32) {
+    fn compute_syn(&mut self, is_alias: bool, fixed_ref: i32) -> Result<(), Box<dyn Error>> {
         match self.record_type {
             RecordType::Main => {
                 if is_alias {
@@ -701,10 +733,11 @@ impl FofdbofRecord {
             }
             RecordType::Alternative => {
                 if is_alias {
-                    self.t_syn = FofdbofRecord::compute_oid_alternative(fixed_ref)
+                    self.t_syn = self.compute_oid_alternative(fixed_ref)?
                 }
             }
         }
+        Ok(())
     }
     fn compute_bic(&mut self, bics: &[String], ddc_bics: &[String]) {
         match self.t_typ {
@@ -712,12 +745,19 @@ impl FofdbofRecord {
             _ => {
                 let mut bics_str = String::new();
                 for bic in bics.iter() {
-                    bics_str.push_str(bic);
-                    bics_str.push(' ');
-                    if bic.len() > 6 {
-                        bics_str.push_str(bic.to_string().drain(0..6).as_str());
-                        bics_str.push(' ');
+                    match IdentifierKind::Bic.validate(bic) {
+                        // Bank and country codes of the BIC
+                        Ok(bic) => {
+                            bics_str.push_str(&bic);
+                            bics_str.push(' ');
+                            bics_str.push_str(&bic[..6]);
+                        }
+                        Err(err) => {
+                            warn!("Invalid BIC {} for oid {} : {}", bic, self.t_oid, err);
+                            bics_str.push_str(bic);
+                        }
                     }
+                    bics_str.push(' ');
                 }
                 for bic in ddc_bics.iter() {
                     bics_str.push_str(bic);
@@ -728,39 +768,22 @@ impl FofdbofRecord {
         }
     }
     fn compute_psp(&mut self, documents: &[SdnRecordDocument]) -> Result<(), Box<dyn Error>> {
-        self.t_psp = filter_documents(documents, &[1571], ' ', Some(&RE_REPLACE_DOCUMENTS));
+        self.t_psp = filter_documents(documents, &[DocumentKind::Passport], ' ', Some(&RE_REPLACE_DOCUMENTS));
         Ok(())
     }
-    fn compute_oid(&mut self, fixed_ref: i32, address_id: i32) {
-        let mut loid = String::new();
-        match self.doc_type {
-            DocumentType::OFAC => loid.push_str("OFAC"),
-            DocumentType::OFACNS => loid.push_str("OFNS"),
-        }
-        let mut roid = String::new();
-        match self.record_type {
-            RecordType::Main => {
-                roid.push_str(&fixed_ref.to_string());
-            }
-            RecordType::Alternative => {
-                loid.push('Z');
-                roid.push_str(&address_id.to_string());
-            }
-        }
-        let zeros = 10 - (loid.len() + roid.len());
-        self.t_oid = loid + &format!("{:0>zeros$}", "", zeros = zeros) + &roid;
+    fn compute_oid(&mut self, fixed_ref: i32, address_id: i32) -> Result<(), Box<dyn Error>> {
+        let prefix = self.doc_type.oid_prefix();
+        self.t_oid = match self.record_type {
+            RecordType::Main => oid(prefix, fixed_ref)?,
+            RecordType::Alternative => oid(&format!("{}Z", prefix), address_id)?,
+        };
+        Ok(())
     }
-    fn compute_oid_alternative(fixed_ref: i32) -> String {
-        let loid = String::from("OFAC");
-        let roid = String::from(&fixed_ref.to_string());
-        let zeros = 10 - (loid.len() + roid.len());
-        loid + &format!("{:0>zeros$}", "", zeros = zeros) + &roid
+    fn compute_oid_alternative(&self, fixed_ref: i32) -> Result<String, Box<dyn Error>> {
+        oid(self.doc_type.alternative_oid_prefix(), fixed_ref)
     }
     fn compute_ref(&mut self, last_update: &str) {
-        match self.doc_type {
-            DocumentType::OFAC => self.t_ref = format!("OFAC_{}", last_update),
-            DocumentType::OFACNS => self.t_ref = format!("OFAC-NS_{}", last_update),
-        }
+        self.t_ref = format!("{}_{}", self.doc_type.designation(), last_update);
     }
     fn compute_typ(&mut self, partysubtypeid: i32, programs: &[String], other_names: &[DdcName]) {
         match partysubtypeid {
@@ -789,8 +812,17 @@ impl FofdbofRecord {
     }
 }
 
-fn filter_documents(documents: &[SdnRecordDocument], doctypes: &[i32], separator: char, re: Option<&Regex>) -> Option<String> {
-    let documents: Vec<&SdnRecordDocument> = documents.iter().filter(|d| doctypes.contains(&d.doc_type)).collect();
+/// `prefix` followed by `id` padded with zeros to the length of an OID
+fn oid(prefix: &str, id: i32) -> Result<String, Box<dyn Error>> {
+    let id = id.to_string();
+    match OID_LENGTH.checked_sub(prefix.len() + id.len()) {
+        Some(zeros) => Ok(format!("{}{}{}", prefix, "0".repeat(zeros), id)),
+        None => Err(format!("{}{} does not fit in an OID of {} characters", prefix, id, OID_LENGTH).into()),
+    }
+}
+
+fn filter_documents(documents: &[SdnRecordDocument], kinds: &[DocumentKind], separator: char, re: Option<&Regex>) -> Option<String> {
+    let documents: Vec<&SdnRecordDocument> = documents.iter().filter(|d| d.kind().is_some_and(|kind| kinds.contains(&kind))).collect();
     if documents.is_empty() {
         return None;
     }
@@ -800,12 +832,13 @@ fn filter_documents(documents: &[SdnRecordDocument], doctypes: &[i32], separator
         if let Some(re) = re {
             registration_number = re.replace_all(&registration_number, "").as_ref().to_owned();
         }
-        if document.doc_type == 1626 {
-            if registration_number.starts_with("IMO") {
-                registration_number = registration_number[3..].to_owned();
-            } else {
-                return None;
-            }
+        // Invalid IMO numbers are exported without their prefix, they are reported by the IdentifiersReport of the import
+        if document.kind() == Some(DocumentKind::VesselRegistration) {
+            registration_number = match (&document.normalized_number, registration_number.strip_prefix("IMO")) {
+                (Some(imo), _) => imo.clone(),
+                (None, Some(number)) => number.to_owned(),
+                (None, None) => return None,
+            };
         }
         document_str.push_str(&registration_number);
         document_str.push(separator);
@@ -815,6 +848,7 @@ fn filter_documents(documents: &[SdnRecordDocument], doctypes: &[i32], separator
 }
 
 impl Display for FofdbofRecord {
+    #[allow(clippy::write_literal)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut inf = &self.t_inf[..];
         if inf.is_empty() {
@@ -867,9 +901,9 @@ impl Display for FofdbofRecord {
             *SEPARATOR,
             self.t_nid.as_deref().unwrap_or("").to_uppercase(),
             *SEPARATOR,
-            if self.t_pob.is_empty() { ("").to_owned() } else { self.t_pob.to_uppercase() },
+            if self.t_pob.is_empty() { String::new() } else { self.t_pob.to_uppercase() },
             *SEPARATOR,
-            if self.t_dob.is_empty() { ("").to_owned() } else { self.t_dob.to_uppercase() },
+            if self.t_dob.is_empty() { String::new() } else { self.t_dob.to_uppercase() },
             *SEPARATOR,
             "", // BGH
             *SEPARATOR,
@@ -901,8 +935,11 @@ impl Display for FofdbofRecord {
     }
 }
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default, clippy::vec_init_then_push)]
 mod record {
-    use crate::processor::entity::sdn::SdnRecordAddress;
+    use crate::db::entity::sdn::{self, DocumentEntity};
+    use crate::document::{eu::EuSource, uk::UkSource, un::UnSource};
+    use crate::processor::entity::sdn::{SdnRecordAddress, SdnRecordDigitalCurrencyAddress};
 
     use super::*;
 
@@ -934,4 +971,192 @@ mod record {
 
         assert_eq!(excepted_records, *record);
     }
+
+    #[test]
+    fn format_ofac_ns_alternative_record() {
+        let db_record = SdnRecord {
+            fixed_ref: 17636,
+            addresses: vec![SdnRecordAddress { is_primary: true, ..Default::default() }, SdnRecordAddress { id: 25, ..Default::default() }],
+            normal_aliases: vec!["ALIAS".to_owned()],
+            last_update: "1970/01/01".to_owned(),
+            ..Default::default()
+        };
+        let records = FofdbofRecord::from_db_record(&db_record, &DocumentType::OFACNS, &[]);
+
+        assert_eq!("OFNS017636", records[0].t_oid);
+        assert_eq!("OFNSZ00025", records[1].t_oid);
+        assert_eq!(Some("OFAC017636".to_owned()), records[1].t_us2);
+        assert_eq!("OFAC017636", records[1].t_syn);
+    }
+
+    #[test]
+    fn format_un_party_records() {
+        let un_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CONSOLIDATED_LIST dateGenerated="2022-11-08T10:00:03.012-05:00">
+  <INDIVIDUALS>
+    <INDIVIDUAL>
+      <DATAID>6908555</DATAID>
+      <FIRST_NAME>RI</FIRST_NAME>
+      <SECOND_NAME>WON HO</SECOND_NAME>
+      <UN_LIST_TYPE>DPRK</UN_LIST_TYPE>
+      <REFERENCE_NUMBER>KPi.033</REFERENCE_NUMBER>
+      <LISTED_ON>2016-11-30</LISTED_ON>
+      <INDIVIDUAL_ADDRESS>
+        <COUNTRY>Syrian Arab Republic</COUNTRY>
+      </INDIVIDUAL_ADDRESS>
+      <INDIVIDUAL_ADDRESS>
+        <CITY>Damascus</CITY>
+        <COUNTRY>Syrian Arab Republic</COUNTRY>
+      </INDIVIDUAL_ADDRESS>
+    </INDIVIDUAL>
+  </INDIVIDUALS>
+  <ENTITIES/>
+</CONSOLIDATED_LIST>"#;
+        let document = crate::document::un::read(un_xml.as_bytes(), "").unwrap();
+        let (sdn, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[0], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        let db_record = SdnRecord {
+            fixed_ref: sdn.fixed_ref,
+            addresses: relations.address.iter().map(|address| SdnRecordAddress { id: address.id, city: address.city.clone(), is_primary: address.is_primary, ..Default::default() }).collect(),
+            last_update: "2022/11/08".to_owned(),
+            ..Default::default()
+        };
+        let records = FofdbofRecord::from_db_record(&db_record, &DocumentType::of(&UnSource), &[]);
+
+        assert_eq!(2, records.len());
+        assert_eq!("UNSC010033", records[0].t_oid);
+        let excepted = format!("UNSCZ{:0>5}", relations.address[1].id);
+        assert_eq!(excepted, records[1].t_oid);
+        assert_eq!(Some("UNSC010033".to_owned()), records[1].t_us2);
+    }
+
+    #[test]
+    fn format_uk_group_records() {
+        let conlist_xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<ArrayOfFinancialSanctionsTarget>
+  <FinancialSanctionsTarget>
+    <Name6>EXAMPLE TRADING LLC</Name6>
+    <Address1>Office 12</Address1>
+    <Country>United Arab Emirates</Country>
+    <GroupTypeDescription>Entity</GroupTypeDescription>
+    <AliasType>Primary name</AliasType>
+    <LastUpdated>08/11/2022</LastUpdated>
+    <GroupID>14950</GroupID>
+  </FinancialSanctionsTarget>
+  <FinancialSanctionsTarget>
+    <Name6>EXAMPLE TRADING</Name6>
+    <Address1>Office 14</Address1>
+    <Country>United Arab Emirates</Country>
+    <GroupTypeDescription>Entity</GroupTypeDescription>
+    <AliasType>AKA</AliasType>
+    <LastUpdated>08/11/2022</LastUpdated>
+    <GroupID>14950</GroupID>
+  </FinancialSanctionsTarget>
+  <FinancialSanctionsTarget>
+    <Name6>OUT OF RANGE LLC</Name6>
+    <GroupTypeDescription>Entity</GroupTypeDescription>
+    <AliasType>Primary name</AliasType>
+    <LastUpdated>08/11/2022</LastUpdated>
+    <GroupID>1000000</GroupID>
+  </FinancialSanctionsTarget>
+</ArrayOfFinancialSanctionsTarget>"#;
+        let document = crate::document::uk::read(conlist_xml.as_bytes(), "").unwrap();
+        assert_eq!(1, document.distinct_parties.parties.len());
+        let (sdn, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[0], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        let db_record = SdnRecord {
+            fixed_ref: sdn.fixed_ref,
+            addresses: relations.address.iter().map(|address| SdnRecordAddress { id: address.id, address: address.address.clone(), is_primary: address.is_primary, ..Default::default() }).collect(),
+            last_update: "2022/11/08".to_owned(),
+            ..Default::default()
+        };
+        let records = FofdbofRecord::from_db_record(&db_record, &DocumentType::of(&UkSource), &[]);
+
+        assert_eq!(2, records.len());
+        assert_eq!("UKHM014950", records[0].t_oid);
+        let excepted = format!("UKHMZ{:0>5}", relations.address[1].id);
+        assert_eq!(excepted, records[1].t_oid);
+        assert_eq!(Some("UKHM014950".to_owned()), records[1].t_us2);
+    }
+
+    #[test]
+    fn skip_oid_overflow() {
+        assert_eq!("UNSCZ00025", oid("UNSCZ", 25).unwrap());
+        assert!(oid("UNSCZ", 55_003_301).is_err());
+        let db_record = SdnRecord {
+            fixed_ref: 55_003_301,
+            addresses: vec![SdnRecordAddress { is_primary: true, ..Default::default() }],
+            last_update: "1970/01/01".to_owned(),
+            ..Default::default()
+        };
+        assert!(FofdbofRecord::from_db_record(&db_record, &DocumentType::of(&UnSource), &[]).is_empty());
+    }
+
+    #[test]
+    fn aircraft_other_documents() {
+        let document = |doc_type: i32, doc_type_value: &str, registration_number: &str| SdnRecordDocument {
+            doc_type,
+            doc_type_value: doc_type_value.to_owned(),
+            registration_number: registration_number.to_owned(),
+            ..Default::default()
+        };
+        let documents = vec![document(1623, "Unknown aircraft document", "28869"), document(1571, "Passport", "A123")];
+        let (_, _, other_docs) = FofdbofRecord::default().compute_inf_documents(&documents, 2);
+
+        assert_eq!("UNKNOWN AIRCRAFT DOCUMENT 28869", other_docs);
+    }
+
+    #[test]
+    fn export_invalid_imo_numbers() {
+        let vessel = |registration_number: &str, normalized_number: Option<&str>| SdnRecordDocument {
+            doc_type: 1626,
+            doc_type_value: "Vessel Registration Identification".to_owned(),
+            registration_number: registration_number.to_owned(),
+            normalized_number: normalized_number.map(str::to_owned),
+            ..Default::default()
+        };
+        let documents = vec![vessel("IMO 9187629", Some("9187629")), vessel("IMO 9187620", None)];
+        let excepted = Some("9187629 9187620".to_owned());
+
+        assert_eq!(excepted, filter_documents(&documents, &[DocumentKind::VesselRegistration], ' ', Some(&RE_REPLACE_DOCUMENTS)));
+    }
+
+    #[test]
+    fn format_eu_main_record() {
+        let db_record = SdnRecord {
+            fixed_ref: 13,
+            addresses: vec![SdnRecordAddress { is_primary: true, ..Default::default() }],
+            last_update: "1970/01/01".to_owned(),
+            ..Default::default()
+        };
+        let record = &FofdbofRecord::from_db_record(&db_record, &DocumentType::of(&EuSource), &[])[0];
+
+        assert_eq!("EU", record.t_dsg);
+        assert_eq!("EU", record.t_ori);
+        assert_eq!("EUFS000013", record.t_oid);
+        assert_eq!("EU_1970/01/01", record.t_ref);
+    }
+
+    #[test]
+    fn format_digital_currency_addresses_in_inf() {
+        let db_record = SdnRecord {
+            fixed_ref: 36,
+            addresses: vec![SdnRecordAddress { is_primary: true, ..Default::default() }],
+            digital_currency_addresses: vec![
+                SdnRecordDigitalCurrencyAddress {
+                    currency: "TRX".to_owned(),
+                    address: "TQn9Y2khEsLJW1ChVWFMSMeRDow5KcbLSE".to_owned(),
+                },
+                SdnRecordDigitalCurrencyAddress {
+                    currency: "XBT".to_owned(),
+                    address: "1AjZPMsnmpdK2Rv9KQNfMurTXinscVro9V".to_owned(),
+                },
+            ],
+            last_update: "1970/01/01".to_owned(),
+            ..Default::default()
+        };
+        let record = &FofdbofRecord::from_db_record(&db_record, &DocumentType::OFAC, &[])[0];
+
+        assert!(record
+            .t_inf
+            .contains("DIGITAL CURRENCY ADDRESS TRX TQn9Y2khEsLJW1ChVWFMSMeRDow5KcbLSE/XBT 1AjZPMsnmpdK2Rv9KQNfMurTXinscVro9V;"));
+    }
 }
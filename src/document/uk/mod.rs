@@ -0,0 +1,384 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::io::BufRead;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use log::warn;
+use quick_xml::DeError;
+use serde::Deserialize;
+
+use crate::document::inputs::builder::{date_period_day, date_period_month, date_period_year, Address, NamePart, OfacDocumentBuilder, PartyBuilder, PartyKind};
+use crate::document::inputs::known::{DocumentKind, KnownValue};
+use crate::document::inputs::list::ListSource;
+use crate::document::inputs::models::feature::DatePeriod;
+use crate::document::inputs::source::SourceReader;
+use crate::document::inputs::{DocumentType, OfacDocument};
+
+/// Upper bound (excluded) of the GroupIDs, the GroupID is the FixedRef and must fit in the 6 digits of the OIDs
+const MAX_GROUP_ID: i32 = 1_000_000;
+
+/// Root of the OFSI consolidated list xml (`ConList.xml`)
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct ArrayOfFinancialSanctionsTarget {
+    #[serde(rename = "FinancialSanctionsTarget", default)]
+    pub targets: Vec<FinancialSanctionsTarget>,
+}
+
+/// A name of a group, the details of the group are repeated on each of its names
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct FinancialSanctionsTarget {
+    /// Surname of an individual, or name of an entity or a ship
+    #[serde(rename = "Name6")]
+    pub name6: Option<String>,
+    #[serde(rename = "Name1")]
+    pub name1: Option<String>,
+    #[serde(rename = "Name2")]
+    pub name2: Option<String>,
+    #[serde(rename = "Name3")]
+    pub name3: Option<String>,
+    #[serde(rename = "Name4")]
+    pub name4: Option<String>,
+    #[serde(rename = "Name5")]
+    pub name5: Option<String>,
+    #[serde(rename = "Title")]
+    pub title: Option<String>,
+    #[serde(rename = "NameNonLatinScript")]
+    pub name_non_latin_script: Option<String>,
+    /// `dd/mm/yyyy`, unknown parts are written `00`
+    #[serde(rename = "DOB")]
+    pub dob: Option<String>,
+    #[serde(rename = "TownOfBirth")]
+    pub town_of_birth: Option<String>,
+    #[serde(rename = "CountryOfBirth")]
+    pub country_of_birth: Option<String>,
+    #[serde(rename = "Nationality")]
+    pub nationality: Option<String>,
+    #[serde(rename = "PassportDetails")]
+    pub passport_details: Option<String>,
+    #[serde(rename = "NINumber")]
+    pub ni_number: Option<String>,
+    #[serde(rename = "Position")]
+    pub position: Option<String>,
+    #[serde(rename = "Address1")]
+    pub address1: Option<String>,
+    #[serde(rename = "Address2")]
+    pub address2: Option<String>,
+    #[serde(rename = "Address3")]
+    pub address3: Option<String>,
+    #[serde(rename = "Address4")]
+    pub address4: Option<String>,
+    #[serde(rename = "Address5")]
+    pub address5: Option<String>,
+    #[serde(rename = "Address6")]
+    pub address6: Option<String>,
+    #[serde(rename = "PostCode")]
+    pub post_code: Option<String>,
+    #[serde(rename = "Country")]
+    pub country: Option<String>,
+    #[serde(rename = "OtherInformation")]
+    pub other_information: Option<String>,
+    /// `Individual`, `Entity` or `Ship`
+    #[serde(rename = "GroupTypeDescription")]
+    pub group_type_description: String,
+    /// `Primary name`, `Primary name variation` or `AKA`
+    #[serde(rename = "AliasType")]
+    pub alias_type: Option<String>,
+    /// `Good quality` or `Low quality`
+    #[serde(rename = "AliasQuality")]
+    pub alias_quality: Option<String>,
+    #[serde(rename = "Regime", alias = "RegimeName")]
+    pub regime: Option<String>,
+    #[serde(rename = "ListedOn", alias = "DateListed")]
+    pub listed_on: Option<String>,
+    #[serde(rename = "UKSanctionsListRef")]
+    pub uk_sanctions_list_ref: Option<String>,
+    #[serde(rename = "LastUpdated")]
+    pub last_updated: Option<String>,
+    #[serde(rename = "GroupID")]
+    pub group_id: i32,
+}
+
+/// Empty elements are written `<Name2 />` in the OFSI list
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(|value| value.trim()).filter(|value| !value.is_empty())
+}
+
+/// A date written `dd/mm/yyyy` (with an optional time) or `yyyy-mm-dd`
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value.split_whitespace().next()?;
+    NaiveDate::parse_from_str(value, "%d/%m/%Y").ok().or_else(|| NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok())
+}
+
+/// A date of birth, `00/00/1960` is a year and `00/05/1960` a month
+fn parse_dob(value: &str) -> Option<DatePeriod> {
+    let parts: Vec<u32> = value.trim().split('/').map(|part| part.trim().parse().ok()).collect::<Option<_>>()?;
+    match parts[..] {
+        [0, 0, year] => Some(date_period_year(year as i32, false)),
+        [0, month, year] => Some(date_period_month(year as i32, month, false)),
+        [day, month, year] => Some(date_period_day(NaiveDate::from_ymd_opt(year as i32, month, day)?, false)),
+        _ => None,
+    }
+}
+
+fn party_kind(group_type: &str) -> Option<PartyKind> {
+    match group_type.trim().to_lowercase().as_str() {
+        "individual" => Some(PartyKind::Individual),
+        "entity" => Some(PartyKind::Entity),
+        "ship" => Some(PartyKind::Vessel),
+        _ => None,
+    }
+}
+
+fn add_name(party: &mut PartyBuilder, kind: PartyKind, target: &FinancialSanctionsTarget) {
+    let alias_type = non_empty(&target.alias_type).unwrap_or_default();
+    let primary = alias_type.eq_ignore_ascii_case("Primary name");
+    let low_quality = non_empty(&target.alias_quality).map(|quality| quality.to_lowercase().starts_with("low")).unwrap_or(false);
+    let mut parts = Vec::new();
+    match kind {
+        PartyKind::Individual => {
+            parts.extend(non_empty(&target.name1).map(|name| (NamePart::FirstName, name)));
+            let middle_names: Vec<&str> = [&target.name2, &target.name3, &target.name4, &target.name5].into_iter().filter_map(non_empty).collect();
+            let middle_names = middle_names.join(" ");
+            if !middle_names.is_empty() {
+                parts.push((NamePart::MiddleName, middle_names.as_str()));
+            }
+            parts.extend(non_empty(&target.name6).map(|name| (NamePart::LastName, name)));
+            party.name(None, primary, low_quality, &parts);
+        }
+        PartyKind::Vessel => party.name(None, primary, low_quality, &[(NamePart::VesselName, non_empty(&target.name6).unwrap_or_default())]),
+        _ => party.name(None, primary, low_quality, &[(NamePart::EntityName, non_empty(&target.name6).unwrap_or_default())]),
+    }
+}
+
+/// Details repeated on every row of a group are only added once
+///
+/// The list gives no id to the names, features and documents, the builder gives them one
+fn add_details(party: &mut PartyBuilder, seen: &mut HashSet<(i32, String)>, target: &FinancialSanctionsTarget) {
+    let mut first = |feature_type: i32, value: &str| seen.insert((feature_type, value.to_lowercase()));
+    if let Some(title) = non_empty(&target.title).filter(|title| first(26, title)) {
+        party.feature_value(None, 26, title);
+    }
+    if let Some(period) = non_empty(&target.dob).filter(|dob| first(8, dob)).and_then(parse_dob) {
+        party.feature_date(None, 8, period);
+    }
+    let place: Vec<&str> = [&target.town_of_birth, &target.country_of_birth].into_iter().filter_map(non_empty).collect();
+    let place = place.join(", ");
+    if !place.is_empty() && first(9, &place) {
+        party.feature_value(None, 9, &place);
+    }
+    if let Some(nationality) = non_empty(&target.nationality).filter(|nationality| first(10, nationality)) {
+        party.feature_location(None, 10, &Address { country: Some(nationality.to_owned()), ..Default::default() });
+    }
+    let lines: Vec<&str> = [&target.address1, &target.address2, &target.address3, &target.address4, &target.address5, &target.address6].into_iter().filter_map(non_empty).collect();
+    let address = Address {
+        // Only three address lines are kept in a location, the remaining lines are added to the third one
+        address: if lines.len() > 3 { vec![lines[0].to_owned(), lines[1].to_owned(), lines[2..].join(", ")] } else { lines.iter().map(|line| line.to_string()).collect() },
+        postal_code: non_empty(&target.post_code).map(|post_code| post_code.to_owned()),
+        country: non_empty(&target.country).map(|country| country.to_owned()),
+        ..Default::default()
+    };
+    if !address.is_empty() && first(25, &format!("{:?}", address)) {
+        party.feature_location(None, 25, &address);
+    }
+    if let Some(passport) = non_empty(&target.passport_details).filter(|passport| first(DocumentKind::Passport.id(), passport)) {
+        party.document(None, "Passport", passport, None, None, None);
+    }
+    if let Some(ni_number) = non_empty(&target.ni_number).filter(|ni_number| first(DocumentKind::NationalId.id(), ni_number)) {
+        party.document(None, "National ID No.", ni_number, None, None, None);
+    }
+    if let Some(regime) = non_empty(&target.regime) {
+        party.program(None, regime);
+    }
+    if let Some(position) = non_empty(&target.position).filter(|position| first(0, position)) {
+        party.comment(&format!("Position: {}.", position));
+    }
+    if let Some(information) = non_empty(&target.other_information).filter(|information| first(0, information)) {
+        party.comment(information);
+    }
+}
+
+/// Read the OFSI consolidated xml into an OfacDocument
+///
+/// Rows are grouped by GroupID, which is used as FixedRef. The list has no publish date, the latest LastUpdated is used as date of issue.
+pub fn read<R: BufRead, P: AsRef<Path>>(reader: R, folder: P) -> Result<OfacDocument, DeError> {
+    let list: ArrayOfFinancialSanctionsTarget = quick_xml::de::from_reader(reader)?;
+    let mut builder = OfacDocumentBuilder::new(folder, DocumentType::of(&UkSource));
+    match list.targets.iter().filter_map(|target| non_empty(&target.last_updated).and_then(parse_date)).max() {
+        Some(date) => builder.date_of_issue(date),
+        None => return Err(DeError::Custom("LastUpdated not found".to_owned())),
+    }
+    let mut groups: Vec<(i32, Vec<&FinancialSanctionsTarget>)> = Vec::new();
+    for target in list.targets.iter() {
+        match groups.iter_mut().find(|(group_id, _)| *group_id == target.group_id) {
+            Some((_, rows)) => rows.push(target),
+            None => groups.push((target.group_id, vec![target])),
+        }
+    }
+    for (group_id, rows) in groups.iter() {
+        let kind = match party_kind(&rows[0].group_type_description) {
+            Some(kind) => kind,
+            None => return Err(DeError::Custom(format!("GroupTypeDescription not recognized {} (GroupID {})", rows[0].group_type_description, group_id))),
+        };
+        if !(0..MAX_GROUP_ID).contains(group_id) {
+            warn!("GroupID {} does not fit in an OID, the group is skipped", group_id);
+            continue;
+        }
+        let mut party = builder.party(*group_id, kind);
+        let mut seen = HashSet::new();
+        for target in rows.iter() {
+            add_name(&mut party, kind, target);
+            if let Some(original) = non_empty(&target.name_non_latin_script).filter(|original| seen.insert((-1, original.to_string()))) {
+                let name_part = if kind == PartyKind::Individual { NamePart::LastName } else { NamePart::EntityName };
+                party.name(None, false, false, &[(name_part, original)]);
+            }
+            add_details(&mut party, &mut seen, target);
+        }
+        if let Some(reference) = rows.iter().find_map(|target| non_empty(&target.uk_sanctions_list_ref)) {
+            party.comment(&format!("UK sanctions list reference {}.", reference));
+        }
+        if let Some(listed_on) = rows.iter().filter_map(|target| non_empty(&target.listed_on).and_then(parse_date)).min() {
+            party.listed_on(listed_on);
+        }
+        party.finish();
+    }
+    Ok(builder.build())
+}
+
+/// UK HM Treasury (OFSI) consolidated list (`ConList.xml`)
+pub struct UkSource;
+
+impl ListSource for UkSource {
+    fn name(&self) -> &'static str {
+        "UK"
+    }
+
+    fn designation(&self) -> &'static str {
+        "UK"
+    }
+
+    fn oid_prefix(&self) -> &'static str {
+        "UKHM"
+    }
+
+    fn file_stem(&self) -> &'static str {
+        "conlist"
+    }
+
+    fn database_url_var(&self) -> &'static str {
+        "UK_DATABASE_URL"
+    }
+
+    fn data_folder_var(&self) -> &'static str {
+        "UK_DATA_FOLDER"
+    }
+
+    fn read(&self, reader: SourceReader, document: &mut OfacDocument) -> Result<(), Box<dyn Error>> {
+        let content = read(reader, &document.root_folder)?;
+        document.set_content(content);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod uk {
+    use super::*;
+    use crate::db::entity::sdn::{self, DocumentEntity};
+
+    const CONLIST_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<ArrayOfFinancialSanctionsTarget xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <FinancialSanctionsTarget>
+    <Name6>HAQQANI</Name6>
+    <Name1>Abdul</Name1>
+    <Name2>Aziz</Name2>
+    <Name3 />
+    <Title>Maulavi</Title>
+    <DOB>00/00/1977</DOB>
+    <TownOfBirth>Miram Shah</TownOfBirth>
+    <CountryOfBirth>Pakistan</CountryOfBirth>
+    <Nationality>Afghan</Nationality>
+    <PassportDetails>D000123 (Afghanistan)</PassportDetails>
+    <Position>Member of the Haqqani Network</Position>
+    <OtherInformation>Believed to be in Pakistan.</OtherInformation>
+    <GroupTypeDescription>Individual</GroupTypeDescription>
+    <AliasType>Primary name</AliasType>
+    <AliasQuality>Good quality</AliasQuality>
+    <Regime>Afghanistan</Regime>
+    <ListedOn>04/09/2015</ListedOn>
+    <UKSanctionsListRef>AFG0126</UKSanctionsListRef>
+    <LastUpdated>01/02/2021</LastUpdated>
+    <GroupID>13274</GroupID>
+  </FinancialSanctionsTarget>
+  <FinancialSanctionsTarget>
+    <Name6>Yahya</Name6>
+    <Title>Maulavi</Title>
+    <DOB>00/00/1977</DOB>
+    <TownOfBirth>Miram Shah</TownOfBirth>
+    <CountryOfBirth>Pakistan</CountryOfBirth>
+    <Nationality>Afghan</Nationality>
+    <PassportDetails>D000123 (Afghanistan)</PassportDetails>
+    <Position>Member of the Haqqani Network</Position>
+    <OtherInformation>Believed to be in Pakistan.</OtherInformation>
+    <GroupTypeDescription>Individual</GroupTypeDescription>
+    <AliasType>AKA</AliasType>
+    <AliasQuality>Low quality</AliasQuality>
+    <Regime>Afghanistan</Regime>
+    <ListedOn>04/09/2015</ListedOn>
+    <UKSanctionsListRef>AFG0126</UKSanctionsListRef>
+    <LastUpdated>01/02/2021</LastUpdated>
+    <GroupID>13274</GroupID>
+  </FinancialSanctionsTarget>
+  <FinancialSanctionsTarget>
+    <Name6>EXAMPLE TRADING LLC</Name6>
+    <Address1>Office 12</Address1>
+    <Address2>Building 4</Address2>
+    <Address3>Dubai Marina</Address3>
+    <Address4>Dubai</Address4>
+    <Country>United Arab Emirates</Country>
+    <GroupTypeDescription>Entity</GroupTypeDescription>
+    <AliasType>Primary name</AliasType>
+    <RegimeName>Russia</RegimeName>
+    <DateListed>15/03/2022</DateListed>
+    <LastUpdated>08/11/2022</LastUpdated>
+    <GroupID>14950</GroupID>
+  </FinancialSanctionsTarget>
+</ArrayOfFinancialSanctionsTarget>"#;
+
+    #[test]
+    fn read_conlist_xml() {
+        let document = read(CONLIST_XML.as_bytes(), "").unwrap();
+        assert_eq!(NaiveDate::from_ymd(2022, 11, 8), document.references.date_of_issue.to_sql_date());
+        assert_eq!(2, document.distinct_parties.parties.len());
+        assert_eq!(1, document.documents.documents.len());
+        assert_eq!(1571, document.documents.documents[0].type_id);
+
+        let (individual, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[0], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!(13274, individual.fixed_ref);
+        assert_eq!("INDIVIDUAL", individual.sdn_type);
+        assert_eq!(Some(NaiveDate::from_ymd(2015, 9, 4)), individual.sanction_date);
+        assert_eq!(2, relations.names.len());
+        assert_eq!("NAME", relations.names[0].name_type);
+        assert_eq!(Some("Aziz".to_owned()), relations.names[0].middle_name);
+        assert_eq!(Some("HAQQANI".to_owned()), relations.names[0].last_name);
+        assert_eq!("ALIAS", relations.names[1].name_type);
+        assert_eq!(Some("LOW".to_owned()), relations.names[1].quality);
+        assert_eq!(1, relations.dobs.len());
+        assert_eq!(1, relations.programs.len());
+        assert_eq!(Some("POSITION: MEMBER OF THE HAQQANI NETWORK. BELIEVED TO BE IN PAKISTAN. UK SANCTIONS LIST REFERENCE AFG0126.".to_owned()), individual.comment);
+
+        let (entity, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[1], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!("ENTITY", entity.sdn_type);
+        assert_eq!(Some("EXAMPLE TRADING LLC".to_owned()), relations.names[0].entity_name);
+        assert_eq!("RUSSIA", relations.programs[0].program);
+        assert_eq!(1, relations.address.len());
+    }
+
+    #[test]
+    fn parse_dob_shapes() {
+        assert_eq!("1960", parse_dob("00/00/1960").unwrap().start.unwrap().from.year);
+        assert_eq!("31", parse_dob("00/05/1960").unwrap().end.unwrap().to.day);
+        assert_eq!(Some(NaiveDate::from_ymd(1960, 5, 17)), parse_dob("17/05/1960").unwrap().parse_from_to());
+        assert!(parse_dob("1960").is_none());
+        assert_eq!(Some(NaiveDate::from_ymd(2022, 11, 8)), parse_date("08/11/2022 10:01:00"));
+    }
+}
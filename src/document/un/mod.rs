@@ -0,0 +1,453 @@
+use std::error::Error;
+use std::io::BufRead;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use quick_xml::DeError;
+use serde::Deserialize;
+
+use crate::document::inputs::builder::{date_period_day, date_period_year, date_period_years, synthetic_id, Address, NamePart, OfacDocumentBuilder, PartyBuilder, PartyKind};
+use crate::document::inputs::list::ListSource;
+use crate::document::inputs::reader::read_root_attribute;
+use crate::document::inputs::models::feature::DatePeriod;
+use crate::document::inputs::source::SourceReader;
+use crate::document::inputs::{DocumentType, OfacDocument};
+
+/// Regimes of the reference numbers, by order of appearance in the list
+///
+/// The FixedRef of a reference number depends on the rank of its regime, new regimes must be added at the end
+const REGIMES: &[&str] = &["QD", "TA", "IQ", "CD", "SO", "KP", "IR", "SD", "LY", "CF", "YE", "SS", "ML", "GB", "HT"];
+
+/// FixedRefs of reference numbers which don't follow the `QDi.001` pattern (or of an unknown regime) start here,
+/// every FixedRef has at most 6 digits so that it fits in the OIDs
+const UNMATCHED_REFERENCE_FIXED_REF: i32 = 100_000;
+
+/// Upper bound (excluded) of the FixedRefs
+const MAX_FIXED_REF: i32 = 1_000_000;
+
+/// Root of the UN Security Council consolidated list xml
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct ConsolidatedList {
+    /// e.g. `2022-11-08T10:00:03.012-05:00`
+    #[serde(rename = "dateGenerated")]
+    pub date_generated: String,
+    #[serde(rename = "INDIVIDUALS")]
+    pub individuals: Option<Individuals>,
+    #[serde(rename = "ENTITIES")]
+    pub entities: Option<Entities>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Individuals {
+    #[serde(rename = "INDIVIDUAL", default)]
+    pub individuals: Vec<UnParty>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Entities {
+    #[serde(rename = "ENTITY", default)]
+    pub entities: Vec<UnParty>,
+}
+
+/// An INDIVIDUAL or an ENTITY, entities only have a FIRST_NAME
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct UnParty {
+    #[serde(rename = "FIRST_NAME")]
+    pub first_name: Option<String>,
+    #[serde(rename = "SECOND_NAME")]
+    pub second_name: Option<String>,
+    #[serde(rename = "THIRD_NAME")]
+    pub third_name: Option<String>,
+    #[serde(rename = "FOURTH_NAME")]
+    pub fourth_name: Option<String>,
+    #[serde(rename = "UN_LIST_TYPE")]
+    pub un_list_type: Option<String>,
+    #[serde(rename = "REFERENCE_NUMBER")]
+    pub reference_number: String,
+    #[serde(rename = "LISTED_ON")]
+    pub listed_on: Option<String>,
+    #[serde(rename = "NAME_ORIGINAL_SCRIPT")]
+    pub name_original_script: Option<String>,
+    #[serde(rename = "GENDER")]
+    pub gender: Option<String>,
+    #[serde(rename = "COMMENTS1")]
+    pub comments: Option<String>,
+    #[serde(rename = "TITLE")]
+    pub title: Option<Values>,
+    #[serde(rename = "DESIGNATION")]
+    pub designation: Option<Values>,
+    #[serde(rename = "NATIONALITY")]
+    pub nationality: Option<Values>,
+    #[serde(rename = "INDIVIDUAL_ALIAS", alias = "ENTITY_ALIAS", default)]
+    pub aliases: Vec<UnAlias>,
+    #[serde(rename = "INDIVIDUAL_ADDRESS", alias = "ENTITY_ADDRESS", default)]
+    pub addresses: Vec<UnAddress>,
+    #[serde(rename = "INDIVIDUAL_DATE_OF_BIRTH", default)]
+    pub dates_of_birth: Vec<UnDateOfBirth>,
+    #[serde(rename = "INDIVIDUAL_PLACE_OF_BIRTH", default)]
+    pub places_of_birth: Vec<UnAddress>,
+    #[serde(rename = "INDIVIDUAL_DOCUMENT", default)]
+    pub documents: Vec<UnDocument>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Values {
+    #[serde(rename = "VALUE", default)]
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct UnAlias {
+    /// `Good`, `Low` or empty
+    #[serde(rename = "QUALITY")]
+    pub quality: Option<String>,
+    #[serde(rename = "ALIAS_NAME")]
+    pub alias_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct UnAddress {
+    #[serde(rename = "STREET")]
+    pub street: Option<String>,
+    #[serde(rename = "CITY")]
+    pub city: Option<String>,
+    #[serde(rename = "STATE_PROVINCE")]
+    pub state_province: Option<String>,
+    #[serde(rename = "ZIP_CODE")]
+    pub zip_code: Option<String>,
+    #[serde(rename = "COUNTRY")]
+    pub country: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct UnDateOfBirth {
+    /// `EXACT`, `APPROXIMATELY` or `BETWEEN`
+    #[serde(rename = "TYPE_OF_DATE")]
+    pub type_of_date: Option<String>,
+    #[serde(rename = "DATE")]
+    pub date: Option<String>,
+    #[serde(rename = "YEAR")]
+    pub year: Option<String>,
+    #[serde(rename = "FROM_YEAR")]
+    pub from_year: Option<String>,
+    #[serde(rename = "TO_YEAR")]
+    pub to_year: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct UnDocument {
+    #[serde(rename = "TYPE_OF_DOCUMENT")]
+    pub type_of_document: Option<String>,
+    #[serde(rename = "NUMBER")]
+    pub number: Option<String>,
+    #[serde(rename = "ISSUING_COUNTRY")]
+    pub issuing_country: Option<String>,
+    #[serde(rename = "COUNTRY_OF_ISSUE")]
+    pub country_of_issue: Option<String>,
+    #[serde(rename = "DATE_OF_ISSUE")]
+    pub date_of_issue: Option<String>,
+}
+
+/// FixedRef of a UN reference number
+///
+/// `KPi.033` is read as the regime `KP`, the kind `i` (or `e` for entities) and the number `33`, which gives
+/// `((regime * 2 + kind) * 1000) + number` where the regime is its rank in `REGIMES`. Reference numbers
+/// that don't follow this pattern are hashed above every computed FixedRef.
+pub fn fixed_ref(reference_number: &str) -> i32 {
+    let reference_number = reference_number.trim();
+    let parsed = (|| {
+        let (prefix, number) = reference_number.split_once('.')?;
+        let number: i32 = number.trim().parse().ok().filter(|number| (0..1000).contains(number))?;
+        if prefix.len() != 3 || !prefix.is_char_boundary(2) {
+            return None;
+        }
+        let kind = match &prefix[2..] {
+            "i" | "I" => 0,
+            "e" | "E" => 1,
+            _ => return None,
+        };
+        let regime = REGIMES.iter().position(|regime| regime.eq_ignore_ascii_case(&prefix[..2]))? as i32;
+        Some((regime * 2 + kind) * 1000 + number)
+    })();
+    parsed.unwrap_or_else(|| UNMATCHED_REFERENCE_FIXED_REF + (synthetic_id(reference_number) % (MAX_FIXED_REF - UNMATCHED_REFERENCE_FIXED_REF)))
+}
+
+/// Empty elements are written `<THIRD_NAME/>` in the UN list
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(|value| value.trim()).filter(|value| !value.is_empty())
+}
+
+fn parse_iso_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn date_of_birth_period(dob: &UnDateOfBirth) -> Option<DatePeriod> {
+    let approximate = non_empty(&dob.type_of_date).map(|kind| kind.eq_ignore_ascii_case("APPROXIMATELY")).unwrap_or(false);
+    if let Some(date) = non_empty(&dob.date).and_then(parse_iso_date) {
+        return Some(date_period_day(date, approximate));
+    }
+    if let Some(year) = non_empty(&dob.year).and_then(|year| year.parse().ok()) {
+        return Some(date_period_year(year, approximate));
+    }
+    let from = non_empty(&dob.from_year)?.parse().ok()?;
+    let to = non_empty(&dob.to_year)?.parse().ok()?;
+    Some(date_period_years(from, to))
+}
+
+/// Documents the exporters know about are given their Ofac type
+fn document_type(type_of_document: &str) -> &str {
+    match type_of_document.to_lowercase().as_str() {
+        "passport" => "Passport",
+        "national identification number" | "national identity card" => "National ID No.",
+        _ => type_of_document,
+    }
+}
+
+fn to_address(address: &UnAddress) -> Address {
+    Address {
+        address: non_empty(&address.street).map(|street| street.to_owned()).into_iter().collect(),
+        city: non_empty(&address.city).map(|city| city.to_owned()),
+        state: non_empty(&address.state_province).map(|state| state.to_owned()),
+        postal_code: non_empty(&address.zip_code).map(|zip_code| zip_code.to_owned()),
+        country: non_empty(&address.country).map(|country| country.to_owned()),
+        ..Default::default()
+    }
+}
+
+/// The UN list gives no id to the names, features and documents, the builder gives them one
+fn add_party(party: &mut PartyBuilder, kind: PartyKind, un_party: &UnParty) {
+    let other_names: Vec<&str> = [&un_party.second_name, &un_party.third_name, &un_party.fourth_name].into_iter().filter_map(non_empty).collect();
+    let other_names = other_names.join(" ");
+    let mut parts = Vec::new();
+    match kind {
+        PartyKind::Individual => {
+            parts.extend(non_empty(&un_party.first_name).map(|name| (NamePart::FirstName, name)));
+            if !other_names.is_empty() {
+                parts.push((NamePart::LastName, other_names.as_str()));
+            }
+        }
+        _ => parts.extend(non_empty(&un_party.first_name).map(|name| (NamePart::EntityName, name))),
+    }
+    party.name(None, true, false, &parts);
+    let name_part = if kind == PartyKind::Individual { NamePart::LastName } else { NamePart::EntityName };
+    if let Some(original) = non_empty(&un_party.name_original_script) {
+        party.name(None, false, false, &[(name_part, original)]);
+    }
+    for alias in un_party.aliases.iter() {
+        if let Some(name) = non_empty(&alias.alias_name) {
+            let low_quality = non_empty(&alias.quality).map(|quality| quality.eq_ignore_ascii_case("Low")).unwrap_or(false);
+            party.name(None, false, low_quality, &[(name_part, name)]);
+        }
+    }
+    if let Some(program) = non_empty(&un_party.un_list_type) {
+        party.program(None, program);
+    }
+    if let Some(listed_on) = non_empty(&un_party.listed_on).and_then(parse_iso_date) {
+        party.listed_on(listed_on);
+    }
+    if let Some(comments) = non_empty(&un_party.comments) {
+        party.comment(comments);
+    }
+    for designation in un_party.designation.iter().flat_map(|values| values.values.iter()) {
+        party.comment(&format!("Designation: {}.", designation.trim()));
+    }
+    if let Some(title) = un_party.title.iter().flat_map(|values| values.values.iter()).next() {
+        party.feature_value(None, 26, title);
+    }
+    match non_empty(&un_party.gender).map(|gender| gender.to_lowercase()).as_deref() {
+        Some("male") => party.gender(None, true),
+        Some("female") => party.gender(None, false),
+        _ => {}
+    }
+    for nationality in un_party.nationality.iter().flat_map(|values| values.values.iter()) {
+        party.feature_location(None, 10, &Address { country: Some(nationality.to_owned()), ..Default::default() });
+    }
+    for dob in un_party.dates_of_birth.iter() {
+        if let Some(period) = date_of_birth_period(dob) {
+            party.feature_date(None, 8, period);
+        }
+    }
+    for pob in un_party.places_of_birth.iter() {
+        let place: Vec<&str> = [&pob.city, &pob.state_province, &pob.country].into_iter().filter_map(non_empty).collect();
+        if !place.is_empty() {
+            party.feature_value(None, 9, &place.join(", "));
+        }
+    }
+    for address in un_party.addresses.iter() {
+        party.feature_location(None, 25, &to_address(address));
+    }
+    for document in un_party.documents.iter() {
+        let number = non_empty(&document.number).unwrap_or_default();
+        let doc_type = document_type(non_empty(&document.type_of_document).unwrap_or("Other"));
+        let country = non_empty(&document.issuing_country).or_else(|| non_empty(&document.country_of_issue));
+        let issued = non_empty(&document.date_of_issue).and_then(parse_iso_date);
+        party.document(None, doc_type, number, country, issued, None);
+    }
+}
+
+/// Read the UN Security Council consolidated xml into an OfacDocument, see [`fixed_ref`] for the FixedRefs
+pub fn read<R: BufRead, P: AsRef<Path>>(reader: R, folder: P) -> Result<OfacDocument, DeError> {
+    let list: ConsolidatedList = quick_xml::de::from_reader(reader)?;
+    let mut builder = OfacDocumentBuilder::new(folder, DocumentType::of(&UnSource));
+    match parse_iso_date(&list.date_generated) {
+        Some(date) => builder.date_of_issue(date),
+        None => return Err(DeError::Custom(format!("dateGenerated not recognized {}", list.date_generated))),
+    }
+    let individuals = list.individuals.iter().flat_map(|individuals| individuals.individuals.iter()).map(|party| (PartyKind::Individual, party));
+    let entities = list.entities.iter().flat_map(|entities| entities.entities.iter()).map(|party| (PartyKind::Entity, party));
+    for (kind, un_party) in individuals.chain(entities) {
+        let fixed_ref = fixed_ref(&un_party.reference_number);
+        if builder.contains(fixed_ref) {
+            return Err(DeError::Custom(format!("Reference number {} is listed twice", un_party.reference_number)));
+        }
+        let mut party = builder.party(fixed_ref, kind);
+        add_party(&mut party, kind, un_party);
+        party.finish();
+    }
+    Ok(builder.build())
+}
+
+/// UN Security Council consolidated list (`consolidated.xml`)
+pub struct UnSource;
+
+impl ListSource for UnSource {
+    fn name(&self) -> &'static str {
+        "UN"
+    }
+
+    fn designation(&self) -> &'static str {
+        "UN"
+    }
+
+    fn oid_prefix(&self) -> &'static str {
+        "UNSC"
+    }
+
+    fn file_stem(&self) -> &'static str {
+        "consolidated"
+    }
+
+    fn database_url_var(&self) -> &'static str {
+        "UN_DATABASE_URL"
+    }
+
+    fn data_folder_var(&self) -> &'static str {
+        "UN_DATA_FOLDER"
+    }
+
+    fn read(&self, reader: SourceReader, document: &mut OfacDocument) -> Result<(), Box<dyn Error>> {
+        let content = read(reader, &document.root_folder)?;
+        document.set_content(content);
+        Ok(())
+    }
+
+    fn read_date_of_issue(&self, reader: SourceReader) -> Result<NaiveDate, Box<dyn Error>> {
+        let date = read_root_attribute(reader, "dateGenerated")?.ok_or("dateGenerated not found")?;
+        Ok(parse_iso_date(&date).ok_or(format!("dateGenerated not recognized {}", date))?)
+    }
+}
+
+#[cfg(test)]
+mod un {
+    use super::*;
+    use crate::db::entity::sdn::{self, DocumentEntity};
+
+    const UN_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CONSOLIDATED_LIST xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" dateGenerated="2022-11-08T10:00:03.012-05:00">
+  <INDIVIDUALS>
+    <INDIVIDUAL>
+      <DATAID>6908555</DATAID>
+      <VERSIONNUM>1</VERSIONNUM>
+      <FIRST_NAME>RI</FIRST_NAME>
+      <SECOND_NAME>WON HO</SECOND_NAME>
+      <THIRD_NAME/>
+      <UN_LIST_TYPE>DPRK</UN_LIST_TYPE>
+      <REFERENCE_NUMBER>KPi.033</REFERENCE_NUMBER>
+      <LISTED_ON>2016-11-30</LISTED_ON>
+      <GENDER>Male</GENDER>
+      <COMMENTS1>Ri Won Ho is a DPRK Ministry of State Security Official.</COMMENTS1>
+      <DESIGNATION>
+        <VALUE>DPRK Ministry of State Security Official</VALUE>
+      </DESIGNATION>
+      <NATIONALITY>
+        <VALUE>Democratic People's Republic of Korea</VALUE>
+      </NATIONALITY>
+      <LIST_TYPE>
+        <VALUE>UN List</VALUE>
+      </LIST_TYPE>
+      <INDIVIDUAL_ALIAS>
+        <QUALITY>Low</QUALITY>
+        <ALIAS_NAME>Ri Wonho</ALIAS_NAME>
+      </INDIVIDUAL_ALIAS>
+      <INDIVIDUAL_ADDRESS>
+        <COUNTRY>Syrian Arab Republic</COUNTRY>
+      </INDIVIDUAL_ADDRESS>
+      <INDIVIDUAL_DATE_OF_BIRTH>
+        <TYPE_OF_DATE>EXACT</TYPE_OF_DATE>
+        <DATE>1964-07-17</DATE>
+      </INDIVIDUAL_DATE_OF_BIRTH>
+      <INDIVIDUAL_PLACE_OF_BIRTH/>
+      <INDIVIDUAL_DOCUMENT>
+        <TYPE_OF_DOCUMENT>Passport</TYPE_OF_DOCUMENT>
+        <NUMBER>381310014</NUMBER>
+      </INDIVIDUAL_DOCUMENT>
+      <SORT_KEY/>
+      <SORT_KEY_LAST_MOD/>
+    </INDIVIDUAL>
+  </INDIVIDUALS>
+  <ENTITIES>
+    <ENTITY>
+      <DATAID>110402</DATAID>
+      <VERSIONNUM>1</VERSIONNUM>
+      <FIRST_NAME>AL-NUR HONEY PRESS SHOPS</FIRST_NAME>
+      <UN_LIST_TYPE>Al-Qaida</UN_LIST_TYPE>
+      <REFERENCE_NUMBER>QDe.026</REFERENCE_NUMBER>
+      <LISTED_ON>2001-10-12</LISTED_ON>
+      <ENTITY_ALIAS>
+        <QUALITY>Good</QUALITY>
+        <ALIAS_NAME>Al-Nur Honey Center</ALIAS_NAME>
+      </ENTITY_ALIAS>
+      <ENTITY_ADDRESS>
+        <CITY>Sanaa</CITY>
+        <COUNTRY>Yemen</COUNTRY>
+      </ENTITY_ADDRESS>
+    </ENTITY>
+  </ENTITIES>
+</CONSOLIDATED_LIST>"#;
+
+    #[test]
+    fn compute_fixed_ref() {
+        assert_eq!((5 * 2) * 1000 + 33, fixed_ref("KPi.033"));
+        assert_eq!(1000 + 26, fixed_ref("QDe.026"));
+        assert_eq!((14 * 2 + 1) * 1000 + 999, fixed_ref("HTe.999"));
+        assert_ne!(fixed_ref("KPi.033"), fixed_ref("KPe.033"));
+        let unmatched = fixed_ref("Unknown reference");
+        assert_eq!(unmatched, fixed_ref(" Unknown reference "));
+        assert!((UNMATCHED_REFERENCE_FIXED_REF..MAX_FIXED_REF).contains(&unmatched));
+        assert!((UNMATCHED_REFERENCE_FIXED_REF..MAX_FIXED_REF).contains(&fixed_ref("ZZi.001")));
+    }
+
+    #[test]
+    fn read_un_xml() {
+        let document = read(UN_XML.as_bytes(), "").unwrap();
+        assert_eq!(NaiveDate::from_ymd(2022, 11, 8), document.references.date_of_issue.to_sql_date());
+        assert_eq!(2, document.distinct_parties.parties.len());
+        assert_eq!(1571, document.documents.documents[0].type_id);
+
+        let (individual, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[0], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!(fixed_ref("KPi.033"), individual.fixed_ref);
+        assert_eq!("INDIVIDUAL", individual.sdn_type);
+        assert_eq!(Some("MALE".to_owned()), individual.gender);
+        assert_eq!(Some(NaiveDate::from_ymd(2016, 11, 30)), individual.sanction_date);
+        assert_eq!(Some("RI".to_owned()), relations.names[0].first_name);
+        assert_eq!(Some("WON HO".to_owned()), relations.names[0].last_name);
+        assert_eq!(Some("LOW".to_owned()), relations.names[1].quality);
+        assert_eq!("17 JUL 1964", relations.dobs[0].dob);
+        assert_eq!("DPRK", relations.programs[0].program);
+
+        let (entity, relations) = sdn::Model::from_ofac_document(&DocumentEntity(&document.distinct_parties.parties[1], &document.locations, &document.sanction_entries), &document.references).unwrap();
+        assert_eq!("ENTITY", entity.sdn_type);
+        assert_eq!(2, relations.names.len());
+        assert_eq!(Some("SANAA".to_owned()), relations.address[0].city);
+    }
+}
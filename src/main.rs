@@ -1,12 +1,15 @@
-use log::{error, info};
+use log::{error, info, warn};
 use ofacxmlagent::config::*;
+use ofacxmlagent::db::mapping::FeatureMapping;
 use ofacxmlagent::db::*;
 use ofacxmlagent::document::*;
+use ofacxmlagent::processor::export::crypto::CryptoExporter;
 use ofacxmlagent::processor::export::fofdbof::FofdbofExporter;
 use ofacxmlagent::processor::export::fofnasy::FofnasyExporter;
 use ofacxmlagent::processor::export::Exporter;
-use ofacxmlagent::processor::import::Importer;
-use std::{error::Error, time::Instant};
+use ofacxmlagent::processor::import::delta::Delta;
+use ofacxmlagent::processor::import::{Importer, ImporterErr};
+use std::{error::Error, path::Path, sync::Arc, time::Instant};
 
 use clap::Parser;
 
@@ -19,6 +22,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     match &args.mode[..] {
         "import" => import_mode(&mut configs).await?,
         "export" => export_mode(&mut configs, &args).await?,
+        "history" => history_mode(&mut configs).await?,
         _ => error!("Mode is not recognized : {}", &args.mode[..]),
     }
     info!("Elapsed time : {}.{}s", now.elapsed().as_secs(), now.elapsed().as_millis());
@@ -27,17 +31,29 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
 fn load_configs(args: &mut Args) -> Vec<Config> {
     let mut configs = Vec::new();
+    if args.datatype == "ALL" && args.input.is_some() {
+        panic!("An input file can only be imported for a single data type (use -t OFAC or -t OFACNS)");
+    }
+    if args.datatype == "ALL" && args.rollback_to.is_some() {
+        panic!("A rollback can only be done for a single data type (use -t OFAC or -t OFACNS)");
+    }
+    if args.datatype == "ALL" && args.mode == "history" {
+        panic!("History can only be queried for a single data type (use -t OFAC or -t OFACNS)");
+    }
     if args.datatype == "ALL" && args.mode == "import" {
-        args.datatype = "OFACNS".to_owned();
-        configs.push(Config::init(args).expect("Could not init config"));
-        args.datatype = "OFAC".to_owned();
-        configs.push(Config::init(args).expect("Could not init config"));
+        for source in list::SOURCES.iter().filter(|source| Config::is_enabled(**source)) {
+            if args.legacy.is_some() && !source.has_legacy_files() {
+                continue;
+            }
+            args.datatype = source.name().to_owned();
+            configs.push(Config::init(args).expect("Could not init config"));
+        }
     } else if args.mode == "export" {
         let mut tmp_args = args.clone();
-        tmp_args.datatype = "OFAC".to_owned();
-        configs.push(Config::init(&tmp_args).expect("Could not init config"));
-        tmp_args.datatype = "OFACNS".to_owned();
-        configs.push(Config::init(&tmp_args).expect("Could not init config"));
+        for source in list::export_order().into_iter().filter(|source| Config::is_enabled(*source)) {
+            tmp_args.datatype = source.name().to_owned();
+            configs.push(Config::init(&tmp_args).expect("Could not init config"));
+        }
     } else {
         configs.push(Config::init(args).expect("Could not init config"));
     }
@@ -70,7 +86,19 @@ async fn export_mode(configs: &mut [Config], args: &Args) -> Result<(), Box<dyn
             exporter.flush()?;
             info!("FOFNASY successfully saved to {}", exporter.filepath);
         }
-        _ => panic!("Export must be FOFNASY or FOFDBOF"),
+        "CRYPTO" => {
+            let mut exporter = CryptoExporter::default();
+            for (i, config) in configs.iter_mut().enumerate() {
+                if i == 0 {
+                    config.init_logging();
+                }
+                let db = init_db(config).await.map_err(|err| exit(Box::new(err))).unwrap();
+                exporter.process(&db, &config.data_type, config).await?;
+            }
+            exporter.flush()?;
+            info!("CRYPTO successfully saved to {}", exporter.filepath);
+        }
+        _ => panic!("Export must be FOFNASY, FOFDBOF or CRYPTO"),
     }
     Ok(())
 }
@@ -81,19 +109,120 @@ async fn import_mode(configs: &mut [Config]) -> Result<(), Box<dyn Error>> {
             config.init_logging();
         }
         let db = init_db(config).await.map_err(|err| exit(Box::new(err))).unwrap();
-        let mut ofac_document = inputs::OfacDocument::new(&config.get_data_folder_path(), config.data_type);
-        ofac_document.load().map_err(|err| exit(err)).unwrap();
+        let mut ofac_document = inputs::OfacDocument::new(config.get_data_folder_path(), config.data_type);
+        ofac_document.input = config.input.clone();
+        if config.validate && config.legacy.is_none() && config.rollback_to.is_none() {
+            let report = ofac_document.validate().map_err(exit).unwrap();
+            report.log();
+            if report.has_blocking() {
+                error!("{} has blocking validation errors and will not be imported", config.data_type.designation());
+                continue;
+            }
+        }
+        let mut force = config.force;
+        let mut rolled_back_from = None;
+        if let Some(rollback_to) = config.rollback_to {
+            let last_date_of_issue = get_last_issued_date(&db).await;
+            if rollback_to >= last_date_of_issue {
+                exit(format!("{} can only be rolled back to a document issued before {}", config.data_type.designation(), last_date_of_issue).into());
+            }
+            ofac_document.load_archive(rollback_to).map_err(exit).unwrap();
+            force = true;
+            rolled_back_from = Some(last_date_of_issue);
+        } else if let Some(format) = config.legacy {
+            ofac_document.load_legacy(format).map_err(exit).unwrap();
+        } else if config.stream {
+            ofac_document.load_streamed().map_err(exit).unwrap();
+        } else {
+            ofac_document.load().map_err(exit).unwrap();
+        }
+        if let Some(path) = config.mapping.as_ref() {
+            let mapping = FeatureMapping::load(path).map_err(exit).unwrap();
+            info!("Feature mapping read from {}, {} FeatureTypes mapped", path.to_string_lossy(), mapping.len());
+            ofac_document.references.mapping = Some(Arc::new(mapping));
+        }
         let mut importer = Importer::init(&db).await;
-        match importer.process_document(&db, &ofac_document, config.force).await {
+        importer.lenient = config.lenient;
+        importer.fail_on_unknowns = config.fail_on_unknowns;
+        importer.dry_run = config.dry_run;
+        importer.rolled_back_from = rolled_back_from;
+        match importer.process_document(&db, &ofac_document, force).await {
             Ok(_) => {
+                if !importer.quarantined.is_empty() {
+                    let fixed_refs: Vec<i32> = importer.quarantined.iter().map(|party| party.fixed_ref).collect();
+                    warn!("{} : {} DistinctParties quarantined {:?}", config.data_type.designation(), fixed_refs.len(), fixed_refs);
+                }
+                if !importer.identifiers.is_empty() {
+                    warn!("{} : {} invalid identifiers", config.data_type.designation(), importer.identifiers.invalid.len());
+                    importer.identifiers.log();
+                }
+                let unknowns = std::mem::take(&mut importer.unknowns);
+                if !unknowns.is_empty() {
+                    warn!("{} : {} unmapped values", config.data_type.designation(), unknowns.unknowns.len());
+                    unknowns.log();
+                }
+                if config.dry_run {
+                    let changes = importer.rollback().await?;
+                    info!("{} : dry run, {} changes", config.data_type.designation(), changes.changes.len());
+                    changes.log();
+                    let path = changes.write(&ofac_document.root_folder).map_err(|err| exit(Box::new(err))).unwrap();
+                    info!("Changes written to {}", path.to_string_lossy());
+                    continue;
+                }
+                let changes = importer.take_changes();
+                let date_of_issue = ofac_document.references.date_of_issue.to_sql_date();
+                let mut delta = Delta::from_changes(&changes, config.data_type, date_of_issue);
+                if let Some(rolled_back_from) = rolled_back_from {
+                    delta.set_rolled_back_from(rolled_back_from);
+                    info!("{} rolled back from the document issued on {} to the one issued on {}", config.data_type.designation(), rolled_back_from, date_of_issue);
+                }
+                importer.save_history(date_of_issue, &delta).await?;
                 importer.commit().await?;
+                let (json, csv) = delta.write(Path::new(LOG_FOLDER)).map_err(exit).unwrap();
+                info!(
+                    "{} : {} sdns added, {} inactivated, {} reactivated, {} changed, delta written to {} and {}",
+                    config.data_type.designation(),
+                    delta.added.len(),
+                    delta.inactivated.len(),
+                    delta.reactivated.len(),
+                    delta.changed.len(),
+                    json.to_string_lossy(),
+                    csv.to_string_lossy()
+                );
+                if !unknowns.is_empty() {
+                    unknowns.save(&ofac_document.root_folder).map_err(|err| exit(Box::new(err))).unwrap();
+                }
                 ofac_document.cleanup().map_err(|err| exit(Box::new(err))).unwrap();
             }
-            Err(_) => info!("This document has beed skipped"),
+            Err(ImporterErr::AlreadyImported(_) | ImporterErr::RolledBack(_)) => info!("This document has beed skipped"),
+            Err(ImporterErr::NewUnknowns(message)) => exit(message.into()),
+            Err(err) => exit(Box::new(err)),
         }
     }
     Ok(())
 }
+async fn history_mode(configs: &mut [Config]) -> Result<(), Box<dyn Error>> {
+    for (i, config) in configs.iter_mut().enumerate() {
+        if i == 0 {
+            config.init_logging();
+        }
+        let db = init_db(config).await.map_err(|err| exit(Box::new(err))).unwrap();
+        let as_of = config.as_of.expect("History mode needs --as-of");
+        let (records, name) = match config.fixed_ref {
+            Some(fixed_ref) => (
+                history::find_record_as_of(&db, fixed_ref, as_of).await?.into_iter().collect(),
+                format!("{}_{}_{}", config.data_type.designation(), fixed_ref, as_of),
+            ),
+            None => (history::find_records_as_of(&db, as_of).await?, format!("{}_{}", config.data_type.designation(), as_of)),
+        };
+        std::fs::create_dir_all("./output")?;
+        let path = format!("./output/{}.json", name);
+        serde_json::to_writer_pretty(std::fs::File::create(&path)?, &records)?;
+        info!("{} : {} sdns in the list on {}, written to {}", config.data_type.designation(), records.len(), as_of, path);
+    }
+    Ok(())
+}
+
 fn exit(err: Box<dyn std::error::Error>) {
     log::error!("{:?}", err);
     log::info!("Exiting...");
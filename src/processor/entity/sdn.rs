@@ -1,11 +1,14 @@
 use std::error::Error;
 use std::fmt::Write;
 
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
 use sea_orm::entity::prelude::*;
 use sea_orm::FromQueryResult;
+use serde::{Deserialize, Serialize};
 
 use super::{extract_field, extract_field_as_vec, name::SdnAlias};
+use crate::document::inputs::builder::LATIN_SCRIPT;
+use crate::document::inputs::known::{DocumentKind, EntryEventKind, KnownValue, ReliabilityKind};
 
 #[derive(FromQueryResult, Default, Debug, Clone)]
 pub struct QuerySdnRecord {
@@ -31,7 +34,6 @@ pub struct QuerySdnRecord {
     pub address_country: Option<String>,
     pub address_region: Option<String>,
     pub address_is_primary: Option<bool>,
-    pub dob_dob: Option<String>,
     pub pob_pob: Option<String>,
     pub citizen_location: Option<String>,
     pub website_website: Option<String>,
@@ -55,6 +57,7 @@ pub struct QuerySdnRecord {
     pub phone_number: Option<String>,
     pub organization_established_date: Option<NaiveDate>,
     pub document_registration_number: Option<String>,
+    pub document_normalized_number: Option<String>,
     pub document_type: Option<i32>,
     pub document_type_value: Option<String>,
     pub document_issued_by: Option<String>,
@@ -82,7 +85,50 @@ pub struct QuerySdnRecord {
     pub relation_linked_to: Option<i32>,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(FromQueryResult, Debug, Clone)]
+pub struct QuerySdnSanctionEvent {
+    pub fixed_ref: i32,
+    pub event_type: i32,
+    pub event_date: NaiveDate,
+    pub legal_basis: Option<i32>,
+}
+
+#[derive(FromQueryResult, Debug, Clone)]
+pub struct QuerySdnSanctionMeasure {
+    pub fixed_ref: i32,
+    pub sanctions_type: i32,
+}
+
+#[derive(FromQueryResult, Debug, Clone)]
+pub struct QuerySdnDob {
+    pub fixed_ref: i32,
+    pub id: i32,
+    pub dob: String,
+    pub reliability: i32,
+    pub earliest: Option<NaiveDate>,
+    pub latest: Option<NaiveDate>,
+    pub approximate: bool,
+}
+
+#[derive(FromQueryResult, Debug, Clone)]
+pub struct QuerySdnDatePeriod {
+    pub fixed_ref: i32,
+    pub feature_type: i32,
+    pub feature: Option<String>,
+    pub earliest: Option<NaiveDate>,
+    pub latest: Option<NaiveDate>,
+    pub approximate: bool,
+    pub value: String,
+}
+
+#[derive(FromQueryResult, Debug, Clone)]
+pub struct QuerySdnDigitalCurrencyAddress {
+    pub fixed_ref: i32,
+    pub currency: String,
+    pub address: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SdnRecord {
     pub fixed_ref: i32,
     pub partysubtypeid: i32,
@@ -95,11 +141,19 @@ pub struct SdnRecord {
     pub ddc_normal_aliases: Vec<String>,
     pub normal_aliases: Vec<String>,
     pub low_aliases: Vec<String>,
+    /// Aliases written in their original script, also in `normal_aliases` or `low_aliases`
+    pub script_aliases: Vec<SdnRecordScriptAlias>,
     pub programs: Vec<String>,
     pub bics: Vec<String>,
     pub addresses: Vec<SdnRecordAddress>,
     pub documents: Vec<SdnRecordDocument>,
     pub dobs: Vec<String>,
+    /// Dobs published with a Fabricated reliability, never exported as dob
+    pub fabricated_dobs: Vec<String>,
+    /// Dobs published with an Unknown reliability, still in `dobs`
+    pub unknown_reliability_dobs: Vec<String>,
+    /// Bounds of the dobs which are not fabricated
+    pub dob_periods: Vec<SdnRecordDobPeriod>,
     pub pobs: Vec<String>,
     pub ddc_programs: Vec<String>,
     pub nationalities: Vec<String>,
@@ -133,9 +187,66 @@ pub struct SdnRecord {
     pub other_vessel_flag: Vec<String>,
     pub other_vessel_call_sign: String,
     pub linked_to_names: Vec<String>,
+    /// Date of the first Created EntryEvent
+    pub designation_date: Option<NaiveDate>,
+    /// Date of the last Amended EntryEvent
+    pub last_amendment_date: Option<NaiveDate>,
+    pub sanction_events: Vec<SdnRecordEvent>,
+    /// SanctionsTypes of the measures
+    pub sanction_measures: Vec<String>,
+    pub legal_basis: Vec<String>,
+    /// DatePeriods of every feature but the birthdates
+    pub date_periods: Vec<SdnRecordDatePeriod>,
+    pub digital_currency_addresses: Vec<SdnRecordDigitalCurrencyAddress>,
 }
 
 impl SdnRecord {
+    /// Only the Created events give the designation date and the Amended ones the last amendment date
+    pub fn add_sanction_event(&mut self, event: SdnRecordEvent, kind: Option<EntryEventKind>) {
+        if let Some(legal_basis) = &event.legal_basis {
+            if !self.legal_basis.contains(legal_basis) {
+                self.legal_basis.push(legal_basis.to_owned());
+            }
+        }
+        match kind {
+            Some(EntryEventKind::Created) => self.designation_date = Some(self.designation_date.map_or(event.date, |date| date.min(event.date))),
+            Some(EntryEventKind::Amended) => self.last_amendment_date = Some(self.last_amendment_date.map_or(event.date, |date| date.max(event.date))),
+            None => {}
+        }
+        if !self.sanction_events.contains(&event) {
+            self.sanction_events.push(event);
+        }
+    }
+
+    /// Add a dob row, fabricated dobs are kept out of `dobs` and dobs with an Unknown reliability are also flagged
+    pub fn add_dob(&mut self, dob: &str, reliability: ReliabilityKind) {
+        let dob = dob.to_owned();
+        let dobs = match reliability {
+            ReliabilityKind::Fabricated => &mut self.fabricated_dobs,
+            ReliabilityKind::Unknown => {
+                if !self.unknown_reliability_dobs.contains(&dob) {
+                    self.unknown_reliability_dobs.push(dob.clone());
+                }
+                &mut self.dobs
+            }
+            ReliabilityKind::Reliable => &mut self.dobs,
+        };
+        if !dobs.contains(&dob) {
+            dobs.push(dob);
+        }
+    }
+
+    pub fn add_dob_period(&mut self, period: SdnRecordDobPeriod) {
+        if !self.dob_periods.contains(&period) {
+            self.dob_periods.push(period);
+        }
+    }
+
+    /// Records without any dob period may have been born any year
+    pub fn may_be_born_in(&self, year: i32) -> bool {
+        self.dob_periods.is_empty() || self.dob_periods.iter().any(|period| period.earliest.year() <= year && year <= period.latest.year())
+    }
+
     pub fn from_query_sdn_record(query_record: &QuerySdnRecord, record: &mut SdnRecord) -> Result<(), Box<dyn Error>> {
         extract_field_as_vec(query_record.relation_linked_to, &mut record.linked_to)?;
         record.fixed_ref = query_record.fixed_ref;
@@ -152,6 +263,7 @@ impl SdnRecord {
                 issued_date: query_record.document_issued_date,
                 issued_by: query_record.document_issued_by.clone(),
                 registration_number: query_record.document_registration_number.as_ref().unwrap().to_owned(),
+                normalized_number: query_record.document_normalized_number.clone(),
             };
             if !record.documents.contains(&document) {
                 record.documents.push(document);
@@ -182,6 +294,12 @@ impl SdnRecord {
                 "NAME" => record.name = name.build_alias(),
                 "ALIAS" => {
                     let alias = name.build_alias();
+                    if let Some(script) = query_record.name_script.filter(|script| *script != LATIN_SCRIPT) {
+                        let script_alias = SdnRecordScriptAlias { script, alias: alias.to_owned() };
+                        if !record.script_aliases.contains(&script_alias) {
+                            record.script_aliases.push(script_alias);
+                        }
+                    }
                     if name.quality == "LOW" && !record.low_aliases.contains(&alias) {
                         record.low_aliases.push(alias);
                     } else if name.quality == "NORMAL" && !record.normal_aliases.contains(&alias) {
@@ -191,15 +309,14 @@ impl SdnRecord {
                 _ => {}
             }
         }
-        if query_record.ddc_alias_name.is_some() {
-            if query_record.ddc_alias_quality.as_ref().unwrap() == "Low" && !record.ddc_low_aliases.contains(query_record.ddc_alias_name.as_ref().unwrap()) {
-                record.ddc_low_aliases.push(query_record.ddc_alias_name.as_ref().unwrap().to_owned());
-            } else if query_record.ddc_alias_quality.as_ref().unwrap() == "Normal" && !record.ddc_normal_aliases.contains(query_record.ddc_alias_name.as_ref().unwrap()) {
-                record.ddc_normal_aliases.push(query_record.ddc_alias_name.as_ref().unwrap().to_owned());
+        if let Some(ddc_alias_name) = query_record.ddc_alias_name.as_ref() {
+            if query_record.ddc_alias_quality.as_ref().unwrap() == "Low" && !record.ddc_low_aliases.contains(ddc_alias_name) {
+                record.ddc_low_aliases.push(ddc_alias_name.to_owned());
+            } else if query_record.ddc_alias_quality.as_ref().unwrap() == "Normal" && !record.ddc_normal_aliases.contains(ddc_alias_name) {
+                record.ddc_normal_aliases.push(ddc_alias_name.to_owned());
             }
         }
         extract_field_as_vec(query_record.ddc_bic.clone(), &mut record.ddc_bics)?;
-        extract_field_as_vec(query_record.dob_dob.clone(), &mut record.dobs)?;
         extract_field_as_vec(query_record.sanction_program.clone(), &mut record.programs)?;
         extract_field_as_vec(query_record.bic_bic.clone(), &mut record.bics)?;
         extract_field_as_vec(query_record.pob_pob.clone(), &mut record.pobs)?;
@@ -272,7 +389,7 @@ impl SdnRecord {
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SdnRecordAddress {
     pub id: i32,
     pub country: Option<String>,
@@ -282,6 +399,22 @@ pub struct SdnRecordAddress {
     pub state: Option<String>,
     pub region: Option<String>,
     pub is_primary: bool,
+    pub variants: Vec<SdnRecordAddressVariant>,
+}
+
+/// A non primary value of an address part, `part` is the LocPartType name
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdnRecordAddressVariant {
+    pub part: String,
+    pub script: Option<i32>,
+    pub value: String,
+}
+
+/// An alias which is not written in latin script
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdnRecordScriptAlias {
+    pub script: i32,
+    pub alias: String,
 }
 
 impl SdnRecordAddress {
@@ -309,7 +442,40 @@ impl SdnRecordAddress {
         }
     }
 }
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// Earliest and latest dates of a dob
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdnRecordDobPeriod {
+    pub earliest: NaiveDate,
+    pub latest: NaiveDate,
+    pub approximate: bool,
+}
+
+/// DatePeriod of a feature, `feature` is the FeatureType name stored in `ref_feature`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdnRecordDatePeriod {
+    pub feature: String,
+    pub earliest: Option<NaiveDate>,
+    pub latest: Option<NaiveDate>,
+    pub approximate: bool,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdnRecordDigitalCurrencyAddress {
+    /// Currency code (e.g. XBT)
+    pub currency: String,
+    pub address: String,
+}
+
+/// An EntryEvent of the sanction, types are the names stored in `ref_value`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdnRecordEvent {
+    pub event_type: String,
+    pub date: NaiveDate,
+    pub legal_basis: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SdnRecordDocument {
     pub id: i32,
     pub doc_type: i32,
@@ -318,9 +484,16 @@ pub struct SdnRecordDocument {
     pub issued_date: Option<Date>,
     pub issued_by: Option<String>,
     pub registration_number: String,
+    /// Normalized IMO, MMSI or LEI, `None` for the other documents and the invalid numbers
+    pub normalized_number: Option<String>,
 }
 
 impl SdnRecordDocument {
+    /// Kind of the document, looked up by the name stored in `ref_document`
+    pub fn kind(&self) -> Option<DocumentKind> {
+        DocumentKind::from_name(&self.doc_type_value).or_else(|| DocumentKind::from_id(self.doc_type))
+    }
+
     pub fn extract_inf(&self, inf: &mut String) {
         if !inf.is_empty() {
             inf.push_str(" / ");
@@ -360,6 +533,7 @@ fn to_firco_date(date: &NaiveDate) -> String {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_conversion)]
 mod sdn_document {
     use super::*;
 
@@ -372,6 +546,7 @@ mod sdn_document {
             issued_date: Some(NaiveDate::from_ymd(1996, 10, 26)),
             issued_by: Some(String::from("France")),
             registration_number: String::from("12345"),
+            normalized_number: None,
         }
     }
 
@@ -392,6 +567,7 @@ mod sdn_document {
                     None
                 },
                 registration_number: String::from(((i + 1) * 12345).to_string()),
+                normalized_number: None,
             });
         }
         documents
@@ -441,3 +617,67 @@ mod sdn_document {
         assert_eq!(expected, inf);
     }
 }
+
+#[cfg(test)]
+mod sdn_record {
+    use super::*;
+
+    #[test]
+    fn sanction_events_dates() {
+        let mut record = SdnRecord::default();
+        let event = |event_type: &str, date: NaiveDate, legal_basis: Option<&str>| SdnRecordEvent {
+            event_type: event_type.to_owned(),
+            date,
+            legal_basis: legal_basis.map(|legal_basis| legal_basis.to_owned()),
+        };
+        record.add_sanction_event(event("CREATED", NaiveDate::from_ymd(2012, 3, 1), Some("EO13224")), Some(EntryEventKind::Created));
+        assert_eq!(Some(NaiveDate::from_ymd(2012, 3, 1)), record.designation_date);
+        assert_eq!(None, record.last_amendment_date);
+        record.add_sanction_event(event("AMENDED", NaiveDate::from_ymd(2020, 5, 4), Some("EO13224")), Some(EntryEventKind::Amended));
+        record.add_sanction_event(event("CREATED", NaiveDate::from_ymd(2008, 1, 2), Some("EO13382")), Some(EntryEventKind::Created));
+        assert_eq!(Some(NaiveDate::from_ymd(2008, 1, 2)), record.designation_date);
+        assert_eq!(Some(NaiveDate::from_ymd(2020, 5, 4)), record.last_amendment_date);
+        record.add_sanction_event(event("DELISTED", NaiveDate::from_ymd(2022, 9, 1), None), None);
+        assert_eq!(Some(NaiveDate::from_ymd(2008, 1, 2)), record.designation_date);
+        assert_eq!(Some(NaiveDate::from_ymd(2020, 5, 4)), record.last_amendment_date);
+        assert_eq!(4, record.sanction_events.len());
+        let excepted = vec!["EO13224".to_owned(), "EO13382".to_owned()];
+        assert_eq!(excepted, record.legal_basis);
+    }
+
+    #[test]
+    fn fabricated_dobs_are_not_dobs() {
+        let mut record = SdnRecord::default();
+        record.add_dob("1961", ReliabilityKind::Reliable);
+        record.add_dob("13 APR 1975", ReliabilityKind::Fabricated);
+        record.add_dob("1984 TO 1986", ReliabilityKind::Unknown);
+        let excepted = vec!["1961".to_owned(), "1984 TO 1986".to_owned()];
+        assert_eq!(excepted, record.dobs);
+        assert_eq!(vec!["13 APR 1975".to_owned()], record.fabricated_dobs);
+        assert_eq!(vec!["1984 TO 1986".to_owned()], record.unknown_reliability_dobs);
+
+        // The same dob published again as fabricated does not hide the reliable one
+        record.add_dob("1961", ReliabilityKind::Fabricated);
+        assert_eq!(excepted, record.dobs);
+        assert_eq!(vec!["13 APR 1975".to_owned(), "1961".to_owned()], record.fabricated_dobs);
+    }
+
+    #[test]
+    fn born_in_dob_periods() {
+        let mut record = SdnRecord::default();
+        assert!(record.may_be_born_in(1950));
+        record.add_dob_period(SdnRecordDobPeriod {
+            earliest: NaiveDate::from_ymd(1984, 1, 1),
+            latest: NaiveDate::from_ymd(1986, 12, 31),
+            approximate: false,
+        });
+        record.add_dob_period(SdnRecordDobPeriod {
+            earliest: NaiveDate::from_ymd(1975, 4, 13),
+            latest: NaiveDate::from_ymd(1975, 4, 13),
+            approximate: true,
+        });
+        assert!(record.may_be_born_in(1985));
+        assert!(record.may_be_born_in(1975));
+        assert!(!record.may_be_born_in(1980));
+    }
+}
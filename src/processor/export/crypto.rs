@@ -0,0 +1,98 @@
+use super::Exporter;
+use async_trait::async_trait;
+use std::{error::Error, fs::OpenOptions, io::Write, path::Path};
+
+use crate::config::Config;
+use crate::db::find_digital_currency_addresses;
+use crate::document::DocumentType;
+use sea_orm::{Condition, DatabaseConnection};
+
+/// Header of the crypto screening dataset
+const HEADER: &str = "id;currency;address";
+
+pub struct CryptoRecord {
+    pub doc_type: DocumentType,
+    pub t_id: i32,
+    pub currency: String,
+    pub address: String,
+}
+
+/// Exports the digital currency addresses of the active sdns, one address per line
+pub struct CryptoExporter {
+    pub filepath: String,
+    records: Vec<CryptoRecord>,
+}
+
+impl Default for CryptoExporter {
+    fn default() -> Self {
+        let path = format!("./output/{}", String::from("CRYPTO.csv"));
+        if Path::new(&path).exists() {
+            std::fs::remove_file(&path).unwrap();
+        }
+        Self {
+            filepath: path,
+            records: Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl Exporter for CryptoExporter {
+    async fn process(&mut self, db: &DatabaseConnection, doc_type: &DocumentType, _config: &Config) -> Result<(), Box<dyn Error>> {
+        for (fixed_ref, addresses) in find_digital_currency_addresses(db, Condition::all()).await? {
+            for address in addresses {
+                self.records.push(CryptoRecord {
+                    doc_type: *doc_type,
+                    t_id: fixed_ref,
+                    currency: address.currency,
+                    address: address.address,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), Box<dyn Error>> {
+        let mut file = OpenOptions::new().read(true).append(true).create(true).truncate(false).open(&self.filepath).unwrap();
+        self.write_in(&mut file)?;
+        Ok(())
+    }
+
+    fn write_in<W: Write>(&self, buffer: &mut W) -> Result<(), Box<dyn Error>> {
+        writeln!(buffer, "{}", HEADER)?;
+        for record in self.records.iter() {
+            // Addresses are case sensitive and written as published
+            writeln!(buffer, "{}{:0>6};{};{}", record.doc_type.oid_prefix(), record.t_id, record.currency, record.address)?;
+        }
+        buffer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod crypto {
+    use std::io::BufWriter;
+
+    use super::*;
+
+    #[test]
+    fn write_crypto_records() {
+        let mut buffer = Vec::new();
+        let mut exporter = CryptoExporter::default();
+        exporter.records.push(CryptoRecord {
+            doc_type: DocumentType::OFAC,
+            t_id: 36,
+            currency: "XBT".to_owned(),
+            address: "1AjZPMsnmpdK2Rv9KQNfMurTXinscVro9V".to_owned(),
+        });
+        exporter.records.push(CryptoRecord {
+            doc_type: DocumentType::OFAC,
+            t_id: 36,
+            currency: "TRX".to_owned(),
+            address: "TQn9Y2khEsLJW1ChVWFMSMeRDow5KcbLSE".to_owned(),
+        });
+        exporter.write_in(&mut BufWriter::new(&mut buffer)).unwrap();
+        let excepted = "id;currency;address\nOFAC000036;XBT;1AjZPMsnmpdK2Rv9KQNfMurTXinscVro9V\nOFAC000036;TRX;TQn9Y2khEsLJW1ChVWFMSMeRDow5KcbLSE\n";
+        assert_eq!(excepted, String::from_utf8(buffer).unwrap());
+    }
+}
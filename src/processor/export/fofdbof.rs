@@ -69,7 +69,7 @@ impl Exporter for FofdbofExporter {
     }
 
     fn flush(&self) -> Result<(), Box<dyn Error>> {
-        let mut file = OpenOptions::new().read(true).write(true).append(true).create(true).truncate(false).open(&self.filepath).unwrap();
+        let mut file = OpenOptions::new().read(true).append(true).create(true).truncate(false).open(&self.filepath).unwrap();
         self.write_in(&mut file)?;
         Ok(())
     }
@@ -188,6 +188,7 @@ impl FofdbofExporter {
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod fofdbof {
     use std::io::BufWriter;
 
@@ -239,6 +240,7 @@ mod fofdbof {
                     postal_code: Some("Postal Code".to_owned()),
                     state: Some("State 1".to_owned()),
                     is_primary: i == 1,
+                    variants: Vec::new(),
                 };
                 addresses.push(address);
             }
@@ -255,6 +257,7 @@ mod fofdbof {
                     issued_by: None,
                     doc_type_value: "TEST".to_owned(),
                     registration_number: format!("12345{}", i),
+                    normalized_number: None,
                 };
                 documents.push(document);
             }
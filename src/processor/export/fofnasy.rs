@@ -45,17 +45,14 @@ impl Exporter for FofnasyExporter {
     }
 
     fn flush(&self) -> Result<(), Box<dyn Error>> {
-        let mut file = OpenOptions::new().read(true).write(true).append(true).create(true).truncate(false).open(&self.filepath).unwrap();
+        let mut file = OpenOptions::new().read(true).append(true).create(true).truncate(false).open(&self.filepath).unwrap();
         self.write_in(&mut file)?;
         Ok(())
     }
 
     fn write_in<W: Write>(&self, buffer: &mut W) -> Result<(), Box<dyn Error>> {
         for record in self.records.iter() {
-            let doc_type = match record.doc_type {
-                DocumentType::OFAC => "OFAC",
-                DocumentType::OFACNS => "OFNS",
-            };
+            let doc_type = record.doc_type.oid_prefix();
             buffer
                 .write_all(
                     format!(
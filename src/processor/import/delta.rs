@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::db::changes::{ChangeKind, ChangeSet, FieldChange};
+use crate::document::DocumentType;
+
+/// Table of the sdns, the other tables are relations of the sdns
+const SDN_TABLE: &str = "sdn";
+
+/// A change of a relation (or of a column) of an sdn
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct DeltaChange {
+    pub table: String,
+    pub kind: ChangeKind,
+    pub key: String,
+    pub fields: Vec<FieldChange>,
+}
+
+/// Sdns changed by an import, written for the downstream teams to screen again only the affected customers
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Delta {
+    pub list: String,
+    pub date_of_issue: String,
+    /// Date of issue of the document replaced when the import rolls the database back
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rolled_back_from: Option<String>,
+    pub added: Vec<i32>,
+    /// Set INACTIVE because they are not in the document anymore
+    pub inactivated: Vec<i32>,
+    /// INACTIVE before the import and ACTIVE again
+    pub reactivated: Vec<i32>,
+    /// Changes of the sdns which were already in database, by FixedRef
+    pub changed: BTreeMap<i32, Vec<DeltaChange>>,
+}
+
+impl Delta {
+    /// Delta of the changes recorded by an import, the topmaj only updates and the reference tables are left out
+    pub fn from_changes(changes: &ChangeSet, document_type: DocumentType, date_of_issue: NaiveDate) -> Delta {
+        let mut delta = Delta {
+            list: document_type.designation().to_owned(),
+            date_of_issue: date_of_issue.format("%Y-%m-%d").to_string(),
+            ..Default::default()
+        };
+        for change in changes.changes.iter() {
+            let fixed_ref = match change.fixed_ref {
+                Some(fixed_ref) => fixed_ref,
+                None => continue,
+            };
+            match (change.table.as_str(), change.kind) {
+                (_, ChangeKind::UpdateTopmajOnly) => continue,
+                (SDN_TABLE, ChangeKind::Insert) => {
+                    delta.added.push(fixed_ref);
+                    continue;
+                }
+                (SDN_TABLE, ChangeKind::Deactivation) => {
+                    delta.inactivated.push(fixed_ref);
+                    continue;
+                }
+                (SDN_TABLE, ChangeKind::Update) if change.fields.iter().any(is_reactivation) => delta.reactivated.push(fixed_ref),
+                _ => {}
+            }
+            delta.changed.entry(fixed_ref).or_default().push(DeltaChange {
+                table: change.table.clone(),
+                kind: change.kind,
+                key: change.key.clone(),
+                fields: change.fields.clone(),
+            });
+        }
+        for fixed_ref in delta.added.iter() {
+            delta.changed.remove(fixed_ref);
+        }
+        for fixed_refs in [&mut delta.added, &mut delta.inactivated, &mut delta.reactivated] {
+            fixed_refs.sort_unstable();
+            fixed_refs.dedup();
+        }
+        delta
+    }
+
+    /// Sdns added, reactivated or changed, their history is saved with the import
+    pub fn affected(&self) -> Vec<i32> {
+        let mut fixed_refs: Vec<i32> = self.added.iter().chain(self.reactivated.iter()).chain(self.changed.keys()).copied().collect();
+        fixed_refs.sort_unstable();
+        fixed_refs.dedup();
+        fixed_refs
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.inactivated.is_empty() && self.reactivated.is_empty() && self.changed.is_empty()
+    }
+
+    /// Mark the delta as the one of a rollback, so that it is not written over the delta of the first import of the document
+    pub fn set_rolled_back_from(&mut self, date_of_issue: NaiveDate) {
+        self.rolled_back_from = Some(date_of_issue.format("%Y-%m-%d").to_string());
+    }
+
+    /// `<list>_<date of issue>`, followed by `_rollback_from_<date of issue>` for a rollback
+    fn file_stem(&self) -> String {
+        match &self.rolled_back_from {
+            Some(rolled_back_from) => format!("{}_{}_rollback_from_{}", self.list, self.date_of_issue, rolled_back_from),
+            None => format!("{}_{}", self.list, self.date_of_issue),
+        }
+    }
+
+    /// Write the delta as `.json` and `.csv` in `delta/` of `folder`, see [`Delta::file_stem`]
+    pub fn write(&self, folder: &Path) -> Result<(PathBuf, PathBuf), Box<dyn Error>> {
+        let folder = folder.join("delta");
+        std::fs::create_dir_all(&folder)?;
+        let name = self.file_stem();
+        let json = folder.join(format!("{}.json", name));
+        serde_json::to_writer_pretty(std::fs::File::create(&json)?, self)?;
+        let csv = folder.join(format!("{}.csv", name));
+        self.write_csv(&mut std::fs::File::create(&csv)?)?;
+        Ok((json, csv))
+    }
+
+    /// One line by added, inactivated or reactivated sdn, then one line by changed column
+    fn write_csv<W: Write>(&self, buffer: &mut W) -> Result<(), std::io::Error> {
+        writeln!(buffer, "fixed_ref;status;table;kind;key;column;before;after")?;
+        for (status, fixed_refs) in [("ADDED", &self.added), ("INACTIVATED", &self.inactivated), ("REACTIVATED", &self.reactivated)] {
+            for fixed_ref in fixed_refs.iter() {
+                writeln!(buffer, "{};{};;;;;;", fixed_ref, status)?;
+            }
+        }
+        for (fixed_ref, changes) in self.changed.iter() {
+            for change in changes.iter() {
+                if change.fields.is_empty() {
+                    writeln!(buffer, "{};CHANGED;{};{};{};;;", fixed_ref, change.table, change.kind, change.key)?;
+                }
+                for field in change.fields.iter() {
+                    let before = field.before.as_deref().unwrap_or_default().replace(';', " ");
+                    let after = field.after.as_deref().unwrap_or_default().replace(';', " ");
+                    writeln!(buffer, "{};CHANGED;{};{};{};{};{};{}", fixed_ref, change.table, change.kind, change.key, field.column, before, after)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_reactivation(field: &FieldChange) -> bool {
+    field.column == "sanction_status" && field.before.as_deref() == Some("INACTIVE") && field.after.as_deref() == Some("ACTIVE")
+}
+
+#[cfg(test)]
+mod delta {
+    use super::*;
+    use crate::db::changes::Change;
+
+    fn change(kind: ChangeKind, table: &str, key: &str, fixed_ref: i32, fields: Vec<FieldChange>) -> Change {
+        Change {
+            kind,
+            table: table.to_owned(),
+            key: key.to_owned(),
+            link: Some(fixed_ref),
+            fixed_ref: Some(fixed_ref),
+            fields,
+        }
+    }
+
+    fn field(column: &str, before: Option<&str>, after: Option<&str>) -> FieldChange {
+        FieldChange {
+            column: column.to_owned(),
+            before: before.map(str::to_owned),
+            after: after.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn delta_of_an_import() {
+        let changes = ChangeSet {
+            changes: vec![
+                change(ChangeKind::Insert, "sdn", "1", 36, vec![field("fixed_ref", None, Some("36"))]),
+                change(ChangeKind::Insert, "name", "100", 36, vec![field("last_name", None, Some("AEROCARIBBEAN AIRLINES"))]),
+                change(ChangeKind::Deactivation, "sdn", "2", 173, Vec::new()),
+                change(ChangeKind::Update, "sdn", "3", 306, vec![field("sanction_status", Some("INACTIVE"), Some("ACTIVE"))]),
+                change(ChangeKind::Delete, "address", "200", 306, vec![field("city", Some("HAVANA"), None)]),
+                change(ChangeKind::UpdateTopmajOnly, "name", "300", 424, Vec::new()),
+                Change {
+                    fixed_ref: None,
+                    ..change(ChangeKind::Insert, "ref_country", "11", 0, Vec::new())
+                },
+            ],
+        };
+        let delta = Delta::from_changes(&changes, DocumentType::OFAC, NaiveDate::from_ymd(2022, 11, 8));
+        assert_eq!(vec![36], delta.added);
+        assert_eq!(vec![173], delta.inactivated);
+        assert_eq!(vec![306], delta.reactivated);
+        assert_eq!(vec![306], delta.changed.keys().copied().collect::<Vec<i32>>());
+        assert_eq!(2, delta.changed[&306].len());
+        assert_eq!(vec![36, 306], delta.affected());
+        assert_eq!("OFAC_2022-11-08", delta.file_stem());
+
+        let mut buffer = Vec::new();
+        delta.write_csv(&mut buffer).unwrap();
+        let excepted = "fixed_ref;status;table;kind;key;column;before;after
+36;ADDED;;;;;;
+173;INACTIVATED;;;;;;
+306;REACTIVATED;;;;;;
+306;CHANGED;sdn;UPDATE;3;sanction_status;INACTIVE;ACTIVE
+306;CHANGED;address;DELETE;200;city;HAVANA;
+";
+        assert_eq!(excepted, String::from_utf8(buffer).unwrap());
+    }
+
+    #[test]
+    fn delta_of_a_rollback() {
+        let mut delta = Delta::from_changes(&ChangeSet::default(), DocumentType::OFAC, NaiveDate::from_ymd(2022, 11, 8));
+        assert!(delta.is_empty());
+        delta.set_rolled_back_from(NaiveDate::from_ymd(2022, 11, 10));
+        assert_eq!("OFAC_2022-11-08_rollback_from_2022-11-10", delta.file_stem());
+        assert!(serde_json::to_string(&delta).unwrap().contains(r#""rolled_back_from":"2022-11-10""#));
+    }
+}
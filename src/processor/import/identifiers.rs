@@ -0,0 +1,129 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use log::warn;
+
+use crate::document::inputs::identifier::{IdentifierError, IdentifierKind};
+use crate::document::inputs::models::distinctparty::DistinctParty;
+use crate::document::inputs::models::document::IDRegDocuments;
+use crate::document::inputs::OfacDocumentReferences;
+
+/// An identifier failing its validation
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidIdentifier {
+    pub fixed_ref: i32,
+    pub kind: IdentifierKind,
+    /// Identifier as published
+    pub value: String,
+    pub error: IdentifierError,
+}
+
+/// Identifiers of the DistinctParties and of their documents failing their validation, see [`IdentifierKind::validate`]
+#[derive(Debug, Default)]
+pub struct IdentifiersReport {
+    pub invalid: Vec<InvalidIdentifier>,
+}
+
+impl IdentifiersReport {
+    /// Validate the identifiers published in the features and the documents of a DistinctParty
+    pub fn visit_party(&mut self, party: &DistinctParty, documents: &IDRegDocuments, references: &OfacDocumentReferences) {
+        for feature in party.profile.feature.iter().flatten() {
+            let value = feature.version.detail.as_ref().and_then(|detail| detail.value.as_ref());
+            if let (Some(kind), Some(value)) = (IdentifierKind::of_feature(feature.feature_type, references), value) {
+                self.check(party.fixed_ref, kind, value);
+            }
+        }
+        for document in documents.by_identity(party.profile.identity.id) {
+            if let (Some(kind), Some(value)) = (IdentifierKind::of_document(document.type_id, references), document.registration_number.as_ref()) {
+                self.check(party.fixed_ref, kind, value);
+            }
+        }
+    }
+
+    fn check(&mut self, fixed_ref: i32, kind: IdentifierKind, value: &str) {
+        if let Err(error) = kind.validate(value) {
+            self.invalid.push(InvalidIdentifier {
+                fixed_ref,
+                kind,
+                value: value.to_owned(),
+                error,
+            });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.invalid.is_empty()
+    }
+
+    pub fn log(&self) {
+        for identifier in self.invalid.iter() {
+            warn!("Invalid {} {} in FixedRef {} : {}", identifier.kind, identifier.value, identifier.fixed_ref, identifier.error);
+        }
+    }
+
+    /// Write the invalid identifiers to `identifiers/` in `folder`
+    pub fn write(&self, folder: &Path) -> Result<PathBuf, std::io::Error> {
+        let folder = folder.join("identifiers");
+        std::fs::create_dir_all(&folder)?;
+        let path = folder.join(format!("identifiers{}.csv", Local::now().format("%Y%m%d_%H%M%S")));
+        let mut file = std::fs::File::create(&path)?;
+        self.write_in(&mut file)?;
+        Ok(path)
+    }
+
+    fn write_in<W: Write>(&self, buffer: &mut W) -> Result<(), std::io::Error> {
+        writeln!(buffer, "fixed_ref;kind;value;error")?;
+        for identifier in self.invalid.iter() {
+            writeln!(buffer, "{};{};{};{}", identifier.fixed_ref, identifier.kind, identifier.value.replace(';', " "), identifier.error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod identifiers {
+    use super::*;
+    use crate::document::inputs::builder::{NamePart, OfacDocumentBuilder, PartyKind};
+    use crate::document::inputs::known::{FeatureKind, KnownValue};
+    use crate::document::DocumentType;
+
+    #[test]
+    fn report_invalid_identifiers() {
+        let mut builder = OfacDocumentBuilder::new("", DocumentType::OFAC);
+        builder.date_of_issue(chrono::NaiveDate::from_ymd(2022, 11, 8));
+        let mut party = builder.party(15036, PartyKind::Vessel);
+        party.name(15036, true, false, &[(NamePart::VesselName, "ADRIAN DARYA 1")]);
+        party.feature_value(15037, FeatureKind::SwiftBic.id(), "HAVIGB2L");
+        party.feature_value(15038, FeatureKind::Isin.id(), "US0378331006");
+        party.document(15039, "Vessel Registration Identification", "IMO 9116412", None, None, None);
+        party.document(15040, "Vessel Registration Identification", "IMO 9116413", None, None, None);
+        party.finish();
+        let document = builder.build();
+        let mut report = IdentifiersReport::default();
+        for party in document.distinct_parties.parties.iter() {
+            report.visit_party(party, &document.documents, &document.references);
+        }
+        let excepted = vec![
+            InvalidIdentifier {
+                fixed_ref: 15036,
+                kind: IdentifierKind::Isin,
+                value: "US0378331006".to_owned(),
+                error: IdentifierError::CheckDigit,
+            },
+            InvalidIdentifier {
+                fixed_ref: 15036,
+                kind: IdentifierKind::Imo,
+                value: "IMO 9116413".to_owned(),
+                error: IdentifierError::CheckDigit,
+            },
+        ];
+        assert_eq!(excepted, report.invalid);
+        let mut buffer = Vec::new();
+        report.write_in(&mut buffer).unwrap();
+        assert_eq!(
+            "fixed_ref;kind;value;error\n15036;ISIN;US0378331006;invalid check digit\n15036;IMO;IMO 9116413;invalid check digit\n",
+            String::from_utf8(buffer).unwrap()
+        );
+    }
+}
@@ -1,5 +1,8 @@
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::Display;
+use std::io::Write;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use log::{info, warn};
@@ -8,108 +11,288 @@ use sea_orm::ActiveValue::NotSet;
 use sea_orm::{ActiveModelTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, Set, TransactionTrait};
 use tokio::sync::Mutex;
 
-use crate::db::entity::sdn::DocumentEntity;
+use chrono::{Local, NaiveDate};
+
+use self::delta::Delta;
+use self::identifiers::IdentifiersReport;
+use self::unknowns::UnknownsReport;
+use crate::db::batch::WriteBatch;
+use crate::db::changes::{Change, ChangeKind, ChangeRecorder, ChangeSet};
+use crate::db::history;
+use crate::db::ConversionErr;
+use crate::db::entity::sdn::{DocumentEntity, SdnInnerRelation};
 use crate::{
-    db::{entity::*, get_last_issued_date, OfacRefEntity},
+    db::{entity::*, OfacRefEntity},
+    document::inputs::models::distinctparty::DistinctParty,
     document::inputs::{OfacDocument, OfacDocumentReferences},
 };
 
+pub mod delta;
+pub mod identifiers;
+pub mod unknowns;
+
+/// Number of DistinctParties processed concurrently
+const SDN_BATCH_SIZE: usize = 500;
+
 #[derive(Debug)]
 pub enum ImporterErr {
     AlreadyImported(String),
+    /// The document has been rolled back, see [`check_date_of_issue`]
+    RolledBack(String),
+    /// Values not mapped by the importer and not reported by a previous import
+    NewUnknowns(String),
+    /// The DistinctParties of the document could not be read or converted
+    Document(Box<dyn Error>),
+    Db(DbErr),
+    Io(std::io::Error),
 }
 
 impl Display for ImporterErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ImporterErr::AlreadyImported(message) => write!(f, "{}", message),
+            ImporterErr::RolledBack(message) => write!(f, "{}", message),
+            ImporterErr::NewUnknowns(message) => write!(f, "{}", message),
+            ImporterErr::Document(err) => write!(f, "{}", err),
+            ImporterErr::Db(err) => write!(f, "{}", err),
+            ImporterErr::Io(err) => write!(f, "{}", err),
         }
     }
 }
 impl Error for ImporterErr {}
 
+impl From<DbErr> for ImporterErr {
+    fn from(err: DbErr) -> Self {
+        ImporterErr::Db(err)
+    }
+}
+
+impl From<std::io::Error> for ImporterErr {
+    fn from(err: std::io::Error) -> Self {
+        ImporterErr::Io(err)
+    }
+}
+
+/// A DistinctParty skipped in lenient mode
+#[derive(Debug)]
+pub struct QuarantinedParty {
+    pub fixed_ref: i32,
+    pub error: String,
+    /// Content of the DistinctParty, written to the quarantine file
+    pub party: String,
+}
+
 pub struct Importer {
     transaction_manager: Arc<Mutex<DatabaseTransaction>>,
+    /// Rows waiting to be written in the transaction
+    batch: Arc<WriteBatch>,
+    /// Changes made in the transaction, see [`Importer::take_changes`]
+    changes: Arc<ChangeRecorder>,
+    /// Skip the DistinctParties that can not be converted instead of aborting the import
+    pub lenient: bool,
+    pub quarantined: Vec<QuarantinedParty>,
+    /// Values of the DistinctParties not mapped by the importer
+    pub unknowns: UnknownsReport,
+    /// Refuse the import when unmapped values are not listed in `unknowns.txt`, see [`UnknownsReport::new_unknowns`]
+    pub fail_on_unknowns: bool,
+    /// Identifiers of the DistinctParties failing their validation
+    pub identifiers: IdentifiersReport,
+    /// The import must be rolled back with [`Importer::rollback`]
+    pub dry_run: bool,
+    /// Date of issue of the document in database when an archived document is imported again to roll the database back
+    pub rolled_back_from: Option<NaiveDate>,
+    /// FixedRef of the identities of the DistinctParties
+    identities: HashMap<i32, i32>,
+}
+
+/// Convert a DistinctParty, a party missing a value it needs (e.g. its SanctionsEntry or a location) is returned as an error
+pub fn convert_party(party: &DistinctParty, document: &OfacDocument, references: &OfacDocumentReferences) -> Result<(sdn::Model, SdnInnerRelation), ConversionErr> {
+    sdn::Model::from_ofac_document(&DocumentEntity(party, &document.locations, &document.sanction_entries), references)
+}
+
+/// A document already in database, or the last one rolled back, is only imported again when forced
+///
+/// The rolled back document is left in the data folder, it must not undo the rollback on the next regular import
+fn check_date_of_issue(stored: Option<&dateofissue::Model>, date_of_issue: NaiveDate, force: bool) -> Result<(), ImporterErr> {
+    let stored = match stored {
+        Some(stored) if !force => stored,
+        _ => return Ok(()),
+    };
+    if stored.last_document == date_of_issue {
+        warn!("This document has already been imported in the database ({:?}) to force import use -f flag to true", stored.last_document);
+        return Err(ImporterErr::AlreadyImported(
+            "This document has already been imported in the database to force import use -f flag to true".to_string(),
+        ));
+    }
+    if stored.rolled_back_from == Some(date_of_issue) {
+        warn!("The document issued on {} has been rolled back to the one issued on {}, to import it again use -f flag to true", date_of_issue, stored.last_document);
+        return Err(ImporterErr::RolledBack(format!("The document issued on {} has been rolled back, to import it again use -f flag to true", date_of_issue)));
+    }
+    Ok(())
+}
+
+/// Date of issue of the last document rolled back once the document issued on `date_of_issue` is imported
+///
+/// The date is kept until another rollback, or until the rolled back document is imported again
+fn rolled_back_marker(stored: Option<NaiveDate>, date_of_issue: NaiveDate, rolled_back_from: Option<NaiveDate>) -> Option<NaiveDate> {
+    rolled_back_from.or(stored.filter(|rolled_back| *rolled_back != date_of_issue))
 }
 
 impl Importer {
     pub async fn init(db: &DatabaseConnection) -> Importer {
         Importer {
             transaction_manager: Arc::new(Mutex::new(db.begin().await.unwrap())),
+            batch: Arc::new(WriteBatch::default()),
+            changes: Arc::new(ChangeRecorder::default()),
+            lenient: false,
+            quarantined: Vec::new(),
+            unknowns: UnknownsReport::default(),
+            fail_on_unknowns: false,
+            identifiers: IdentifiersReport::default(),
+            dry_run: false,
+            rolled_back_from: None,
+            identities: HashMap::new(),
         }
     }
 
     async fn save_date_of_issue(&self, db: &DatabaseConnection, references: &OfacDocumentReferences) -> Result<(), DbErr> {
         let lock = self.transaction_manager.lock().await;
-        if dateofissue::Entity::find_by_id(0).one(db).await?.is_some() {
-            dateofissue::ActiveModel {
-                id: Set(0),
-                last_document: Set(references.date_of_issue.to_sql_date()),
-            }
-            .save(&*lock)
-            .await?;
-            return Ok(());
-        }
+        let date_of_issue = references.date_of_issue.to_sql_date();
+        let (id, rolled_back_from) = match dateofissue::Entity::find_by_id(0).one(db).await? {
+            Some(stored) => (Set(0), rolled_back_marker(stored.rolled_back_from, date_of_issue, self.rolled_back_from)),
+            None => (NotSet, self.rolled_back_from),
+        };
         dateofissue::ActiveModel {
-            id: NotSet,
-            last_document: Set(references.date_of_issue.to_sql_date()),
+            id,
+            last_document: Set(date_of_issue),
+            rolled_back_from: Set(rolled_back_from),
         }
-        .save(db)
+        .save(&*lock)
         .await?;
         Ok(())
     }
 
     async fn save_references(&self, db: &DatabaseConnection, references: &OfacDocumentReferences) -> Result<(), DbErr> {
         info!("Saving References");
-        let ref_reference = ref_reference::Entity::find().all(db).await?;
+        let ref_reference: HashMap<i32, ref_reference::Model> = ref_reference::Entity::find().all(db).await?.into_iter().map(|model| (model.id, model)).collect();
         {
             let lock = self.transaction_manager.lock().await;
             for reference in references.detail_references.detail_references.iter() {
-                if let Some(e) = ref_reference::ActiveModel::from_ofac_document(reference, &ref_reference, references, &lock).await? {
+                if let Some(e) = ref_reference::ActiveModel::from_ofac_document(reference, &ref_reference, references, &lock, &self.changes).await? {
+                    self.changes.record(Change::of_active_model(ChangeKind::Update, &e, None));
                     e.update(&*lock).await?;
                 }
             }
-            let ref_type = ref_type::Entity::find().all(db).await?;
+            let ref_type: HashMap<i32, ref_type::Model> = ref_type::Entity::find().all(db).await?.into_iter().map(|model| (model.id, model)).collect();
             for reftype in references.party_sub_type_values.values.iter() {
-                if let Some(e) = ref_type::ActiveModel::from_ofac_document(reftype, &ref_type, references, &lock).await? {
+                if let Some(e) = ref_type::ActiveModel::from_ofac_document(reftype, &ref_type, references, &lock, &self.changes).await? {
+                    self.changes.record(Change::of_active_model(ChangeKind::Update, &e, None));
                     e.update(&*lock).await?;
                 }
             }
-            let ref_country = ref_country::Entity::find().all(db).await?;
+            let ref_country: HashMap<i32, ref_country::Model> = ref_country::Entity::find().all(db).await?.into_iter().map(|model| (model.id, model)).collect();
             for refcountry in references.area_codes.iter() {
-                if let Some(e) = ref_country::ActiveModel::from_ofac_document(refcountry, &ref_country, references, &lock).await? {
+                if let Some(e) = ref_country::ActiveModel::from_ofac_document(refcountry, &ref_country, references, &lock, &self.changes).await? {
+                    self.changes.record(Change::of_active_model(ChangeKind::Update, &e, None));
                     e.update(&*lock).await?;
                 }
             }
-            let ref_document = ref_document::Entity::find().all(db).await?;
+            let ref_document: HashMap<i32, ref_document::Model> = ref_document::Entity::find().all(db).await?.into_iter().map(|model| (model.id, model)).collect();
             for refdocument in references.reg_doc_types.reg_doc_types.iter() {
-                if let Some(e) = ref_document::ActiveModel::from_ofac_document(refdocument, &ref_document, references, &lock).await? {
+                if let Some(e) = ref_document::ActiveModel::from_ofac_document(refdocument, &ref_document, references, &lock, &self.changes).await? {
+                    self.changes.record(Change::of_active_model(ChangeKind::Update, &e, None));
                     e.update(&*lock).await?;
                 }
             }
-            let ref_feature = ref_feature::Entity::find().all(db).await?;
+            let ref_feature: HashMap<i32, ref_feature::Model> = ref_feature::Entity::find().all(db).await?.into_iter().map(|model| (model.id, model)).collect();
             for reffeature in references.feature_types.types.iter() {
-                if let Some(e) = ref_feature::ActiveModel::from_ofac_document(reffeature, &ref_feature, references, &lock).await? {
+                if let Some(e) = ref_feature::ActiveModel::from_ofac_document(reffeature, &ref_feature, references, &lock, &self.changes).await? {
+                    self.changes.record(Change::of_active_model(ChangeKind::Update, &e, None));
                     e.update(&*lock).await?;
                 }
             }
+            let ref_value: HashMap<(String, i32), ref_value::Model> = ref_value::Entity::find().all(db).await?.into_iter().map(|model| ((model.reference_set.clone(), model.id), model)).collect();
+            for value_set in references.value_sets.iter() {
+                for value in value_set.values.iter() {
+                    if let Some(e) = ref_value::ActiveModel::from_ofac_document(&(value_set.name.as_str(), value), &ref_value, references, &lock, &self.changes).await? {
+                        self.changes.record(Change::of_active_model(ChangeKind::Update, &e, None));
+                        e.update(&*lock).await?;
+                    }
+                }
+            }
         }
         info!("References saved");
         Ok(())
     }
 
-    async fn save_sdns(&self, db: &DatabaseConnection, references: &OfacDocumentReferences, document: &OfacDocument) -> Result<(), DbErr> {
+    /// Convert a DistinctParty, in lenient mode a party that can not be converted is quarantined and `None` is returned
+    fn convert(&mut self, party: &DistinctParty, document: &OfacDocument, references: &OfacDocumentReferences) -> Result<Option<(sdn::Model, SdnInnerRelation)>, Box<dyn Error>> {
+        self.unknowns.visit_party(party, &document.locations, references);
+        self.identifiers.visit_party(party, &document.documents, references);
+        self.identities.insert(party.profile.identity.id, party.fixed_ref);
+        if !self.lenient {
+            return Ok(Some(convert_party(party, document, references)?));
+        }
+        match convert_party(party, document, references) {
+            Ok(sdn) => Ok(Some(sdn)),
+            Err(error) => {
+                warn!("DistinctParty {} quarantined : {}", party.fixed_ref, error);
+                self.quarantined.push(QuarantinedParty {
+                    fixed_ref: party.fixed_ref,
+                    error: error.to_string(),
+                    party: format!("{:#?}", party),
+                });
+                Ok(None)
+            }
+        }
+    }
+
+    /// Quarantined parties are kept as they are in the database, they must not be set INACTIVE
+    async fn save_sdns(&mut self, db: &DatabaseConnection, references: &OfacDocumentReferences, document: &OfacDocument) -> Result<(), ImporterErr> {
         info!("Saving DistinctParties");
-        let mut sdns = Vec::new();
-        for distinct_party in document.distinct_parties.parties.iter() {
-            sdns.push(sdn::Model::from_ofac_document(&DocumentEntity(distinct_party, &document.locations, &document.sanction_entries), references)?);
+        let mut saved_sdns = Vec::new();
+        if document.is_streamed {
+            let (mut parties, reading) = document.stream_distinct_parties(SDN_BATCH_SIZE).map_err(ImporterErr::Document)?;
+            let mut sdns = Vec::with_capacity(SDN_BATCH_SIZE);
+            while let Some(distinct_party) = parties.recv().await {
+                sdns.extend(self.convert(&distinct_party, document, references).map_err(ImporterErr::Document)?);
+                if sdns.len() == SDN_BATCH_SIZE {
+                    saved_sdns.append(&mut sdn::ActiveModel::process_entities(&sdns, db.clone(), &self.transaction_manager, &self.batch, &self.changes).await?);
+                    sdns.clear();
+                    info!("{} DistinctParties processed", saved_sdns.len());
+                }
+            }
+            saved_sdns.append(&mut sdn::ActiveModel::process_entities(&sdns, db.clone(), &self.transaction_manager, &self.batch, &self.changes).await?);
+            // A partially read document must not set the unread parties INACTIVE
+            reading.await.map_err(|err| ImporterErr::Document(err.into()))?.map_err(|err| ImporterErr::Document(err.into()))?;
+        } else {
+            let mut sdns = Vec::new();
+            for distinct_party in document.distinct_parties.parties.iter() {
+                sdns.extend(self.convert(distinct_party, document, references).map_err(ImporterErr::Document)?);
+            }
+            info!("DistinctParties parsed, found {} entities", sdns.len());
+            for batch in sdns.chunks(SDN_BATCH_SIZE) {
+                saved_sdns.append(&mut sdn::ActiveModel::process_entities(batch, db.clone(), &self.transaction_manager, &self.batch, &self.changes).await?);
+            }
         }
-        info!("DistinctParties parsed, found {} entities", sdns.len());
-        sdn::ActiveModel::process_entities(&sdns, db.clone(), &self.transaction_manager).await?;
-        info!("DistinctParties saved");
+        let quarantined: Vec<i32> = self.quarantined.iter().map(|party| party.fixed_ref).collect();
+        sdn::set_sanction_inactive(Arc::clone(&self.transaction_manager), &self.changes, &[saved_sdns.as_slice(), quarantined.as_slice()].concat()).await?;
+        info!("DistinctParties saved, found {} entities", saved_sdns.len());
         Ok(())
     }
 
+    /// Write the quarantined parties and their error to `quarantine/` in the data folder
+    fn write_quarantine(&self, document: &OfacDocument) -> Result<PathBuf, std::io::Error> {
+        let folder = document.root_folder.join("quarantine");
+        std::fs::create_dir_all(&folder)?;
+        let path = folder.join(format!("quarantine{}.txt", Local::now().format("%Y%m%d_%H%M%S")));
+        let mut file = std::fs::File::create(&path)?;
+        for party in self.quarantined.iter() {
+            writeln!(file, "FixedRef {} : {}\n{}\n", party.fixed_ref, party.error, party.party)?;
+        }
+        Ok(path)
+    }
+
     async fn save_documents(&self, db: &DatabaseConnection, references: &OfacDocumentReferences, document: &OfacDocument) -> Result<(), DbErr> {
         info!("Saving Documents");
         let mut documents = Vec::new();
@@ -117,7 +300,7 @@ impl Importer {
             documents.push(document::Model::from_ofac_document(document, references));
         }
         info!("Documents parsed, found {} entities", documents.len());
-        document::ActiveModel::process_entities(documents.clone(), db, &self.transaction_manager).await?;
+        document::ActiveModel::process_entities(documents.clone(), db, &self.transaction_manager, &self.batch, &self.changes).await?;
         info!("Documents saved");
         Ok(())
     }
@@ -129,7 +312,7 @@ impl Importer {
             relations.push(relation::Model::from_ofac_document(relationshipdoc));
         }
         info!("Relationships parsed, found {} entities", relations.len());
-        relation::ActiveModel::process_entities(relations.clone(), db, &self.transaction_manager).await?;
+        relation::ActiveModel::process_entities(relations.clone(), db, &self.transaction_manager, &self.batch, &self.changes).await?;
         info!("Relationships saved");
         Ok(())
     }
@@ -144,25 +327,134 @@ impl Importer {
             warn!("This document has not be loaded correctly");
             return Ok(());
         }
-        let last_date_of_issue = get_last_issued_date(db).await;
-        if last_date_of_issue == document.references.date_of_issue.to_sql_date() && !force {
-            warn!("This document has already been imported in the database ({:?}) to force import use -f flag to true", last_date_of_issue);
-            return Err(ImporterErr::AlreadyImported(
-                "This document has already been imported in the database to force import use -f flag to true".to_string(),
-            ));
+        let stored = dateofissue::Entity::find_by_id(0).one(db).await?;
+        check_date_of_issue(stored.as_ref(), document.references.date_of_issue.to_sql_date(), force)?;
+        if let Some(rolled_back_from) = self.rolled_back_from {
+            warn!("Rolling the database back from the document issued on {} to the one issued on {}", rolled_back_from, document.references.date_of_issue.to_sql_date());
+        }
+        self.save_date_of_issue(db, &document.references).await?;
+        self.save_references(db, &document.references).await?;
+        self.save_sdns(db, &document.references, document).await?;
+        if self.fail_on_unknowns {
+            let new_unknowns: Vec<String> = self.unknowns.new_unknowns(&document.root_folder).iter().map(|value| value.to_string()).collect();
+            if !new_unknowns.is_empty() {
+                self.unknowns.log();
+                warn!("New unmapped values found : {}", new_unknowns.join(", "));
+                return Err(ImporterErr::NewUnknowns(format!("New unmapped values found : {}", new_unknowns.join(", "))));
+            }
+        }
+        if !self.quarantined.is_empty() {
+            let path = self.write_quarantine(document)?;
+            warn!("{} DistinctParties quarantined, see {}", self.quarantined.len(), path.to_string_lossy());
         }
-        self.save_date_of_issue(db, &document.references).await.unwrap();
-        self.save_references(db, &document.references).await.unwrap();
-        self.save_sdns(db, &document.references, document).await.unwrap();
-        self.save_documents(db, &document.references, document).await.unwrap();
-        self.save_relationships(db, document).await.unwrap();
+        if !self.identifiers.is_empty() {
+            let path = self.identifiers.write(&document.root_folder)?;
+            warn!("{} invalid identifiers, see {}", self.identifiers.invalid.len(), path.to_string_lossy());
+        }
+        self.save_documents(db, &document.references, document).await?;
+        self.save_relationships(db, document).await?;
         info!("Ofac Document Successfully saved to database");
         Ok(())
     }
 
+    /// Changes made by the import, to be taken before [`Importer::commit`]
+    pub fn take_changes(&self) -> ChangeSet {
+        self.recorded_changes()
+    }
+
+    /// Save the history of the sdns of `delta` in the transaction of the import, so that it is committed with the import
+    ///
+    /// A rollback is saved as a new revision of the document it replaces, see [`history::save_snapshots`]
+    pub async fn save_history(&self, date_of_issue: NaiveDate, delta: &Delta) -> Result<usize, DbErr> {
+        let lock = self.transaction_manager.lock().await;
+        history::save_snapshots(&*lock, date_of_issue, self.rolled_back_from, &delta.affected(), &delta.inactivated).await
+    }
+
+    /// Commit the import
     pub async fn commit(self) -> Result<(), DbErr> {
         Arc::try_unwrap(self.transaction_manager).unwrap().into_inner().commit().await?;
         info!("Main Transaction succesfully commited");
         Ok(())
     }
+
+    /// Roll the import back, returns the changes it would have made
+    pub async fn rollback(self) -> Result<ChangeSet, DbErr> {
+        let changes = self.recorded_changes();
+        Arc::try_unwrap(self.transaction_manager).unwrap().into_inner().rollback().await?;
+        info!("Main Transaction rolled back");
+        Ok(changes)
+    }
+
+    fn recorded_changes(&self) -> ChangeSet {
+        let mut changes = self.changes.take();
+        changes.resolve_documents(&self.identities);
+        changes
+    }
+}
+
+#[cfg(test)]
+mod import {
+    use super::*;
+    use crate::document::inputs::builder::{NamePart, OfacDocumentBuilder, PartyKind};
+    use crate::document::DocumentType;
+
+    fn builder_with_party() -> OfacDocument {
+        let mut builder = OfacDocumentBuilder::new("", DocumentType::OFAC);
+        builder.date_of_issue(chrono::NaiveDate::from_ymd(2022, 11, 8));
+        let mut party = builder.party(36, PartyKind::Entity);
+        party.name(36, true, false, &[(NamePart::EntityName, "AEROCARIBBEAN AIRLINES")]);
+        party.finish();
+        builder.build()
+    }
+
+    #[test]
+    fn rolled_back_document_is_not_imported_again() {
+        let rolled_back = NaiveDate::from_ymd(2022, 11, 8);
+        let restored = NaiveDate::from_ymd(2022, 11, 7);
+        let mut stored = dateofissue::Model {
+            id: 0,
+            last_document: rolled_back,
+            rolled_back_from: None,
+        };
+        // Rollback to the archived document
+        assert!(check_date_of_issue(Some(&stored), restored, true).is_ok());
+        stored.rolled_back_from = rolled_back_marker(stored.rolled_back_from, restored, Some(rolled_back));
+        stored.last_document = restored;
+        assert_eq!(Some(rolled_back), stored.rolled_back_from);
+
+        // The next regular import finds the rolled back document still in the data folder
+        assert!(matches!(check_date_of_issue(Some(&stored), rolled_back, false), Err(ImporterErr::RolledBack(_))));
+        assert!(matches!(check_date_of_issue(Some(&stored), restored, false), Err(ImporterErr::AlreadyImported(_))));
+
+        // A new document is imported and the rolled back one is still refused
+        let next = NaiveDate::from_ymd(2022, 11, 9);
+        assert!(check_date_of_issue(Some(&stored), next, false).is_ok());
+        stored.rolled_back_from = rolled_back_marker(stored.rolled_back_from, next, None);
+        stored.last_document = next;
+        assert!(matches!(check_date_of_issue(Some(&stored), rolled_back, false), Err(ImporterErr::RolledBack(_))));
+
+        // Unless forced
+        assert!(check_date_of_issue(Some(&stored), rolled_back, true).is_ok());
+        assert_eq!(None, rolled_back_marker(stored.rolled_back_from, rolled_back, None));
+        assert!(check_date_of_issue(None, rolled_back, false).is_ok());
+    }
+
+    #[test]
+    fn convert_party_returns_errors() {
+        let mut document = builder_with_party();
+        let party = &document.distinct_parties.parties[0];
+        assert_eq!(36, convert_party(party, &document, &document.references).unwrap().0.fixed_ref);
+
+        document.sanction_entries.entries.clear();
+        document.build_indexes();
+        let party = &document.distinct_parties.parties[0];
+        let error = convert_party(party, &document, &document.references).unwrap_err();
+        assert_eq!(ConversionErr::MissingSanctionsEntry(36), error);
+
+        let mut document = builder_with_party();
+        let entry_id = document.sanction_entries.entries[0].id;
+        document.sanction_entries.entries[0].events.clear();
+        let party = &document.distinct_parties.parties[0];
+        assert_eq!(Err(ConversionErr::MissingSanctionsEvent(entry_id)), convert_party(party, &document, &document.references).map(|_| ()));
+    }
 }
@@ -0,0 +1,172 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+
+use crate::db::entity::digital_currency_address;
+use crate::document::inputs::known::{FeatureKind, KnownValue, LocPartKind, NamePart};
+use crate::document::inputs::models::distinctparty::DistinctParty;
+use crate::document::inputs::models::location::Locations;
+use crate::document::inputs::OfacDocumentReferences;
+
+/// Number of FixedRefs kept as examples for each unknown value
+const EXAMPLES: usize = 5;
+
+/// File of the data folder listing the unknown values already reported, only the others are new
+const KNOWN_UNKNOWNS_FILE: &str = "unknowns.txt";
+
+/// A reference value the importer does not map
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UnknownValue {
+    /// ReferenceValueSet of the value (e.g. `FeatureType`)
+    pub set: &'static str,
+    pub id: i32,
+}
+
+impl Display for UnknownValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.set, self.id)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UnknownOccurrences {
+    /// Name published in the ReferenceValueSets, if any
+    pub name: Option<String>,
+    pub count: usize,
+    /// First FixedRefs the value has been found in
+    pub fixed_refs: Vec<i32>,
+}
+
+/// Unmapped FeatureTypes (see [`FeatureMapping`](crate::db::mapping::FeatureMapping)), NamePartTypes and LocPartTypes found while importing the DistinctParties
+#[derive(Debug, Default)]
+pub struct UnknownsReport {
+    pub unknowns: BTreeMap<UnknownValue, UnknownOccurrences>,
+}
+
+impl UnknownsReport {
+    /// Collect the values of a DistinctParty that are not known by the importer
+    pub fn visit_party(&mut self, party: &DistinctParty, locations: &Locations, references: &OfacDocumentReferences) {
+        let profile = &party.profile;
+        for group in profile.identity.name_part_groups.master_name_part_group.iter() {
+            self.check::<NamePart>(group.name_part_group.name_part_type_id, party.fixed_ref, references);
+        }
+        for feature in profile.feature.iter().flatten() {
+            // Digital currency addresses are stored whatever their currency
+            if references.feature_mapping().find(feature.feature_type, references).is_none() && digital_currency_address::currency(feature.feature_type, references).is_none() {
+                self.add(FeatureKind::SET, feature.feature_type, party.fixed_ref, references);
+            }
+            let location = feature.version.location.as_ref().and_then(|location| locations.get(location.id));
+            for part in location.and_then(|location| location.location_parts.as_ref()).into_iter().flatten() {
+                self.check::<LocPartKind>(part.id, party.fixed_ref, references);
+            }
+        }
+    }
+
+    fn check<T: KnownValue>(&mut self, id: i32, fixed_ref: i32, references: &OfacDocumentReferences) {
+        if references.resolve::<T>(id).is_none() {
+            self.add(T::SET, id, fixed_ref, references);
+        }
+    }
+
+    fn add(&mut self, set: &'static str, id: i32, fixed_ref: i32, references: &OfacDocumentReferences) {
+        let occurrences = self.unknowns.entry(UnknownValue { set, id }).or_default();
+        if occurrences.name.is_none() {
+            occurrences.name = references.value_name(set, id).map(str::to_owned);
+        }
+        occurrences.count += 1;
+        if occurrences.fixed_refs.len() < EXAMPLES && !occurrences.fixed_refs.contains(&fixed_ref) {
+            occurrences.fixed_refs.push(fixed_ref);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unknowns.is_empty()
+    }
+
+    pub fn log(&self) {
+        for (value, occurrences) in self.unknowns.iter() {
+            warn!(
+                "Unmapped {} ({}) found {} times, e.g. in FixedRefs {:?}",
+                value,
+                occurrences.name.as_deref().unwrap_or("no name"),
+                occurrences.count,
+                occurrences.fixed_refs
+            );
+        }
+    }
+
+    /// Unknown values not listed in the `unknowns.txt` file of `folder`
+    pub fn new_unknowns(&self, folder: &Path) -> Vec<&UnknownValue> {
+        let known = read_known_unknowns(folder);
+        self.unknowns.keys().filter(|value| !known.contains(&value.to_string())).collect()
+    }
+
+    /// Add the unknown values to the `unknowns.txt` file of `folder`, they are no longer new for the next imports
+    pub fn save(&self, folder: &Path) -> Result<PathBuf, std::io::Error> {
+        let mut known = read_known_unknowns(folder);
+        known.extend(self.unknowns.keys().map(|value| value.to_string()));
+        let path = folder.join(KNOWN_UNKNOWNS_FILE);
+        let mut file = std::fs::File::create(&path)?;
+        for value in known {
+            writeln!(file, "{}", value)?;
+        }
+        Ok(path)
+    }
+}
+
+fn read_known_unknowns(folder: &Path) -> BTreeSet<String> {
+    match std::fs::read_to_string(folder.join(KNOWN_UNKNOWNS_FILE)) {
+        Ok(content) => content.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_owned).collect(),
+        Err(_) => BTreeSet::new(),
+    }
+}
+
+#[cfg(test)]
+mod unknowns {
+    use super::*;
+    use crate::document::inputs::builder::{Address, OfacDocumentBuilder, PartyKind};
+    use crate::document::DocumentType;
+
+    #[test]
+    fn collect_unmapped_values() {
+        let mut builder = OfacDocumentBuilder::new("", DocumentType::OFAC);
+        builder.date_of_issue(chrono::NaiveDate::from_ymd(2022, 11, 8));
+        for fixed_ref in [36, 173] {
+            let mut party = builder.party(fixed_ref, PartyKind::Entity);
+            party.name(fixed_ref, true, false, &[(NamePart::EntityName, "AEROCARIBBEAN AIRLINES")]);
+            party.feature_value(fixed_ref, 99999, "NEW COIN ADDRESS");
+            party.feature_value(fixed_ref + 1, FeatureKind::Website.id(), "WWW.EXAMPLE.COM");
+            party.feature_location(fixed_ref + 2, FeatureKind::Location.id(), &Address { city: Some("HAVANA".to_owned()), ..Default::default() });
+            party.finish();
+        }
+        let document = builder.build();
+        let mut report = UnknownsReport::default();
+        for party in document.distinct_parties.parties.iter() {
+            report.visit_party(party, &document.locations, &document.references);
+        }
+        let excepted = UnknownOccurrences {
+            name: None,
+            count: 2,
+            fixed_refs: vec![36, 173],
+        };
+        assert_eq!(1, report.unknowns.len());
+        assert_eq!(Some(&excepted), report.unknowns.get(&UnknownValue { set: "FeatureType", id: 99999 }));
+    }
+
+    #[test]
+    fn known_unknowns_are_not_new() {
+        let folder = std::env::temp_dir().join(format!("ofacxmlagent_unknowns_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let mut report = UnknownsReport::default();
+        report.unknowns.insert(UnknownValue { set: "FeatureType", id: 99999 }, UnknownOccurrences::default());
+        assert_eq!(1, report.new_unknowns(&folder).len());
+        report.save(&folder).unwrap();
+        assert!(report.new_unknowns(&folder).is_empty());
+        report.unknowns.insert(UnknownValue { set: "LocPartType", id: 1 }, UnknownOccurrences::default());
+        assert_eq!(vec![&UnknownValue { set: "LocPartType", id: 1 }], report.new_unknowns(&folder));
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+}